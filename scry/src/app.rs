@@ -3,14 +3,23 @@ use eframe::{egui, epi};
 // use eframe::egui::Ui;
 use egui::plot::{Legend, Line, Plot, Value, Values, Text};
 use egui::Align2;
-use log::debug;
+use log::warn;
 use rfd::FileDialog;
 use std::collections::HashMap;
 use std::error::Error;
-use tdms::{DataTypeVec, TdmsFile};
+use std::sync::Arc;
+use tdms::{CachedTdmsFile, ChannelHandle, DataTypeVec, NonFinite, TdmsFile};
+
+/// Channels estimated to take more memory than this to load are plotted as an envelope
+const MAX_PLOT_LOAD_BYTES: u64 = 256 * 1024 * 1024;
+/// Buckets in the envelope of a channel too large to load
+const ENVELOPE_BUCKETS: usize = 10_000;
+/// Loaded channels are kept for replotting up to this many bytes, the least recently
+/// plotted are dropped first
+const PLOT_CACHE_BYTES: u64 = 1024 * 1024 * 1024;
 
 pub struct ChannelState {
-    name: String,
+    handle: ChannelHandle,
     selected: bool,
 }
 
@@ -41,9 +50,11 @@ pub struct ChannelState {
 
 pub struct ScryApp {
     // Example stuff:
-    file_handle: Option<TdmsFile>,
+    file_handle: Option<CachedTdmsFile>,
     channel_state: Vec<ChannelState>,
-    cached_data: HashMap<String, DataTypeVec>,
+    cached_data: HashMap<String, Arc<DataTypeVec>>,
+    // Why the last file picked couldn't be opened, shown until another is
+    open_error: Option<String>,
 }
 
 impl Default for ScryApp {
@@ -52,6 +63,7 @@ impl Default for ScryApp {
             file_handle: None,
             channel_state: Vec::new(),
             cached_data: HashMap::new(),
+            open_error: None,
         }
     }
 }
@@ -60,18 +72,23 @@ impl Default for ScryApp {
 impl ScryApp {
     fn open_dialog(&mut self) {
         if let Some(path) = FileDialog::new().pick_file() {
-            let tdms_file = TdmsFile::open(&path).unwrap();
-            //println!("{:?}", tdms_file.tdms_map.all_objects);
-            self.file_handle = Some(tdms_file)
+            match TdmsFile::open(&path) {
+                Ok(tdms_file) => {
+                    self.file_handle = Some(CachedTdmsFile::new(tdms_file, PLOT_CACHE_BYTES));
+                    self.open_error = None;
+                    self.populate_channels();
+                }
+                Err(err) => {
+                    self.open_error = Some(format!("Couldn't open {}: {}", path.display(), err))
+                }
+            }
         }
-
-        self.populate_channels();
     }
 
     fn populate_channels(&mut self) {
-        for channel in self.file_handle.as_ref().expect("No chans").data_objects() {
+        for handle in self.file_handle.as_ref().expect("No chans").file().channels() {
             self.channel_state.push(ChannelState {
-                name: channel.to_string(),
+                handle,
                 selected: false,
             });
         }
@@ -81,19 +98,46 @@ impl ScryApp {
         let mut out_lines: Vec<Line> = Vec::new();
 
         for (name, data) in self.cached_data.iter() {
-            let double_data = Vec::<f64>::try_from(data.clone()).expect("Unimplemented datatype");
-            let iter = double_data.iter().step_by(1);
-            let vecy = (0..iter.len()).zip(iter).map(|(i, val)| {
-                let x = i as f64;
-                Value::new(x, *val)
-            });
-            out_lines.push(Line::new(Values::from_values_iter(vecy.clone())).name(name))
+            // Plots can't draw NaN or infinite values, so the line is broken around them into
+            // runs of finite values, which share the channel's legend entry
+            let mut run: Vec<Value> = Vec::new();
+            let values = data.to_f64_cleaned(NonFinite::Gap);
+            for (i, val) in values.into_iter().enumerate() {
+                match val {
+                    Some(val) => run.push(Value::new(i as f64, val)),
+                    None if run.is_empty() => {}
+                    None => out_lines.push(
+                        Line::new(Values::from_values(std::mem::take(&mut run))).name(name),
+                    ),
+                }
+            }
+            if !run.is_empty() {
+                out_lines.push(Line::new(Values::from_values(run)).name(name))
+            }
         }
 
         Some(out_lines)
     }
 }
 
+/// Load a channel to plot, from the cache if it was plotted before. Channels too large to load
+/// whole are decimated to the min then max of each bucket of their envelope.
+fn load_for_plot(
+    handle: &ChannelHandle,
+    file: &CachedTdmsFile,
+) -> tdms::Result<Arc<DataTypeVec>> {
+    if file.file().estimated_load_size(handle)? > MAX_PLOT_LOAD_BYTES {
+        let envelope = file.file().load_envelope(handle, ENVELOPE_BUCKETS)?;
+        return Ok(Arc::new(DataTypeVec::Double(
+            envelope
+                .into_iter()
+                .flat_map(|(min, max)| [min, max])
+                .collect(),
+        )));
+    }
+    file.get(handle)
+}
+
 impl epi::App for ScryApp {
     fn name(&self) -> &str {
         "Scry TDMS Reader"
@@ -122,6 +166,9 @@ impl epi::App for ScryApp {
                 if ui.button("Load File").clicked() {
                     self.open_dialog()
                 }
+                if let Some(err) = &self.open_error {
+                    ui.colored_label(egui::Color32::RED, err);
+                }
                 let scroll_area = ScrollArea::new([false, true]);
 
                 let (_current_scroll, _max_scroll) = scroll_area
@@ -129,23 +176,26 @@ impl epi::App for ScryApp {
                         if self.channel_state.len() > 0 {
                             for channel in self.channel_state.iter_mut() {
                                 ui.horizontal(|ui| {
-                                    ui.label(channel.name.clone().replace("\n", " "));
+                                    ui.label(channel.handle.to_string());
                                     if ui.checkbox(&mut channel.selected, "").changed() {
                                         if channel.selected {
-                                            let result = self
-                                                .file_handle
-                                                .as_mut()
-                                                .unwrap()
-                                                .load_data(&channel.name);
+                                            let result = load_for_plot(
+                                                &channel.handle,
+                                                self.file_handle.as_ref().unwrap(),
+                                            );
                                             match result {
                                                 Ok(data) => {
                                                     self.cached_data
-                                                        .insert(channel.name.clone(), data.clone());
+                                                        .insert(channel.handle.to_string(), data);
                                                 }
-                                                Err(err) => println!("{}", err),
+                                                Err(err) => warn!(
+                                                    "Couldn't load {} to plot: {}",
+                                                    channel.handle, err
+                                                ),
                                             }
                                         } else {
-                                            self.cached_data.remove_entry(&channel.name);
+                                            self.cached_data
+                                                .remove_entry(&channel.handle.to_string());
                                         }
                                     }
                                 });
@@ -0,0 +1,80 @@
+/*
+ * Smoke test for the C bindings. Build the library with `cargo build -p tdms-ffi --release`
+ * then compile and run from the tdms-ffi directory:
+ *
+ *   cc c/smoke.c -Iinclude -L../target/release -ltdms_ffi -o smoke
+ *   LD_LIBRARY_PATH=../target/release ./smoke path/to/file.tdms
+ *
+ * Exits non-zero if any check fails.
+ */
+#include <stdio.h>
+#include <stdlib.h>
+
+#include "tdms.h"
+
+int main(int argc, char **argv) {
+    if (argc < 2) {
+        fprintf(stderr, "usage: %s <file.tdms>\n", argv[0]);
+        return 2;
+    }
+
+    /* Opening a missing file must fail cleanly with a message */
+    if (tdms_open("does/not/exist.tdms") != NULL || tdms_last_error_message() == NULL) {
+        fprintf(stderr, "expected open of missing file to fail\n");
+        return 1;
+    }
+
+    TdmsHandle *handle = tdms_open(argv[1]);
+    if (handle == NULL) {
+        fprintf(stderr, "open failed: %s\n", tdms_last_error_message());
+        return 1;
+    }
+
+    int64_t count = tdms_channel_count(handle);
+    if (count < 0) {
+        fprintf(stderr, "channel count failed: %s\n", tdms_last_error_message());
+        tdms_close(handle);
+        return 1;
+    }
+    printf("channels: %lld\n", (long long)count);
+
+    for (int64_t i = 0; i < count; i++) {
+        char name[256];
+        if (tdms_channel_name(handle, (uintptr_t)i, name, sizeof(name)) < 0) {
+            fprintf(stderr, "channel name failed: %s\n", tdms_last_error_message());
+            tdms_close(handle);
+            return 1;
+        }
+
+        int64_t len = tdms_read_f64(handle, name, NULL, 0);
+        if (len < 0) {
+            printf("  %s: error %lld, %s\n", name, (long long)len, tdms_last_error_message());
+            continue;
+        }
+
+        double *values = malloc((size_t)len * sizeof(double) + 1);
+        int64_t written = tdms_read_f64(handle, name, values, (uintptr_t)len);
+        if (written != len) {
+            fprintf(stderr, "expected %lld values, got %lld\n", (long long)len, (long long)written);
+            free(values);
+            tdms_close(handle);
+            return 1;
+        }
+        printf("  %s: %lld values", name, (long long)written);
+        if (written > 0) {
+            printf(", first %g", values[0]);
+        }
+        printf("\n");
+        free(values);
+    }
+
+    /* An out of range index is an error, not a crash */
+    if (tdms_channel_name(handle, (uintptr_t)count, NULL, 0) != TDMS_ERR_INDEX) {
+        fprintf(stderr, "expected index error\n");
+        tdms_close(handle);
+        return 1;
+    }
+
+    tdms_close(handle);
+    return 0;
+}
@@ -0,0 +1,103 @@
+#ifndef TDMS_FFI_H
+#define TDMS_FFI_H
+
+/* Generated with cbindgen, do not edit by hand. Regenerate with: cbindgen --config cbindgen.toml --output include/tdms.h */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/*
+ A null pointer was passed for a required argument
+ */
+#define TDMS_ERR_NULL_ARGUMENT -1
+
+/*
+ A string argument was not valid UTF-8
+ */
+#define TDMS_ERR_INVALID_UTF8 -2
+
+/*
+ The library returned an error while opening or reading the file
+ */
+#define TDMS_ERR_READ -3
+
+/*
+ The requested channel index is out of range
+ */
+#define TDMS_ERR_INDEX -4
+
+/*
+ A panic was caught at the boundary
+ */
+#define TDMS_ERR_PANIC -5
+
+/*
+ Opaque handle to an open TDMS file
+ */
+typedef struct TdmsHandle TdmsHandle;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/*
+ Open a TDMS file. Returns NULL on failure, see `tdms_last_error_message`.
+ The returned handle must be released with `tdms_close`.
+
+ # Safety
+ `path` must be a valid nul terminated string.
+ */
+struct TdmsHandle *tdms_open(const char *path);
+
+/*
+ Returns the number of channels containing data, or a negative error code.
+
+ # Safety
+ `handle` must be a handle returned by `tdms_open` that has not been closed.
+ */
+int64_t tdms_channel_count(struct TdmsHandle *handle);
+
+/*
+ Copy the path of the channel at `index` into `buf` as a nul terminated string, truncating
+ if `len` is too small. Returns the full length of the path excluding the nul terminator,
+ so a return value >= `len` indicates truncation. `buf` may be NULL to query the length.
+
+ # Safety
+ `handle` must be a handle returned by `tdms_open` that has not been closed, and `buf` must
+ be NULL or point to at least `len` writable bytes.
+ */
+int64_t tdms_channel_name(struct TdmsHandle *handle, uintptr_t index, char *buf, uintptr_t len);
+
+/*
+ Read a channel converted to doubles into `out`, writing at most `capacity` values.
+ Returns the number of values written, or a negative error code, `TDMS_ERR_READ` for
+ channels of strings or timestamps. If `out` is NULL the number of values in the channel is
+ returned without writing anything.
+
+ # Safety
+ `handle` must be a handle returned by `tdms_open` that has not been closed, `path` must be
+ a valid nul terminated string and `out` must be NULL or point to `capacity` doubles.
+ */
+int64_t tdms_read_f64(struct TdmsHandle *handle, const char *path, double *out, uintptr_t capacity);
+
+/*
+ Returns a description of the last error on this thread, or NULL if the last call succeeded.
+ The string is owned by the library and is valid until the next call on this thread.
+ */
+const char *tdms_last_error_message(void);
+
+/*
+ Release a handle returned by `tdms_open`. Passing NULL is a no-op.
+
+ # Safety
+ `handle` must be NULL or a handle returned by `tdms_open` that has not already been closed.
+ */
+void tdms_close(struct TdmsHandle *handle);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* TDMS_FFI_H */
@@ -0,0 +1,211 @@
+//! C bindings for the tdms library.
+//!
+//! Memory ownership rules:
+//! - A `TdmsHandle` returned by `tdms_open` is owned by the caller and must be released with
+//!   `tdms_close`, exactly once.
+//! - Buffers passed into `tdms_channel_name` and `tdms_read_f64` are owned by the caller, the
+//!   library only writes into them and never keeps a reference.
+//! - The string returned by `tdms_last_error_message` is owned by the library. It remains valid
+//!   until the next call into the library on the same thread and must not be freed.
+//!
+//! Functions returning `int64_t` return a negative error code on failure, in which case
+//! `tdms_last_error_message` describes the failure. No panic crosses the boundary, they are
+//! caught and reported as `TDMS_ERR_PANIC`.
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::Path;
+use std::ptr;
+
+use tdms::TdmsFile;
+
+/// A null pointer was passed for a required argument
+pub const TDMS_ERR_NULL_ARGUMENT: i64 = -1;
+/// A string argument was not valid UTF-8
+pub const TDMS_ERR_INVALID_UTF8: i64 = -2;
+/// The library returned an error while opening or reading the file
+pub const TDMS_ERR_READ: i64 = -3;
+/// The requested channel index is out of range
+pub const TDMS_ERR_INDEX: i64 = -4;
+/// A panic was caught at the boundary
+pub const TDMS_ERR_PANIC: i64 = -5;
+
+/// Opaque handle to an open TDMS file
+pub struct TdmsHandle {
+    file: TdmsFile,
+    channels: Vec<CString>,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    // Interior nul bytes can't be represented in a C string, strip them rather than lose the message
+    let message = CString::new(message.replace('\0', "")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+fn clear_last_error() {
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+}
+
+/// Run a closure, translating both errors and panics into error codes
+fn guard<F: FnOnce() -> Result<i64, (i64, String)>>(f: F) -> i64 {
+    clear_last_error();
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => value,
+        Ok(Err((code, message))) => {
+            set_last_error(message);
+            code
+        }
+        Err(panic) => {
+            let message = if let Some(s) = panic.downcast_ref::<&str>() {
+                s.to_string()
+            } else if let Some(s) = panic.downcast_ref::<String>() {
+                s.clone()
+            } else {
+                "unknown panic".to_string()
+            };
+            set_last_error(format!("panic caught at ffi boundary: {}", message));
+            TDMS_ERR_PANIC
+        }
+    }
+}
+
+unsafe fn str_arg<'a>(arg: *const c_char) -> Result<&'a str, (i64, String)> {
+    if arg.is_null() {
+        return Err((
+            TDMS_ERR_NULL_ARGUMENT,
+            "string argument was null".to_string(),
+        ));
+    }
+    CStr::from_ptr(arg)
+        .to_str()
+        .map_err(|e| (TDMS_ERR_INVALID_UTF8, e.to_string()))
+}
+
+unsafe fn handle_arg<'a>(handle: *mut TdmsHandle) -> Result<&'a mut TdmsHandle, (i64, String)> {
+    handle
+        .as_mut()
+        .ok_or((TDMS_ERR_NULL_ARGUMENT, "handle was null".to_string()))
+}
+
+/// Open a TDMS file. Returns NULL on failure, see `tdms_last_error_message`.
+/// The returned handle must be released with `tdms_close`.
+///
+/// # Safety
+/// `path` must be a valid nul terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn tdms_open(path: *const c_char) -> *mut TdmsHandle {
+    let mut handle: *mut TdmsHandle = ptr::null_mut();
+    guard(|| {
+        let path = str_arg(path)?;
+        let file = TdmsFile::open(Path::new(path)).map_err(|e| (TDMS_ERR_READ, e.to_string()))?;
+        let channels = file
+            .data_objects()
+            .iter()
+            .map(|name| CString::new(name.replace('\0', "")).unwrap_or_default())
+            .collect();
+        handle = Box::into_raw(Box::new(TdmsHandle { file, channels }));
+        Ok(0)
+    });
+    handle
+}
+
+/// Returns the number of channels containing data, or a negative error code.
+///
+/// # Safety
+/// `handle` must be a handle returned by `tdms_open` that has not been closed.
+#[no_mangle]
+pub unsafe extern "C" fn tdms_channel_count(handle: *mut TdmsHandle) -> i64 {
+    guard(|| Ok(handle_arg(handle)?.channels.len() as i64))
+}
+
+/// Copy the path of the channel at `index` into `buf` as a nul terminated string, truncating
+/// if `len` is too small. Returns the full length of the path excluding the nul terminator,
+/// so a return value >= `len` indicates truncation. `buf` may be NULL to query the length.
+///
+/// # Safety
+/// `handle` must be a handle returned by `tdms_open` that has not been closed, and `buf` must
+/// be NULL or point to at least `len` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn tdms_channel_name(
+    handle: *mut TdmsHandle,
+    index: usize,
+    buf: *mut c_char,
+    len: usize,
+) -> i64 {
+    guard(|| {
+        let handle = handle_arg(handle)?;
+        let name = handle.channels.get(index).ok_or_else(|| {
+            (
+                TDMS_ERR_INDEX,
+                format!("channel index {} out of range", index),
+            )
+        })?;
+        let bytes = name.as_bytes();
+        if !buf.is_null() && len > 0 {
+            let copy_len = bytes.len().min(len - 1);
+            ptr::copy_nonoverlapping(bytes.as_ptr() as *const c_char, buf, copy_len);
+            *buf.add(copy_len) = 0;
+        }
+        Ok(bytes.len() as i64)
+    })
+}
+
+/// Read a channel converted to doubles into `out`, writing at most `capacity` values.
+/// Returns the number of values written, or a negative error code, `TDMS_ERR_READ` for
+/// channels of strings or timestamps. If `out` is NULL the number of values in the channel is
+/// returned without writing anything.
+///
+/// # Safety
+/// `handle` must be a handle returned by `tdms_open` that has not been closed, `path` must be
+/// a valid nul terminated string and `out` must be NULL or point to `capacity` doubles.
+#[no_mangle]
+pub unsafe extern "C" fn tdms_read_f64(
+    handle: *mut TdmsHandle,
+    path: *const c_char,
+    out: *mut f64,
+    capacity: usize,
+) -> i64 {
+    guard(|| {
+        let handle = handle_arg(handle)?;
+        let path = str_arg(path)?;
+        // Strings and timestamps fail with NotNumeric rather than reaching a conversion
+        let mut values = Vec::new();
+        handle
+            .file
+            .load_data_into(path, &mut values)
+            .map_err(|e| (TDMS_ERR_READ, e.to_string()))?;
+        if out.is_null() {
+            return Ok(values.len() as i64);
+        }
+        let written = values.len().min(capacity);
+        ptr::copy_nonoverlapping(values.as_ptr(), out, written);
+        Ok(written as i64)
+    })
+}
+
+/// Returns a description of the last error on this thread, or NULL if the last call succeeded.
+/// The string is owned by the library and is valid until the next call on this thread.
+#[no_mangle]
+pub extern "C" fn tdms_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|last| match last.borrow().as_ref() {
+        Some(message) => message.as_ptr(),
+        None => ptr::null(),
+    })
+}
+
+/// Release a handle returned by `tdms_open`. Passing NULL is a no-op.
+///
+/// # Safety
+/// `handle` must be NULL or a handle returned by `tdms_open` that has not already been closed.
+#[no_mangle]
+pub unsafe extern "C" fn tdms_close(handle: *mut TdmsHandle) {
+    if !handle.is_null() {
+        let _ = catch_unwind(AssertUnwindSafe(|| drop(Box::from_raw(handle))));
+    }
+}
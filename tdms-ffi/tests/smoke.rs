@@ -0,0 +1,89 @@
+//! Builds `c/smoke.c` against the library and runs it on a synthetic file, so the C API is
+//! exercised as a C program sees it. Needs a C compiler, `cc` or the one `CC` names.
+#![cfg(unix)]
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+use tdms::test_util::{ramp, SegmentBuilder, TdmsBuilder};
+use tdms::DataTypeRaw;
+
+/// Builds the shared library, which `cargo test` doesn't do for a cdylib, and returns the
+/// target/<profile> directory it lands in, the one above the deps directory holding this test
+fn build_library() -> PathBuf {
+    let exe = env::current_exe().unwrap();
+    let library_dir = exe.parent().unwrap().parent().unwrap().to_path_buf();
+    let mut cargo = Command::new(env!("CARGO"));
+    cargo.args(["build", "-p", "tdms-ffi", "--lib"]);
+    if library_dir.ends_with("release") {
+        cargo.arg("--release");
+    }
+    assert!(
+        cargo.status().unwrap().success(),
+        "tdms-ffi failed to build"
+    );
+    library_dir
+}
+
+#[test]
+fn c_smoke_test() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("smoke.tdms");
+    let bytes = TdmsBuilder::new()
+        .segment(
+            SegmentBuilder::new()
+                .object("/")
+                .object("/'group'")
+                .channel("/'group'/'ramp'", ramp(DataTypeRaw::DoubleFloat, 5, 100))
+                .channel("/'group'/'text'", ramp(DataTypeRaw::TdmsString, 0, 3))
+                .channel("/'group'/'time'", ramp(DataTypeRaw::TimeStamp, 0, 3)),
+        )
+        .build();
+    fs::write(&file, bytes).unwrap();
+
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let library_dir = build_library();
+    let smoke = dir.path().join("smoke");
+    let compiler = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let status = Command::new(compiler)
+        .arg(manifest_dir.join("c/smoke.c"))
+        .arg("-I")
+        .arg(manifest_dir.join("include"))
+        .arg("-L")
+        .arg(&library_dir)
+        .arg("-ltdms_ffi")
+        .arg("-o")
+        .arg(&smoke)
+        .status()
+        .expect("a C compiler is needed to build the smoke test");
+    assert!(status.success(), "smoke.c failed to build");
+
+    let output = Command::new(&smoke)
+        .arg(&file)
+        .env("LD_LIBRARY_PATH", &library_dir)
+        .env("DYLD_LIBRARY_PATH", &library_dir)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "smoke failed:\n{}{}",
+        stdout,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(stdout.contains("channels: 3"), "{}", stdout);
+    assert!(
+        stdout.contains("/'group'/'ramp': 100 values, first 5"),
+        "{}",
+        stdout
+    );
+    // Channels without numeric values are an error for the caller, not a caught panic
+    for (path, data_type) in [("text", "TdmsString"), ("time", "TimeStamp")] {
+        let expected = format!(
+            "/'group'/'{path}': error -3, \"/'group'/'{path}'\" holds {data_type} data which has no numeric value",
+        );
+        assert!(stdout.contains(&expected), "{}", stdout);
+    }
+}
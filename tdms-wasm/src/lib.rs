@@ -0,0 +1,97 @@
+//! Example of using the tdms library in the browser. The file contents are handed over from
+//! javascript as an ArrayBuffer and read through an in memory cursor.
+//!
+//! Build with `wasm-pack build --target web tdms-wasm` then serve the `tdms-wasm` directory
+//! and open `www/index.html`.
+
+use std::io::Cursor;
+
+use tdms::{TdmsError, TdmsFile};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+pub struct TdmsViewer {
+    file: TdmsFile<Cursor<Vec<u8>>>,
+}
+
+#[wasm_bindgen]
+impl TdmsViewer {
+    /// Map the Tdms file held in the buffer
+    #[wasm_bindgen(constructor)]
+    pub fn new(buffer: &js_sys::ArrayBuffer) -> Result<TdmsViewer, JsValue> {
+        let bytes = js_sys::Uint8Array::new(buffer).to_vec();
+        TdmsViewer::from_bytes(bytes).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Return an array of channel paths for channels with data
+    pub fn channels(&self) -> js_sys::Array {
+        self.file
+            .data_objects()
+            .into_iter()
+            .map(JsValue::from_str)
+            .collect()
+    }
+
+    /// Load a channel converted to doubles, throwing for channels of strings or timestamps
+    pub fn load_f64(&mut self, path: &str) -> Result<js_sys::Float64Array, JsValue> {
+        let values = self
+            .values_f64(path)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+        Ok(js_sys::Float64Array::from(&values[..]))
+    }
+}
+
+/// The parts of the viewer that don't touch javascript, so they can be tested natively
+impl TdmsViewer {
+    fn from_bytes(bytes: Vec<u8>) -> Result<TdmsViewer, TdmsError> {
+        let file = TdmsFile::new(Cursor::new(bytes))?;
+
+        Ok(TdmsViewer { file })
+    }
+
+    fn values_f64(&self, path: &str) -> Result<Vec<f64>, TdmsError> {
+        let mut values = Vec::new();
+        self.file.load_data_into(path, &mut values)?;
+
+        Ok(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tdms::test_util::{ramp, SegmentBuilder, TdmsBuilder};
+    use tdms::DataTypeRaw;
+
+    #[test]
+    fn parses_files_handed_over_as_bytes() {
+        let bytes = TdmsBuilder::new()
+            .segment(
+                SegmentBuilder::new()
+                    .channel("/'group'/'ramp'", ramp(DataTypeRaw::I16, 3, 4))
+                    .channel("/'group'/'text'", ramp(DataTypeRaw::TdmsString, 0, 2)),
+            )
+            .segment(SegmentBuilder::new().channel("/'group'/'ramp'", ramp(DataTypeRaw::I16, 7, 2)))
+            .build();
+        let viewer = TdmsViewer::from_bytes(bytes).unwrap();
+
+        assert_eq!(
+            viewer.file.data_objects(),
+            ["/'group'/'ramp'", "/'group'/'text'"]
+        );
+        assert_eq!(
+            viewer.values_f64("/'group'/'ramp'").unwrap(),
+            [3.0, 4.0, 5.0, 6.0, 7.0, 8.0]
+        );
+        assert!(matches!(
+            viewer.values_f64("/'group'/'text'"),
+            Err(TdmsError::NotNumeric { .. })
+        ));
+    }
+
+    #[test]
+    fn bytes_that_arent_a_file_fail_to_parse() {
+        assert!(TdmsViewer::from_bytes(b"not a tdms file at all, just text".to_vec()).is_err());
+    }
+}
@@ -0,0 +1,245 @@
+use std::io::{BufReader, Cursor, Read, Seek, SeekFrom};
+use std::thread;
+use std::time::Duration;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use tdms::tdms_datatypes::{DataType, DataTypeRaw, DataTypeVec};
+use tdms::test_util::{synthetic_file, SegmentBuilder, TdmsBuilder};
+use tdms::{ReadOptions, TdmsFile};
+
+/// Open the file and load every channel in it
+fn load_all(bytes: &[u8]) {
+    let file = TdmsFile::new(Cursor::new(bytes)).unwrap();
+    for channel in file.channels() {
+        criterion::black_box(channel.load(&file).unwrap());
+    }
+}
+
+fn bench_load(c: &mut Criterion, name: &str, bytes: &[u8]) {
+    let mut group = c.benchmark_group(name);
+    group.throughput(Throughput::Bytes(bytes.len() as u64));
+    group.bench_function("load", |b| b.iter(|| load_all(bytes)));
+    group.finish();
+}
+
+fn many_small_segments(c: &mut Criterion) {
+    let bytes = synthetic_file(
+        &[("group", "channel", DataTypeRaw::DoubleFloat)],
+        10_000,
+        10,
+        false,
+    );
+    bench_load(c, "many_small_segments", &bytes);
+}
+
+fn few_huge_segments(c: &mut Criterion) {
+    let bytes = synthetic_file(
+        &[("group", "channel", DataTypeRaw::DoubleFloat)],
+        4,
+        1_000_000,
+        false,
+    );
+    bench_load(c, "few_huge_segments", &bytes);
+}
+
+fn interleaved_channels(c: &mut Criterion) {
+    let names: Vec<String> = (0..16).map(|i| format!("channel {}", i)).collect();
+    let channels: Vec<(&str, &str, DataTypeRaw)> = names
+        .iter()
+        .map(|name| ("group", name.as_str(), DataTypeRaw::I32))
+        .collect();
+    let bytes = synthetic_file(&channels, 10, 10_000, true);
+    bench_load(c, "interleaved_16_channels", &bytes);
+}
+
+fn string_channels(c: &mut Criterion) {
+    let bytes = synthetic_file(
+        &[
+            ("group", "strings a", DataTypeRaw::TdmsString),
+            ("group", "strings b", DataTypeRaw::TdmsString),
+        ],
+        100,
+        1_000,
+        false,
+    );
+    bench_load(c, "string_channels", &bytes);
+}
+
+/// Single byte types, read in bulk rather than value by value
+fn byte_channels(c: &mut Criterion) {
+    for (name, data_type) in [
+        ("boolean_channel", DataTypeRaw::Boolean),
+        ("u8_channel", DataTypeRaw::U8),
+    ] {
+        let bytes = synthetic_file(&[("group", "channel", data_type)], 1, 10_000_000, false);
+        bench_load(c, name, &bytes);
+    }
+}
+
+/// Mapping alone, no data is read
+fn open_map(c: &mut Criterion) {
+    let bytes = synthetic_file(
+        &[
+            ("group", "channel a", DataTypeRaw::DoubleFloat),
+            ("group", "channel b", DataTypeRaw::I16),
+        ],
+        10_000,
+        10,
+        false,
+    );
+    let mut group = c.benchmark_group("open_map");
+    group.throughput(Throughput::Bytes(bytes.len() as u64));
+    group.bench_function("open", |b| {
+        b.iter(|| TdmsFile::new(Cursor::new(&bytes[..])).unwrap())
+    });
+    group.finish();
+}
+
+/// A reader that sleeps on every read, simulating a high latency source such as a network
+/// filesystem
+struct SlowReader<R> {
+    inner: R,
+    latency: Duration,
+}
+
+impl<R: Read> Read for SlowReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        thread::sleep(self.latency);
+        self.inner.read(buf)
+    }
+}
+
+impl<R: Seek> Seek for SlowReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+fn slow_reader(bytes: &[u8], capacity: usize) -> BufReader<SlowReader<Cursor<&[u8]>>> {
+    let reader = SlowReader {
+        inner: Cursor::new(bytes),
+        latency: Duration::from_micros(50),
+    };
+    BufReader::with_capacity(capacity, reader)
+}
+
+/// Buffer capacity and read ahead against a reader where every read call is slow
+fn high_latency(c: &mut Criterion) {
+    // Meta data much larger than the default buffer, with properties on every channel
+    let mut segment = SegmentBuilder::new().object("/'group'");
+    for i in 0..1_000 {
+        segment = segment
+            .channel(
+                &format!("/'group'/'channel {}'", i),
+                DataTypeVec::Double(vec![0.0; 10]),
+            )
+            .property("description", DataType::TdmsString("a channel".repeat(10)))
+            .property("wf_increment", DataType::Double(0.001));
+    }
+    let large_meta_data = TdmsBuilder::new().segment(segment).build();
+
+    let names: Vec<String> = (0..4).map(|i| format!("channel {}", i)).collect();
+    let channels: Vec<(&str, &str, DataTypeRaw)> = names
+        .iter()
+        .map(|name| ("group", name.as_str(), DataTypeRaw::I32))
+        .collect();
+    let interleaved = synthetic_file(&channels, 1, 100_000, true);
+
+    let mut group = c.benchmark_group("high_latency");
+    group.sample_size(10);
+    for capacity in [8 * 1024, 1024 * 1024] {
+        group.bench_with_input(
+            BenchmarkId::new("open_large_meta_data", capacity),
+            &capacity,
+            |b, &capacity| {
+                b.iter(|| TdmsFile::new(slow_reader(&large_meta_data, capacity)).unwrap())
+            },
+        );
+    }
+    // Values are read ahead in blocks rather than one read per value
+    group.bench_function("load_interleaved", |b| {
+        b.iter(|| {
+            let file = TdmsFile::new(slow_reader(&interleaved, 8 * 1024)).unwrap();
+            file.load_data("/'group'/'channel 0'").unwrap()
+        })
+    });
+    group.finish();
+}
+
+/// Loading a large contiguous channel from a file on disk, buffered with the standard 8 KB and
+/// with 1 MB
+fn buffer_capacity(c: &mut Criterion) {
+    let bytes = synthetic_file(
+        &[("group", "channel", DataTypeRaw::DoubleFloat)],
+        4,
+        1_000_000,
+        false,
+    );
+    let path = std::env::temp_dir().join("tdms_bench_buffer_capacity.tdms");
+    std::fs::write(&path, &bytes).unwrap();
+
+    let mut group = c.benchmark_group("buffer_capacity");
+    group.throughput(Throughput::Bytes(bytes.len() as u64));
+    for capacity in [8 * 1024, 1024 * 1024] {
+        group.bench_with_input(
+            BenchmarkId::new("load_contiguous", capacity),
+            &capacity,
+            |b, &capacity| {
+                b.iter(|| {
+                    let options = ReadOptions::new().buffer_capacity(Some(capacity));
+                    let file = TdmsFile::open_with_options(&path, options).unwrap();
+                    file.load_data("/'group'/'channel'").unwrap()
+                })
+            },
+        );
+    }
+    group.finish();
+    std::fs::remove_file(&path).ok();
+}
+
+/// Opening a file with many properties, parsing them while mapping and deferring them
+fn defer_properties(c: &mut Criterion) {
+    let mut segment = SegmentBuilder::new().object("/'group'");
+    for i in 0..1_000 {
+        segment = segment.channel(
+            &format!("/'group'/'channel {}'", i),
+            DataTypeVec::Double(vec![0.0]),
+        );
+        for property in 0..100 {
+            segment = segment.property(
+                &format!("property {}", property),
+                DataType::TdmsString(format!("value {}", property)),
+            );
+        }
+    }
+    let bytes = TdmsBuilder::new().segment(segment).build();
+    let path = std::env::temp_dir().join("tdms_bench_defer_properties.tdms");
+    std::fs::write(&path, &bytes).unwrap();
+
+    let mut group = c.benchmark_group("defer_properties");
+    group.throughput(Throughput::Bytes(bytes.len() as u64));
+    for defer in [false, true] {
+        group.bench_with_input(BenchmarkId::new("open", defer), &defer, |b, &defer| {
+            b.iter(|| {
+                let options = ReadOptions::new().defer_properties(defer);
+                TdmsFile::open_with_options(&path, options).unwrap()
+            })
+        });
+    }
+    group.finish();
+    std::fs::remove_file(&path).ok();
+}
+
+criterion_group!(
+    benches,
+    many_small_segments,
+    few_huge_segments,
+    interleaved_channels,
+    string_channels,
+    byte_channels,
+    open_map,
+    high_latency,
+    buffer_capacity,
+    defer_properties
+);
+criterion_main!(benches);
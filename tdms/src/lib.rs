@@ -2,24 +2,92 @@ use indexmap::IndexMap;
 use std::fmt;
 use std::fs;
 use std::io;
-use std::io::{BufReader, ErrorKind, Read, Seek, SeekFrom};
+use std::io::{BufReader, Cursor, ErrorKind, Read, Seek, SeekFrom};
+use std::ops::Range;
 use std::path;
+use std::sync::OnceLock;
 
 use byteorder::{BE, LE, *};
-use log::debug;
+use log::{debug, info, warn};
+mod tdms_alias;
+pub mod tdms_cache;
+pub use tdms_cache::CachedTdmsFile;
+pub mod tdms_channel;
+pub use tdms_channel::{ChannelHandle, ChannelInfo, Quantity};
+mod tdms_csv;
 pub mod tdms_datatypes;
-pub use tdms_datatypes::DataTypeVec;
 use tdms_datatypes::{
-    read_data_vector, read_datatype, read_string, DataType, DataTypeRaw, TocMask, TocProperties,
+    lossy_string, read_data_vector, read_datatype, read_pairs_vector, read_raw_pair, read_string,
+    vec_len, TocMask, TocProperties,
 };
+pub use tdms_datatypes::{
+    DataType, DataTypeRaw, DataTypeVec, FormatOptions, NonFinite, PropertyValue,
+};
+pub mod tdms_diff;
+pub use tdms_diff::{
+    diff, DiffOptions, DiffReport, LengthMismatch, PropertyDiff, TypeMismatch, ValueDiff,
+};
+pub mod tdms_digest;
+pub use tdms_digest::DigestAlgo;
 pub mod tdms_error;
 pub use tdms_error::{Result, TdmsError};
+pub mod tdms_group;
+pub use tdms_group::GroupTable;
+pub mod tdms_merge;
+pub use tdms_merge::MergedChannels;
+pub mod tdms_options;
+pub use tdms_options::{ParseMode, ReadOptions};
+pub mod tdms_parse;
+pub use tdms_parse::{parse_segment, SegmentContext};
+pub mod tdms_path;
+use tdms_path::path_components;
+pub use tdms_path::{object_path, parse_object_path, ObjectPathRef};
+mod tdms_pool;
+use tdms_pool::ReaderPool;
+pub mod tdms_property;
+use tdms_property::PropertyBlock;
+pub use tdms_property::StandardProperty;
+pub mod tdms_time;
+pub use tdms_time::{Discontinuity, FillPolicy};
+use tdms_time::{TimingRun, TIMING_PROPERTIES};
+pub mod tdms_template;
+pub use tdms_template::TdmsMapTemplate;
+pub mod tdms_tree;
+pub use tdms_tree::{ObjectNode, ObjectTree};
+pub mod tdms_validate;
+pub use tdms_validate::ValidationIssue;
+pub mod tdms_warning;
+pub use tdms_warning::{TdmsWarning, WarningReason};
+#[cfg(feature = "hdf5")]
+pub mod tdms_hdf5;
+#[cfg(feature = "serde")]
+pub mod tdms_json;
+pub mod tdms_wav;
+#[cfg(any(test, feature = "test_util"))]
+pub mod test_util;
+#[cfg(feature = "hdf5")]
+pub use tdms_hdf5::Hdf5Options;
+pub use tdms_wav::{WavOptions, WavSampleFormat};
 
 const HEADER_LEN: u64 = 28;
+/// "TDSm", the tag starting every segment of a .tdms file, as read little endian
+pub const TDMS_TAG: u32 = u32::from_le_bytes(*b"TDSm");
+/// "TDSh", the tag starting every segment of a .tdms_index file
+pub const TDMS_INDEX_TAG: u32 = u32::from_le_bytes(*b"TDSh");
+/// Version number written by TDMS 1.0
+pub const TDMS_VERSION_1: u32 = 4712;
+/// Version number written by TDMS 2.0
+pub const TDMS_VERSION_2: u32 = 4713;
 const NO_RAW_DATA: u32 = 0xFFFF_FFFF;
-const DATA_INDEX_MATCHES_PREVIOUS: u32 = 0x0000_000;
+/// Next segment offset of a segment still being written, whose raw data runs to the end of the file
+const UNFINISHED_SEGMENT: u64 = 0xFFFF_FFFF_FFFF_FFFF;
+const DATA_INDEX_MATCHES_PREVIOUS: u32 = 0x0000_0000;
 const FORMAT_CHANGING_SCALER: u32 = 0x6912_0000;
 const DIGITAL_LINE_SCALER: u32 = 0x6912_0000;
+/// Matches the standard library's BufReader
+const DEFAULT_BUFFER_CAPACITY: usize = 8 * 1024;
+/// Channels streamed rather than loaded whole are decoded this many values at a time
+const STREAM_BLOCK_VALUES: u64 = 1 << 20;
 /*
 The TDMS file structure consists of a series of segments which contain metadata regarding the file.
 Each segment contains any number of group objects, each of which can contain any number of properties.
@@ -35,15 +103,213 @@ Root Object
 
 #[derive(Debug, Clone)]
 /// ReadPairs give the absolute file index, and the #no of bytes to read at that index, a channel
-/// is accessed by a vector of ReadPairs, the length of which should correspond to the number of
-/// raw data chunks in the file in which the channel is present.
+/// is accessed by a vector of ReadPairs. Each pair covers a run of raw data chunks with the same
+/// layout evenly spaced through the file, either the chunks of one segment or a run of
+/// consecutive single chunk segments, so files of many alike segments need few pairs.
 pub struct ReadPair {
     start_index: u64,
     no_values: u64,
+    no_bytes: u64, // bytes of this channel's data in the chunk, gathered from the stride if interleaved
     interleaved: bool,
     /// This is the sum of the datatype sizes for all channels in the chunk i.e. the number of bytes till
     /// the next value of this channel in interleaved data. Only present if interleaved is true.
     stride: Option<u64>,
+    bigendian: bool, // endianness is set per segment, so is tracked per pair rather than per object
+    segment_index: usize, // index of the segment the pair was mapped from, the first if several
+    no_chunks: u64,  // the number of chunks the pair covers, chunk_stride bytes apart
+    chunk_stride: u64,
+    segment_count: usize, // the number of segments the chunks are spread across, 1 or no_chunks
+}
+
+impl ReadPair {
+    /// A pair for each chunk the pair covers, in file order
+    fn chunks(&self) -> impl Iterator<Item = ReadPair> + '_ {
+        (0..self.no_chunks).map(move |i| ReadPair {
+            start_index: self.start_index + i * self.chunk_stride,
+            segment_index: self.segment_index
+                + (i as usize * self.segment_count) / self.no_chunks as usize,
+            no_chunks: 1,
+            chunk_stride: 0,
+            segment_count: 1,
+            ..self.clone()
+        })
+    }
+
+    /// Split a single chunk pair into pairs of at most max_values values, so a large chunk can
+    /// be read a piece at a time. Only values of a fixed size can be split, pairs of strings,
+    /// given a value_size of None, are left whole.
+    fn pieces(
+        &self,
+        value_size: Option<u64>,
+        max_values: u64,
+    ) -> impl Iterator<Item = ReadPair> + '_ {
+        let (piece_values, row) = match value_size {
+            Some(size) if self.no_values > max_values && max_values > 0 => {
+                let row = match self.stride {
+                    Some(stride) if self.interleaved => size + stride,
+                    _ => size,
+                };
+                (max_values, row)
+            }
+            _ => (self.no_values.max(1), 0),
+        };
+        let no_pieces = self.no_values.div_ceil(piece_values).max(1);
+        (0..no_pieces).map(move |i| {
+            let no_values = piece_values.min(self.no_values - i * piece_values);
+            ReadPair {
+                start_index: self.start_index + i * piece_values * row,
+                no_values,
+                no_bytes: if no_pieces > 1 {
+                    no_values * value_size.unwrap_or(0)
+                } else {
+                    self.no_bytes
+                },
+                ..self.clone()
+            }
+        })
+    }
+
+    /// The pair for no_values values of a single chunk pair starting skip values in, where
+    /// each value takes value_size bytes
+    fn value_range(&self, value_size: u64, skip: u64, no_values: u64) -> ReadPair {
+        let row = match self.stride {
+            Some(stride) if self.interleaved => value_size + stride,
+            _ => value_size,
+        };
+        ReadPair {
+            start_index: self.start_index + skip * row,
+            no_values,
+            no_bytes: no_values * value_size,
+            ..self.clone()
+        }
+    }
+
+    /// Extend the pair with the pair of the following segment if they continue a run of
+    /// single chunk segments with the same layout, evenly spaced through the file, as
+    /// streaming writers produce. Returns false if the pair can't be extended.
+    fn try_extend(&mut self, next: &ReadPair) -> bool {
+        let same_layout = self.no_values == next.no_values
+            && self.no_bytes == next.no_bytes
+            && self.interleaved == next.interleaved
+            && self.stride == next.stride
+            && self.bigendian == next.bigendian;
+        let single_chunks = self.no_chunks as usize == self.segment_count && next.no_chunks == 1;
+        let consecutive = self.segment_index + self.segment_count == next.segment_index;
+        let last_start = self.start_index + (self.no_chunks - 1) * self.chunk_stride;
+        let spacing = next.start_index.saturating_sub(last_start);
+        let evenly_spaced = spacing > 0 && (self.no_chunks == 1 || spacing == self.chunk_stride);
+
+        if same_layout && single_chunks && consecutive && evenly_spaced {
+            self.chunk_stride = spacing;
+            self.no_chunks += 1;
+            self.segment_count += 1;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// The byte order to decode raw data with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endianness {
+    Little,
+    Big,
+}
+
+/// Describes how a channel is present in a single segment
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SegmentPresence {
+    pub present: bool,
+    pub no_chunks: u64,
+    pub no_values: u64,
+}
+
+/// Summary information about a segment, available without reading any data
+#[derive(Debug, Clone, PartialEq)]
+pub struct SegmentInfo {
+    pub start_index: u64,
+    pub toc_flags: u32,
+    pub version: u32,
+    pub next_segment_offset: u64,
+    pub raw_data_offset: u64,
+    pub no_chunks: u64,
+}
+
+impl SegmentInfo {
+    /// Check if the segment's ToC has a given flag
+    pub fn has_flag(&self, flag: TocProperties) -> bool {
+        TocMask::from_flags(self.toc_flags).has_flag(flag)
+    }
+
+    pub fn has_meta_data(&self) -> bool {
+        self.has_flag(TocProperties::KTocMetaData)
+    }
+
+    pub fn has_raw_data(&self) -> bool {
+        self.has_flag(TocProperties::KTocRawData)
+    }
+
+    pub fn has_interleaved(&self) -> bool {
+        self.has_flag(TocProperties::KTocInterleavedData)
+    }
+
+    pub fn has_daqmx(&self) -> bool {
+        self.has_flag(TocProperties::KTocDAQmxRawData)
+    }
+
+    pub fn is_big_endian(&self) -> bool {
+        self.has_flag(TocProperties::KTocBigEndian)
+    }
+
+    pub fn has_new_object_list(&self) -> bool {
+        self.has_flag(TocProperties::KTocNewObjList)
+    }
+}
+
+/// A summary of the whole file for triage, see `TdmsFile::info`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TdmsFileInfo {
+    pub version: Option<u32>,
+    pub no_segments: usize,
+    pub interleaved: bool,
+    pub big_endian: bool,
+    pub daqmx: bool,
+    pub total_raw_bytes: u64,
+    pub channel_count: usize,
+    pub truncated: bool,
+}
+
+/// What was done to map a damaged file, under options that skip past damage rather than fail
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RecoveryInfo {
+    /// Segments skipped under skip_overlapping_segments, as the address of each and the
+    /// address its lead in gave for the next segment, which lies inside it
+    pub overlapping_segments: Vec<(u64, u64)>,
+}
+
+impl fmt::Display for TdmsFileInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.version {
+            Some(version) => write!(f, "TDMS version {}", version)?,
+            None => write!(f, "TDMS version unknown")?,
+        }
+        writeln!(
+            f,
+            ", {} segments, {} channels, {} raw bytes",
+            self.no_segments, self.channel_count, self.total_raw_bytes
+        )?;
+        let yes_no = |flag: bool| if flag { "yes" } else { "no" };
+        writeln!(
+            f,
+            "interleaved: {}, big endian: {}, DAQmx: {}, truncated: {}",
+            yes_no(self.interleaved),
+            yes_no(self.big_endian),
+            yes_no(self.daqmx),
+            yes_no(self.truncated)
+        )?;
+        Ok(())
+    }
 }
 
 impl fmt::Display for ReadPair {
@@ -63,8 +329,44 @@ pub struct ObjectMap {
     last_object: TdmsObject, // the most up to date version of the object, properties and indexing information are copied to this.
     read_map: Vec<ReadPair>, // for each segment in the file a vector of read pairs exist.
     total_bytes: u64, // The total byte count of raw data associated with the object, for keeping track of locations in file
-    total_values: usize, // Used to allocate memory to read the data
-    bigendian: bool,  // whether the object associated with this map has been logged as bigendian
+    timing: Vec<TimingRun>, // waveform timing as listed by each segment changing it, in segment order
+    property_blocks: Vec<PropertyBlock>, // where its properties are listed, if their parsing was deferred
+    lazy_properties: OnceLock<IndexMap<String, ObjectProperty>>, // parsed from property_blocks on first access
+}
+
+impl ObjectMap {
+    /// The total number of values across all read pairs, used to allocate memory to read the
+    /// data. Each pair keeps the value count that was valid when it was mapped, so this stays
+    /// correct when a channel's values per chunk change between segments.
+    pub fn total_values(&self) -> u64 {
+        self.read_map.iter().fold(0, |total, pair| {
+            total.saturating_add(pair.no_values.saturating_mul(pair.no_chunks))
+        })
+    }
+
+    /// The dimension of the object's raw data, 1 for objects without raw data
+    pub fn dimension(&self) -> u32 {
+        self.last_object.raw_data_dim.unwrap_or(1)
+    }
+
+    /// Bytes of memory the object's values take loaded, each string's raw data taken as the
+    /// size of its contents
+    fn load_size(&self) -> u64 {
+        let no_values: u64 = self
+            .read_map
+            .iter()
+            .map(|pair| pair.no_values * pair.no_chunks)
+            .sum();
+        let value_size = match self.last_object.raw_data_type {
+            Some(DataTypeRaw::TdmsString) => {
+                return no_values * std::mem::size_of::<String>() as u64 + self.total_bytes
+            }
+            // Other types take the same space loaded as stored
+            Some(data_type) => data_type.size().unwrap_or(0),
+            None => 0,
+        };
+        no_values * value_size
+    }
 }
 
 impl fmt::Display for ObjectMap {
@@ -75,649 +377,4032 @@ impl fmt::Display for ObjectMap {
     }
 }
 
-//handle: io::BufReader<std::fs::File>,
+/// An open Tdms file. Any seekable source can be read, files opened from disk are buffered
+/// by default. The file is `Send + Sync` for readers that are `Send`, so channels can be
+/// loaded from several threads at once. Files opened from disk give each thread its own
+/// handle, other sources are read by one thread at a time.
+pub struct TdmsFile<R: Read + Seek = BufReader<fs::File>> {
+    readers: ReaderPool<R>,
+    tdms_map: TdmsMap,
+    aliases: IndexMap<String, String>, // friendly names for channels, keyed by path
+}
+
+/// The file to open for path and the `.tdms_index` file that would index it, given either
+/// `name.tdms` or `name.tdms_index`. Extensions are matched ignoring case, and the rest of the
+/// path is kept as it is so names needn't be valid UTF-8. Files with other extensions have no
+/// index.
+fn companion_paths(path: &path::Path) -> (path::PathBuf, Option<path::PathBuf>) {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some(extension) if extension.eq_ignore_ascii_case("tdms_index") => {
+            let data_path = path.with_extension(&extension[.."tdms".len()]);
+            (data_path, Some(path.to_path_buf()))
+        }
+        Some(extension) if extension.eq_ignore_ascii_case("tdms") => {
+            let mut index_path = path.as_os_str().to_os_string();
+            index_path.push("_index");
+            (path.to_path_buf(), Some(index_path.into()))
+        }
+        _ => (path.to_path_buf(), None),
+    }
+}
+
+/// Buffer reads from inner with the capacity the options ask for
+fn buffered<R: Read>(inner: R, options: &ReadOptions) -> BufReader<R> {
+    let capacity = options.buffer_capacity.unwrap_or(DEFAULT_BUFFER_CAPACITY);
+    BufReader::with_capacity(capacity, inner)
+}
 
-pub struct TdmsFile {
+/// A pool of buffered readers for the file at path, starting with reader
+fn file_readers(
     reader: BufReader<fs::File>,
-    tdms_map: TdmsMap,
+    path: &path::Path,
+    capacity: usize,
+) -> ReaderPool<BufReader<fs::File>> {
+    let path = path.to_path_buf();
+    ReaderPool::reopening(reader, move || {
+        Ok(BufReader::with_capacity(capacity, fs::File::open(&path)?))
+    })
 }
 
 impl TdmsFile {
     /// Open a Tdms file and initialize a buf rdr to handle access.
     pub fn open(path: &path::Path) -> Result<TdmsFile> {
-        let fh = fs::File::open(path)?;
-        let file_length = fh.metadata().unwrap().len();
-        println!("file size on load: {:?}", file_length);
-        let mut reader = io::BufReader::new(fh);
-        let mut tdms_map = TdmsMap::new();
-        tdms_map.map_segments(&mut reader, file_length)?;
+        TdmsFile::open_with_options(path, ReadOptions::default())
+    }
 
-        Ok(TdmsFile { reader, tdms_map })
+    /// Open a Tdms file with the given options, `open` is equivalent to the default options.
+    /// A `.tdms_index` file beside the file is used to map it unless the options say
+    /// otherwise, carrying on through the file for any segments written after the index. If
+    /// the index can't be mapped the file itself is walked instead. Given the path of a
+    /// `.tdms_index` file, the file it indexes is opened.
+    pub fn open_with_options(path: &path::Path, options: ReadOptions) -> Result<TdmsFile> {
+        TdmsFile::open_mapped(path, options, None, &mut |_, _| {})
     }
 
-    /// Stub implementation of load functionality, currently up to trying to get vector loading working gracefully
-    pub fn load_data(&mut self, path: &str) -> Result<DataTypeVec> {
-        // check if object exists in map
+    /// Open a Tdms file as open_with_options does, calling progress with the bytes mapped so
+    /// far and the file length as mapping goes, e.g. to drive a progress bar on large files.
+    /// The bytes mapped only increase, ending at the file length once it's mapped in full.
+    /// Segments mapped from an index file are reported together.
+    pub fn open_with_progress<F: FnMut(u64, u64)>(
+        path: &path::Path,
+        options: ReadOptions,
+        mut progress: F,
+    ) -> Result<TdmsFile> {
+        TdmsFile::open_mapped(path, options, None, &mut progress)
+    }
 
-        let object_map = self
-            .tdms_map
-            .all_objects
-            .get(path)
-            .ok_or(TdmsError::ChannelNotFound)?;
-        if object_map.bigendian {
-            Ok(read_data_vector::<_, BE>(object_map, &mut self.reader)?)
+    /// Open and map a Tdms file, from its index file where there is one, reporting progress as
+    /// segments are mapped. With a channel only that channel's read map is built.
+    fn open_mapped(
+        path: &path::Path,
+        options: ReadOptions,
+        channel: Option<&str>,
+        progress: &mut dyn FnMut(u64, u64),
+    ) -> Result<TdmsFile> {
+        let (path, companion) = companion_paths(path);
+        let path = path.as_path();
+        let fh = fs::File::open(path)?;
+        let mut reader = buffered(fh, &options);
+        let capacity = reader.capacity();
+        let file_length = reader.seek(SeekFrom::End(0))?;
+        let new_map = || {
+            let mut tdms_map = TdmsMap::new(options.clone());
+            tdms_map.channel_filter = channel.map(str::to_string);
+            tdms_map
+        };
+        let mut tdms_map = new_map();
+
+        if let Some(index_path) = tdms_map.options.index_file.clone() {
+            tdms_map.map_index_file(&index_path, capacity, file_length)?;
+            progress(tdms_map.next_segment_address.min(file_length), file_length);
         } else {
-            Ok(read_data_vector::<_, LE>(object_map, &mut self.reader)?)
+            let companion = companion
+                .filter(|index_path| !tdms_map.options.ignore_index_file && index_path.is_file());
+            let mapped = match companion {
+                Some(index_path) => {
+                    match tdms_map.map_index_file(&index_path, capacity, file_length) {
+                        Ok(_) => true,
+                        Err(e) => {
+                            warn!("Walking the file, {:?} can't be mapped: {}", index_path, e);
+                            false
+                        }
+                    }
+                }
+                None => false,
+            };
+            if mapped {
+                progress(tdms_map.next_segment_address.min(file_length), file_length);
+            } else {
+                tdms_map = new_map();
+            }
+            tdms_map.map_segments_with_progress(&mut reader, file_length, progress)?;
         }
+
+        Ok(TdmsFile {
+            readers: file_readers(reader, path, capacity),
+            tdms_map,
+            aliases: IndexMap::new(),
+        })
     }
 
-    /// Return a vector of object paths
-    pub fn all_objects(&self) -> Vec<&str> {
-        let mut objects: Vec<&str> = Vec::new();
+    /// Open a Tdms file with the given options and load a single channel. Only the requested
+    /// channel's read map is built, which saves time and memory on files with many channels.
+    /// Other channels in the returned file have no data.
+    pub fn open_channel(
+        path: &path::Path,
+        channel: &str,
+        options: ReadOptions,
+    ) -> Result<(TdmsFile, DataTypeVec)> {
+        let file = TdmsFile::open_mapped(path, options, Some(channel), &mut |_, _| {})?;
+        let data = file.load_data(channel)?;
+        Ok((file, data))
+    }
+}
 
-        for key in self.tdms_map.all_objects.keys() {
-            objects.push(key)
+impl<R: Read + Seek> TdmsFile<R> {
+    /// Map a Tdms file held by any seekable reader. This is the constructor to use where there
+    /// is no filesystem, e.g. in the browser with the file contents in a `Cursor<Vec<u8>>`.
+    pub fn new(mut reader: R) -> Result<TdmsFile<R>> {
+        let file_length = reader.seek(SeekFrom::End(0))?;
+        TdmsFile::from_reader(reader, file_length)
+    }
+
+    /// Map a Tdms file occupying the first length bytes of a seekable reader, anything after
+    /// is ignored. Useful where the reader's end isn't the file's, e.g. a stream with trailing
+    /// data or one whose length is known up front.
+    pub fn from_reader(mut reader: R, length: u64) -> Result<TdmsFile<R>> {
+        debug!("file size on load: {:?}", length);
+        let mut tdms_map = TdmsMap::new(ReadOptions::default());
+        tdms_map.map_segments(&mut reader, length)?;
+
+        Ok(TdmsFile {
+            readers: ReaderPool::single(reader),
+            tdms_map,
+            aliases: IndexMap::new(),
+        })
+    }
+
+    /// Re-read the file length and map any segments appended since the file was opened or last
+    /// refreshed. A final segment still being written when last mapped is mapped again, picking
+    /// up the data written since. Returns the number of new segments found.
+    pub fn refresh(&mut self) -> Result<usize> {
+        let reader = self.readers.get_mut()?;
+        let file_length = reader.seek(SeekFrom::End(0))?;
+        let known_segments = self.tdms_map.segments.len();
+        // A segment that was still being written is mapped again, from the map as it was before
+        if let Some(before_unfinished) = self.tdms_map.before_unfinished.take() {
+            self.tdms_map = *before_unfinished;
         }
-        objects
+        self.tdms_map.map_segments(reader, file_length)?;
+
+        Ok(self.tdms_map.segments.len().saturating_sub(known_segments))
     }
 
-    /// Return a vector of channel paths for channels with data
-    pub fn data_objects(&self) -> Vec<&str> {
-        let mut objects: Vec<&str> = Vec::new();
+    /// Stub implementation of load functionality, currently up to trying to get vector loading working gracefully
+    pub fn load_data<'a, P: Into<ObjectPathRef<'a>>>(&self, path: P) -> Result<DataTypeVec> {
+        // check if object exists in map
 
-        for (key, object_map) in &self.tdms_map.all_objects {
-            if object_map.last_object.no_bytes > 0 {
-                objects.push(key);
-            }
+        let object_map = self.tdms_map.object_map(path.into().as_str())?;
+        if self.tdms_map.options.metadata_only {
+            return Err(TdmsError::MetadataOnly);
         }
-        objects
+        self.check_load_size(object_map)?;
+        read_data_vector(
+            object_map,
+            &mut *self.readers.take()?,
+            self.tdms_map.options.lossy_strings,
+        )
     }
 
-    /// Display an objects properties
-    pub fn object_properties(&self, path: &str) -> Result<()> {
-        let object = self
-            .tdms_map
-            .all_objects
-            .get(path)
-            .ok_or(TdmsError::ChannelNotFound)?;
+    /// Load every channel with data, carrying on past any that fail so one bad channel doesn't
+    /// lose the rest, e.g. for bulk conversion. Results are keyed by path in file order, with
+    /// each error wrapped in `LoadFailed` giving the channel's path.
+    pub fn load_all_channels(&self) -> IndexMap<String, Result<DataTypeVec>> {
+        self.data_objects()
+            .into_iter()
+            .map(|path| {
+                let data = self.load_data(path).map_err(|e| TdmsError::LoadFailed {
+                    path: path.to_string(),
+                    source: Box::new(e),
+                });
+                (path.to_string(), data)
+            })
+            .collect()
+    }
 
-        print!("{}", object.last_object);
+    /// Load a channel's data decoded with the given byte order, ignoring the big endian flag
+    /// of the segments it was read from. For diagnosing files whose writer set the flag wrong,
+    /// otherwise use `load_data`.
+    pub fn load_data_with_endianness<'a, P: Into<ObjectPathRef<'a>>>(
+        &self,
+        path: P,
+        endianness: Endianness,
+    ) -> Result<DataTypeVec> {
+        let object_map = self.tdms_map.object_map(path.into().as_str())?;
+        if self.tdms_map.options.metadata_only {
+            return Err(TdmsError::MetadataOnly);
+        }
+        self.check_load_size(object_map)?;
 
-        Ok(())
+        let bigendian = endianness == Endianness::Big;
+        let pairs: Vec<ReadPair> = object_map
+            .read_map
+            .iter()
+            .map(|pair| ReadPair {
+                bigendian,
+                ..pair.clone()
+            })
+            .collect();
+        read_pairs_vector(
+            object_map,
+            &pairs,
+            &mut *self.readers.take()?,
+            self.tdms_map.options.lossy_strings,
+        )
     }
 
-    /// Print an object's read pairs
-    pub fn object_with_read_pairs(&self, path: &str) -> Result<()> {
-        let object = self
-            .tdms_map
-            .all_objects
-            .get(path)
-            .ok_or(TdmsError::ChannelNotFound)?;
+    /// An estimate of the memory in bytes loading a channel whole would take, from its value
+    /// count and type without reading any data. Strings are estimated from the size of their
+    /// raw data. Fails with `MetadataOnly` if the file was opened metadata only.
+    pub fn estimated_load_size<'a, P: Into<ObjectPathRef<'a>>>(&self, path: P) -> Result<u64> {
+        let object_map = self.tdms_map.object_map(path.into().as_str())?;
+        if self.tdms_map.options.metadata_only {
+            return Err(TdmsError::MetadataOnly);
+        }
+        Ok(object_map.load_size())
+    }
 
-        print!("{:?}", object);
-        Ok(())
+    /// The bytes of raw data a channel has in the file, across all its segments, taken from
+    /// the map without reading the file. For numeric channels this is the type size times the
+    /// number of values. Fails with `MetadataOnly` if the file was opened metadata only.
+    pub fn channel_total_bytes<'a, P: Into<ObjectPathRef<'a>>>(&self, path: P) -> Result<u64> {
+        let object_map = self.tdms_map.object_map(path.into().as_str())?;
+        if self.tdms_map.options.metadata_only {
+            return Err(TdmsError::MetadataOnly);
+        }
+        Ok(object_map.total_bytes)
     }
-}
 
-/// Diagnostic function to print current location for debugging purposes
-pub fn current_loc<R: Read + Seek>(reader: &mut R) {
-    println!("{:?}", reader.seek(SeekFrom::Current(0)));
-}
+    /// The bytes of raw data in a segment, from its raw data offset to the next segment.
+    /// Segments cut back to their complete chunks when repaired give the size kept.
+    pub fn segment_raw_data_size(&self, segment_index: usize) -> Result<u64> {
+        let no_segments = self.tdms_map.segments.len();
+        if segment_index >= no_segments {
+            return Err(TdmsError::SegmentOutOfRange {
+                index: segment_index,
+                no_segments,
+            });
+        }
 
-/// Represents the contents of a Tdms file which consists of a series  of segments + ancillary data which is created to index those segments.
-#[derive(Debug)]
-pub struct TdmsMap {
-    segments: Vec<TdmsSegment>,
-    pub all_objects: IndexMap<String, ObjectMap>, // Keeps track of all objects in file and their read maps, order not important for this one, using indexmap to avoid running multiple hashmap types.
-    live_objects: Vec<String>, // Keeps track of order of objects accumulated over segments, is reset when kToCNewObjectList flag is detected
-}
+        let segment = &self.tdms_map.segments[segment_index];
+        segment
+            .next_seg_offset
+            .checked_sub(segment.raw_data_offset)
+            .ok_or(TdmsError::MalformedSegment)
+    }
 
-impl TdmsMap {
-    fn new() -> TdmsMap {
-        TdmsMap {
-            segments: Vec::new(),
-            all_objects: IndexMap::new(),
-            live_objects: Vec::new(),
+    /// Fail with `LoadTooLarge` if loading a channel whole would take more memory than the
+    /// max_load_bytes option allows
+    fn check_load_size(&self, object_map: &ObjectMap) -> Result<()> {
+        match self.tdms_map.options.max_load_bytes {
+            Some(limit) if object_map.load_size() > limit => Err(TdmsError::LoadTooLarge {
+                needed: object_map.load_size(),
+                limit,
+            }),
+            _ => Ok(()),
         }
     }
 
-    /// Walk the file attempting to load the segment meta data and objects.
-    /// Raw data is not loaded during these reads in the interest of Lazy Loading
-    /// i.e. memory efficienct handling of very large files.
-    fn map_segments<R: Read + Seek>(
-        &mut self,
-        reader: &mut R,
-        file_length: u64,
-    ) -> Result<&mut Self> {
-        let mut next_segment_address = 0;
+    /// Load a channel's data along with its unit, None if the channel doesn't have one
+    pub fn load_with_unit<'a, P: Into<ObjectPathRef<'a>>>(
+        &self,
+        path: P,
+    ) -> Result<(DataTypeVec, Option<String>)> {
+        let path = path.into();
+        let data = self.load_data(path.as_str())?;
+        Ok((data, self.channel_unit(path.as_str())))
+    }
 
-        // If the file is corrupted, the last segment will contain 0xFFFF_FFFF for the "next segment offset".
-        // In this case the reader will attempt to map the segment but will hit an Unexpected end of file error
-        // while doing so.
-        while next_segment_address < file_length {
-            // Try read in a segment, if an error is returned, intercept it if it's
-            // unexpected EoF which indicates there's nothing at the target segment
-            // address, or bubble it up if it's a different kind of error.
-
-            let segment = match self.read_segment(reader, next_segment_address) {
-                Ok(segment) => segment,
-                Err(err) => match &err {
-                    TdmsError::Io(e) => match e.kind() {
-                        ErrorKind::UnexpectedEof => {
-                            println!("Completed read, final segment is corrupted");
-                            return Ok(self);
-                        }
-                        // Any other io error, repackage it and send it on
-                        _ => return Err(err),
-                    },
-                    _ => return Err(err), // Return early on weird custom errors as well
-                },
-            };
+    /// Load a channel's data converted to f64 along with its unit, which is empty if the
+    /// channel doesn't have one. Fails with `NotNumeric` as load_data_with does.
+    pub fn load_quantity<'a, P: Into<ObjectPathRef<'a>>>(&self, path: P) -> Result<Quantity> {
+        let path = path.into();
+        let values = self.load_data_with(path.as_str(), |value| value)?;
+        Ok(Quantity {
+            values,
+            unit: self.channel_unit(path.as_str()).unwrap_or_default(),
+        })
+    }
 
-            next_segment_address = segment.next_seg_offset + next_segment_address + HEADER_LEN;
+    /// A channel's unit, from the `unit_string` property or failing that
+    /// `NI_UnitDescription`. Where both are set and disagree `unit_string` wins, as it's the
+    /// one LabVIEW and DIAdem write and display. Empty strings count as no unit.
+    pub fn channel_unit<'a, P: Into<ObjectPathRef<'a>>>(&self, path: P) -> Option<String> {
+        let object_map = self.tdms_map.all_objects.get(path.into().as_str())?;
+        let properties = self.properties_of(object_map).ok()?;
+        [
+            StandardProperty::UnitString,
+            StandardProperty::NiUnitDescription,
+        ]
+        .iter()
+        .filter_map(|property| properties.get(property.name()))
+        .map(ObjectProperty::value)
+        .find_map(|value| match value {
+            DataType::TdmsString(unit) if !unit.is_empty() => Some(unit.clone()),
+            _ => None,
+        })
+    }
 
-            self.segments.push(segment);
+    /// Load a channel's raw bytes exactly as stored, without decoding them by data type, e.g. to
+    /// decode them in bulk or hash the data. The values of interleaved channels are gathered
+    /// from between the other channels' values into contiguous form, so the length always
+    /// matches the channel's `total_bytes`. Works for types that can't be decoded, e.g. DAQmx
+    /// raw data, which gives the whole raw buffer the channel reads from, shared with the other
+    /// DAQmx channels in that buffer.
+    pub fn load_raw_bytes<'a, P: Into<ObjectPathRef<'a>>>(&self, path: P) -> Result<Vec<u8>> {
+        let path = path.into();
+        let total_bytes = self.tdms_map.object_map(path.as_str())?.total_bytes;
+        let mut bytes = Vec::with_capacity(vec_len::<u8>(path.as_str(), total_bytes)?);
+        for chunk in self.raw_chunks(path)? {
+            bytes.extend(chunk?);
         }
-        println!("Completed read");
-        Ok(self)
+        Ok(bytes)
     }
 
-    /// Load in a segment and parse all objects and properties, does not load raw data.
-    /// This allows lazy loading to handle very large files.
-    fn read_segment<R: Read + Seek>(
-        &mut self,
-        reader: &mut R,
-        start_index: u64,
-    ) -> Result<TdmsSegment> {
-        // Seek to the "absolute index" (relative to start) This index has to be built up for each segment as we go.
-        // This is handled in the map_segments function
-        reader.seek(SeekFrom::Start(start_index))?;
-
-        let mut segment = TdmsSegment::new(start_index);
-
-        // Convert the critical lead in information to appropriate representation, we know the
-        // first part of the lead in is little endian so we save a check here.
-        segment.file_tag = reader.read_u32::<LE>()?;
-        segment.toc_mask = TocMask::from_flags(reader.read_u32::<LE>()?);
+    /// A channel's undecoded bytes, the same as `load_raw_bytes`
+    #[deprecated(note = "use `load_raw_bytes`, which this duplicates")]
+    pub fn channel_raw_bytes<'a, P: Into<ObjectPathRef<'a>>>(&self, path: P) -> Result<Vec<u8>> {
+        self.load_raw_bytes(path)
+    }
 
-        if segment.toc_mask.has_flag(TocProperties::KTocBigEndian) {
-            self.read_segment_metadata::<R, BE>(reader, segment)
-        } else {
-            self.read_segment_metadata::<R, LE>(reader, segment)
+    /// Iterate over a channel's raw bytes one read pair i.e. one raw data chunk at a time,
+    /// reading each only as it's reached. No reader is held between chunks, so the file can
+    /// be read from elsewhere while iterating.
+    pub fn raw_chunks<'a, P: Into<ObjectPathRef<'a>>>(
+        &self,
+        path: P,
+    ) -> Result<impl Iterator<Item = Result<Vec<u8>>> + '_> {
+        let object_map = self.tdms_map.object_map(path.into().as_str())?;
+        if self.tdms_map.options.metadata_only {
+            return Err(TdmsError::MetadataOnly);
         }
+        let path = &object_map.last_object.object_path;
+        let readers = &self.readers;
+
+        Ok(object_map
+            .read_map
+            .iter()
+            .flat_map(ReadPair::chunks)
+            .map(move |pair| read_raw_pair(path, &pair, &mut *readers.take()?)))
     }
 
-    fn read_segment_metadata<R: Read + Seek, O: ByteOrder>(
-        &mut self,
-        reader: &mut R,
-        mut segment: TdmsSegment,
-    ) -> Result<TdmsSegment> {
-        debug!("_______ENTERING SEGMENT________");
-        // Finish out the lead in
-        segment.version_no = reader.read_u32::<O>()?;
-        segment.next_seg_offset = reader.read_u64::<O>()?;
-        segment.raw_data_offset = reader.read_u64::<O>()?;
+    /// Returns whether a channel's most recent data was logged as big endian, false if the
+    /// channel has no data. Endianness is a segment level flag and data is decoded according
+    /// to the segment it was read from, so a channel may contain both.
+    pub fn channel_is_bigendian<'a, P: Into<ObjectPathRef<'a>>>(&self, path: P) -> Result<bool> {
+        let object_map = self.tdms_map.object_map(path.into().as_str())?;
 
-        debug!(
-            "NewObjFlag?: {}",
-            segment.toc_mask.has_flag(TocProperties::KTocNewObjList)
-        );
+        Ok(object_map
+            .read_map
+            .last()
+            .is_some_and(|pair| pair.bigendian))
+    }
 
-        // Load the meta_data for this segment, parsing objects that appear in this segment
-        let mut meta_data = TdmsMetaData::read_metadata::<R, O>(self, reader)?;
+    /// Describe a channel's presence in each segment of the file, in segment order. A channel
+    /// added part way through acquisition is reported absent in the segments before it appears.
+    pub fn channel_segment_layout<'a, P: Into<ObjectPathRef<'a>>>(
+        &self,
+        path: P,
+    ) -> Result<Vec<SegmentPresence>> {
+        let object_map = self.tdms_map.object_map(path.into().as_str())?;
 
-        // Update the object maps
-        if segment.toc_mask.has_flag(TocProperties::KTocNewObjList) {
-            // create new map of objects
-            let mut new_map: Vec<String> = Vec::new();
-            for object_path in meta_data.objects.iter() {
-                new_map.push(object_path.clone());
-            }
-            self.live_objects = new_map;
-
-            // if new_obj list has been set, then the chunk size as reported by new metadata is
-            // everything and we could have a totally new ordering of data for this segment.
-            // This will reset the live_objects map
-            segment.no_chunks = if meta_data.chunk_size > 0 {
-                (segment.next_seg_offset - segment.raw_data_offset) / meta_data.chunk_size
-            } else {
-                0
-            };
+        let mut layout = vec![SegmentPresence::default(); self.tdms_map.segments.len()];
+        for pair in object_map.read_map.iter().flat_map(ReadPair::chunks) {
+            let presence = &mut layout[pair.segment_index];
+            presence.present = true;
+            presence.no_chunks += 1;
+            presence.no_values += pair.no_values;
+        }
+        Ok(layout)
+    }
 
-            self.update_indexes(&segment, &meta_data)?;
-        } else {
-            // Need to iterate over the new list of objects in the segment, this list should only contain newly added objects
-            // check if it's in all_objects and update, otherwise update live objects
-            for object_path in meta_data.objects.iter() {
-                // If the object isn't in the live objects then it is truly new, so push it. If it is there
-                // then something about the object has changed but its order is still correct.
-                if !self.live_objects.contains(object_path) {
-                    self.live_objects.push(object_path.clone());
-                }
-            }
+    /// Load a channel's data as one vector per segment it has data in, in segment order, for
+    /// files where each segment is meaningful on its own e.g. one per trigger event
+    pub fn load_data_segmented<'a, P: Into<ObjectPathRef<'a>>>(
+        &self,
+        path: P,
+    ) -> Result<Vec<DataTypeVec>> {
+        let object_map = self.tdms_map.object_map(path.into().as_str())?;
+        if self.tdms_map.options.metadata_only {
+            return Err(TdmsError::MetadataOnly);
+        }
+        self.check_load_size(object_map)?;
 
-            // meta_data chunk size calculation during read-in only accounted for new objects,
-            // recalculate
-            let mut new_chunk_size = 0;
-            let mut new_channels_size = 0;
-
-            // First we have to establish the correct chunk_size and channels_size computation
-            // accounting for all live_objects
-            for key in self.live_objects.iter() {
-                let object_map = self.all_objects.get(key).unwrap();
-                new_chunk_size += object_map.last_object.no_bytes;
-                if let Some(raw_type) = object_map.last_object.raw_data_type {
-                    new_channels_size += match raw_type {
-                        // TODO no idea if this is correct i.e. how strings interleave
-                        DataTypeRaw::TdmsString => object_map.last_object.no_bytes,
-                        other => other.size()?,
-                    };
-                };
+        let mut segment_pairs: Vec<Vec<ReadPair>> = Vec::new();
+        for pair in object_map.read_map.iter().flat_map(ReadPair::chunks) {
+            match segment_pairs.last_mut() {
+                Some(pairs) if pairs[0].segment_index == pair.segment_index => pairs.push(pair),
+                _ => segment_pairs.push(vec![pair]),
             }
+        }
 
-            meta_data.chunk_size += new_chunk_size;
-            meta_data.channels_size += new_channels_size;
-
-            let no_chunks: u64 = if meta_data.chunk_size > 0 {
-                (segment.next_seg_offset - segment.raw_data_offset) / meta_data.chunk_size
-            } else {
-                0
-            };
+        let mut reader = self.readers.take()?;
+        segment_pairs
+            .iter()
+            .map(|pairs| {
+                read_pairs_vector(
+                    object_map,
+                    pairs,
+                    &mut *reader,
+                    self.tdms_map.options.lossy_strings,
+                )
+            })
+            .collect()
+    }
 
-            segment.no_chunks = no_chunks;
+    /// A min/max envelope of a numeric channel for plotting, the minimum and maximum of each of
+    /// buckets equal ranges of its values in order. There are fewer buckets if the channel has
+    /// fewer values. The channel is streamed rather than loaded, so only a block of values is
+    /// held at a time. NaN values are ignored unless a bucket has nothing else.
+    pub fn load_envelope<'a, P: Into<ObjectPathRef<'a>>>(
+        &self,
+        path: P,
+        buckets: usize,
+    ) -> Result<Vec<(f64, f64)>> {
+        let object_map = self.numeric_object_map(path.into().as_str())?;
 
-            // Now we can go over it again and calculate the new read_map points for the segment
-            self.update_indexes(&segment, &meta_data)?;
+        let total_values = object_map.total_values();
+        let buckets = (buckets as u64).min(total_values);
+        let mut envelope = vec![(f64::NAN, f64::NAN); buckets as usize];
+        if envelope.is_empty() {
+            return Ok(envelope);
         }
-
-        Ok(segment)
+        let mut index: u64 = 0;
+        self.stream_f64(object_map, |value| {
+            let bucket = (index as u128 * buckets as u128 / total_values as u128) as usize;
+            let (min, max) = &mut envelope[bucket];
+            *min = min.min(value);
+            *max = max.max(value);
+            index += 1;
+        })?;
+        Ok(envelope)
     }
 
-    fn update_indexes(&mut self, segment: &TdmsSegment, meta_data: &TdmsMetaData) -> Result<()> {
-        let mut relative_position: u64 = 0; // Used in computing read pairs as we go
-        for key in self.live_objects.iter() {
-            let object_map = self.all_objects.get_mut(key).unwrap();
-            let type_size = if let Some(raw_type) = object_map.last_object.raw_data_type {
-                match raw_type {
-                    // TODO no idea if this is correct i.e. how strings interleave
-                    DataTypeRaw::TdmsString => object_map.last_object.no_bytes,
-                    other => other.size()?,
-                }
-            } else {
-                0
-            };
-            debug!("Type Size: {}", type_size);
+    /// Load a numeric channel's values transformed by f, e.g. converting units or narrowing
+    /// to f32 to halve the memory needed. Values are streamed through f as f64 a block at a
+    /// time, so the channel's data is never held whole in its stored type. Fails with
+    /// `NotNumeric` for strings, timestamps and channels of type Void.
+    pub fn load_data_with<'a, P, F, T>(&self, path: P, mut f: F) -> Result<Vec<T>>
+    where
+        P: Into<ObjectPathRef<'a>>,
+        F: FnMut(f64) -> T,
+    {
+        let object_map = self.numeric_object_map(path.into().as_str())?;
 
-            //compute read pairs as we go to save double iteration over the objects map,
-            // only compute if size here is > 0
-            if object_map.last_object.no_bytes > 0 {
-                for i in 0..segment.no_chunks {
-                    let pair = ReadPair {
-                        start_index: segment.start_index
-                            + HEADER_LEN
-                            + segment.raw_data_offset
-                            + i * meta_data.chunk_size
-                            + relative_position,
-                        no_values: object_map.last_object.no_raw_vals.unwrap(),
-                        interleaved: segment
-                            .toc_mask
-                            .has_flag(TocProperties::KTocInterleavedData),
-                        stride: Some(meta_data.channels_size - type_size),
-                    };
+        let path = &object_map.last_object.object_path;
+        let mut values = Vec::with_capacity(vec_len::<T>(path, object_map.total_values())?);
+        self.stream_f64(object_map, |value| values.push(f(value)))?;
+        Ok(values)
+    }
 
-                    debug!("Read Pair {:?}", pair);
+    /// Load a numeric channel's values as f64 into buf, replacing its contents. The buffer's
+    /// allocation is reused, and only grows if it can't hold the channel, so repeated loads
+    /// e.g. while monitoring a file being written needn't allocate. Fails with `NotNumeric`
+    /// as load_data_with does.
+    pub fn load_data_into<'a, P: Into<ObjectPathRef<'a>>>(
+        &self,
+        path: P,
+        buf: &mut Vec<f64>,
+    ) -> Result<()> {
+        let object_map = self.numeric_object_map(path.into().as_str())?;
+
+        buf.clear();
+        let path = &object_map.last_object.object_path;
+        buf.reserve(vec_len::<f64>(path, object_map.total_values())?);
+        self.stream_f64(object_map, |value| buf.push(value))
+    }
 
-                    object_map.read_map.push(pair);
-                    object_map.total_bytes += object_map.last_object.no_bytes;
-                    object_map.total_values += object_map.last_object.no_raw_vals.unwrap() as usize;
-                    debug!("Accum values: {}", object_map.total_values);
+    /// Read the values of a channel from index range.start up to range.end, only reading the
+    /// chunks the range covers. Chunks of strings can't be read in part, so are read whole
+    /// and cut down.
+    fn read_value_range(&self, object_map: &ObjectMap, range: Range<u64>) -> Result<DataTypeVec> {
+        let value_size = object_map
+            .last_object
+            .raw_data_type
+            .and_then(|data_type| data_type.size().ok());
+        let mut pairs = Vec::new();
+        let mut skipped = None; // Values before the range in the string chunks read
+        let mut first: u64 = 0;
+        for chunk in object_map.read_map.iter().flat_map(ReadPair::chunks) {
+            let (start, end) = (
+                range.start.max(first),
+                range.end.min(first + chunk.no_values),
+            );
+            if start < end {
+                match value_size {
+                    Some(size) => pairs.push(chunk.value_range(size, start - first, end - start)),
+                    None => {
+                        skipped.get_or_insert(start - first);
+                        pairs.push(chunk.clone());
+                    }
                 }
-            };
+            }
+            first += chunk.no_values;
+            if first >= range.end {
+                break;
+            }
+        }
 
-            debug!("Accum Obj Size: {}", object_map.total_bytes);
+        let data = read_pairs_vector(
+            object_map,
+            &pairs,
+            &mut *self.readers.take()?,
+            self.tdms_map.options.lossy_strings,
+        )?;
+        Ok(match (data, skipped) {
+            (DataTypeVec::TdmsString(strings), Some(skipped)) => DataTypeVec::TdmsString(
+                strings
+                    .into_iter()
+                    .skip(skipped as usize)
+                    .take((range.end - range.start) as usize)
+                    .collect(),
+            ),
+            (data, _) => data,
+        })
+    }
 
-            object_map.bigendian = segment.toc_mask.has_flag(TocProperties::KTocBigEndian);
+    /// The map of a channel whose values can be streamed as f64
+    fn numeric_object_map(&self, path: &str) -> Result<&ObjectMap> {
+        let object_map = self.tdms_map.object_map(path)?;
+        if self.tdms_map.options.metadata_only {
+            return Err(TdmsError::MetadataOnly);
+        }
+        let data_type = object_map
+            .last_object
+            .raw_data_type
+            .ok_or(TdmsError::ObjectHasNoRawData)?;
+        if matches!(
+            data_type,
+            DataTypeRaw::Void | DataTypeRaw::TdmsString | DataTypeRaw::TimeStamp
+        ) {
+            return Err(TdmsError::NotNumeric {
+                path: object_map.last_object.object_path.clone(),
+                data_type,
+            });
+        }
+        Ok(object_map)
+    }
 
-            // If interleaved then the start position depends on the item sizes, if continuous
-            // then it's the number of values x type size i.e. "total_bytes"
-            debug!(
-                "Interleaved data: {}",
-                segment
-                    .toc_mask
-                    .has_flag(TocProperties::KTocInterleavedData)
-            );
-            debug!("Flags: {:b}", segment.toc_mask.flags);
-            if segment
-                .toc_mask
-                .has_flag(TocProperties::KTocInterleavedData)
-            {
-                relative_position += type_size;
-            } else {
-                relative_position += object_map.last_object.no_bytes;
+    /// Read a numeric channel's values in order, decoding at most STREAM_BLOCK_VALUES at a
+    /// time, and pass each to f as f64
+    fn stream_f64(&self, object_map: &ObjectMap, mut f: impl FnMut(f64)) -> Result<()> {
+        let value_size = object_map
+            .last_object
+            .raw_data_type
+            .and_then(|data_type| data_type.size().ok());
+        let mut reader = self.readers.take()?;
+        for chunk in object_map.read_map.iter().flat_map(ReadPair::chunks) {
+            for piece in chunk.pieces(value_size, STREAM_BLOCK_VALUES) {
+                let values = read_pairs_vector(
+                    object_map,
+                    std::slice::from_ref(&piece),
+                    &mut *reader,
+                    false,
+                )?;
+                values.iter_f64().for_each(&mut f);
             }
-            debug!("relative position: {}", relative_position);
         }
         Ok(())
     }
-}
 
-/// A TdmsSegment consists of a 28 byte lead in followed by a series of optional MetaData
-/// properties. This is followed in turn by raw data if it exists.
-#[derive(Debug)]
-pub struct TdmsSegment {
-    // Segment lead in data is 28 bytes long
-    file_tag: u32, // "TDSm" always the same
-    toc_mask: TocMask,
-    version_no: u32,
-    next_seg_offset: u64,
-    raw_data_offset: u64,
-    // Ancillary helper fields
-    start_index: u64,
-    no_chunks: u64,
-}
+    /// Load only the data a channel has in one segment, given by its index in `segments`, e.g.
+    /// to inspect what was written in a particular segment. Empty if the channel has no data
+    /// in that segment, fails with `SegmentOutOfRange` if the file has no such segment.
+    pub fn load_segment_data<'a, P: Into<ObjectPathRef<'a>>>(
+        &self,
+        path: P,
+        segment_index: usize,
+    ) -> Result<DataTypeVec> {
+        let object_map = self.tdms_map.object_map(path.into().as_str())?;
+        if self.tdms_map.options.metadata_only {
+            return Err(TdmsError::MetadataOnly);
+        }
+        let no_segments = self.tdms_map.segments.len();
+        if segment_index >= no_segments {
+            return Err(TdmsError::SegmentOutOfRange {
+                index: segment_index,
+                no_segments,
+            });
+        }
 
-impl fmt::Display for TdmsSegment {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        writeln!(f, "Segment filetag:\t{:X}", self.file_tag)?;
-        writeln!(f, "Segment flags:\t{:?}", self.toc_mask)?;
-        writeln!(f, "Version no.:\t\t{}", self.version_no)?;
-        writeln!(f, "Next segment offset:\t{}", self.next_seg_offset)?;
-        writeln!(f, "Raw data offset:\t{}", self.raw_data_offset)?;
-        writeln!(f, "No_chunks:\t{}", self.no_chunks)?;
-        Ok(())
+        let pairs: Vec<ReadPair> = object_map
+            .read_map
+            .iter()
+            .flat_map(ReadPair::chunks)
+            .filter(|pair| pair.segment_index == segment_index)
+            .collect();
+        read_pairs_vector(
+            object_map,
+            &pairs,
+            &mut *self.readers.take()?,
+            self.tdms_map.options.lossy_strings,
+        )
     }
-}
 
-impl TdmsSegment {
-    pub fn new(start_index: u64) -> TdmsSegment {
-        TdmsSegment {
-            start_index,
-            file_tag: 0,
-            toc_mask: TocMask::from_flags(0),
-            version_no: 0,
-            next_seg_offset: 0,
-            raw_data_offset: 0,
-            no_chunks: 0,
+    /// The offsets into a channel's loaded data at which each segment it has data in ends, so
+    /// the last is the total number of values. Splitting the data at these offsets gives the
+    /// vectors of `load_data_segmented`.
+    pub fn segment_boundaries<'a, P: Into<ObjectPathRef<'a>>>(&self, path: P) -> Result<Vec<u64>> {
+        let object_map = self.tdms_map.object_map(path.into().as_str())?;
+
+        let mut boundaries: Vec<u64> = Vec::new();
+        let mut offset = 0;
+        let mut segment_index = None;
+        for pair in object_map.read_map.iter().flat_map(ReadPair::chunks) {
+            offset += pair.no_values;
+            if segment_index == Some(pair.segment_index) {
+                *boundaries.last_mut().unwrap() = offset;
+            } else {
+                boundaries.push(offset);
+                segment_index = Some(pair.segment_index);
+            }
         }
+        Ok(boundaries)
     }
-}
-
-#[derive(Debug)]
-pub struct TdmsMetaData {
-    no_objects: u32,
-    objects: Vec<String>,
-    // chunk_size is used in combination with segment index information to
-    // figure out how many blocks of channel data there are in any given
-    // segment
-    chunk_size: u64,
-    /// The sum total of byte sizes for each channel's data type
-    channels_size: u64,
-}
 
-impl fmt::Display for TdmsMetaData {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        writeln!(f, "No. objects:\t{}", self.no_objects)?;
-        writeln!(f, "Chunk Size:\t{}", self.chunk_size)?;
-        for obj in &self.objects {
-            writeln!(f, "__Object__")?;
-            write!(f, "{}", obj)?;
+    /// A summary of the file, accumulated while mapping it. Whether any segment is interleaved,
+    /// big endian or holds DAQmx data covers all segments, truncated only the last.
+    pub fn info(&self) -> TdmsFileInfo {
+        let flags = TocMask::from_flags(self.tdms_map.toc_flags);
+        TdmsFileInfo {
+            version: self.tdms_version(),
+            no_segments: self.tdms_map.segments.len(),
+            interleaved: flags.has_flag(TocProperties::KTocInterleavedData),
+            big_endian: flags.has_flag(TocProperties::KTocBigEndian),
+            daqmx: flags.has_flag(TocProperties::KTocDAQmxRawData),
+            total_raw_bytes: self.tdms_map.raw_bytes,
+            channel_count: self.channel_count(),
+            truncated: self.tdms_map.truncated,
         }
-        Ok(())
     }
-}
 
-impl TdmsMetaData {
-    /// Creates a new meta data struct and reads objects into it.
-    /// abs_data_index points to the index of raw data in the segment
-    /// with respect to the start of the file.
-    /// Read in objects, keep track of accumulating channel size so objects can be loaded
-    /// later by directly addressing their constituent addresses
-    pub fn read_metadata<R: Read + Seek, O: ByteOrder>(
-        tdms_map: &mut TdmsMap,
-        reader: &mut R,
-    ) -> Result<TdmsMetaData> {
-        let no_objects = reader.read_u32::<O>()?;
-
-        let mut chunk_size: u64 = 0;
-        let mut channels_size: u64 = 0;
-        let mut objects: Vec<String> = Vec::new();
-
-        for _i in 0..no_objects {
-            let path = read_string::<R, O>(reader)?;
-            // Read in an object including properties
-            TdmsObject::update_read_object::<R, O>(tdms_map, path.clone(), reader)?;
-            let obj = &tdms_map.all_objects.get(&path).unwrap().last_object;
-            // Keep track of the accumulating raw data size for objects
-            chunk_size += obj.no_bytes;
-
-            if let Some(raw_type) = obj.raw_data_type {
-                channels_size += match raw_type {
-                    DataTypeRaw::TdmsString => obj.no_bytes, // TODO no idea if this is correct i.e. how strings interleave
-                    other => other.size()?,
-                };
-            };
+    /// Summaries of the file's segments in file order, including their lead in and ToC flags
+    pub fn segments(&self) -> Vec<SegmentInfo> {
+        self.tdms_map
+            .segments
+            .iter()
+            .map(TdmsSegment::info)
+            .collect()
+    }
+
+    /// What was skipped to map the file, if it was damaged and opened with options that skip
+    /// past damage rather than fail
+    pub fn recovery_info(&self) -> &RecoveryInfo {
+        &self.tdms_map.recovery
+    }
+
+    /// Whether the file was mapped from its `.tdms_index` file rather than by walking the file
+    /// itself, see `ReadOptions::index_file`
+    pub fn mapped_from_index(&self) -> bool {
+        self.tdms_map.from_index
+    }
 
-            objects.push(path);
+    /// Everything breaking the spec that mapping carried on past, in the order it was found.
+    /// Always empty with `ParseMode::Strict`, where each is an error instead.
+    pub fn warnings(&self) -> &[TdmsWarning] {
+        &self.tdms_map.warnings
+    }
+
+    /// A hex dump of a segment's 28 byte lead in, given by its index in `segments`, with the
+    /// tag, ToC mask, version and offsets each annotated, for attaching to bug reports. The
+    /// bytes are as parsed, rebuilt from the mapped segment rather than read again.
+    pub fn dump_segment_lead_in(&self, segment_index: usize) -> Result<String> {
+        let no_segments = self.tdms_map.segments.len();
+        let segment =
+            self.tdms_map
+                .segments
+                .get(segment_index)
+                .ok_or(TdmsError::SegmentOutOfRange {
+                    index: segment_index,
+                    no_segments,
+                })?;
+        Ok(format!(
+            "Segment {} lead in at byte {}\n{}",
+            segment_index,
+            segment.start_index,
+            segment.dump_lead_in()
+        ))
+    }
+
+    /// Returns the TDMS version the file was written with, 4713 for 2.0 and 4712 for 1.0, read
+    /// from the first segment. None if the file has no segments.
+    pub fn tdms_version(&self) -> Option<u32> {
+        self.tdms_map
+            .segments
+            .first()
+            .map(|segment| segment.version_no)
+    }
+
+    /// Return a vector of object paths
+    pub fn all_objects(&self) -> Vec<&str> {
+        let mut objects: Vec<&str> = Vec::new();
+
+        for key in self.tdms_map.all_objects.keys() {
+            objects.push(key)
         }
+        objects
+    }
 
-        Ok(TdmsMetaData {
-            no_objects,
-            objects,
-            chunk_size,
-            channels_size,
-        })
+    /// Return handles to the channels with data, carrying the parsed group and channel names
+    pub fn channels(&self) -> Vec<ChannelHandle> {
+        self.data_objects()
+            .into_iter()
+            .map(ChannelHandle::new)
+            .collect()
     }
-}
 
-#[derive(Debug, Clone, Default)]
-pub struct TdmsObject {
-    object_path: String,
-    index_info_len: u32, // The length in bytes of the indexing info for raw data, including the length of this field. Should always be 20 (defined length) or 28 (variable length)
-    raw_data_type: Option<DataTypeRaw>, // appears in file as u32.
-    raw_data_dim: Option<u32>,
-    no_raw_vals: Option<u64>,
-    no_bytes: u64, // of raw data in bytes, appears in file for variable length types (String) only. comptued otherwise
-    no_properties: u32,
-    daqmx_info: Option<DAQMxInfo>,
-    properties: IndexMap<String, ObjectProperty>,
-}
+    /// Return a vector of channel paths for channels with data
+    pub fn data_objects(&self) -> Vec<&str> {
+        let mut objects: Vec<&str> = Vec::new();
 
-#[derive(Debug, Clone)]
-pub struct DAQMxInfo {
-    formatvec_size: u32,
-    scalers: Vec<DAQMxScaler>,
-    widthvec_size: u32,
-    widthvec: Vec<u32>,
-}
+        for (key, object_map) in &self.tdms_map.all_objects {
+            let object = &object_map.last_object;
+            if object.no_bytes > 0 && object.raw_data_type.is_some() {
+                objects.push(key);
+            }
+        }
+        objects
+    }
 
-#[derive(Debug, Clone)]
-pub struct DAQMxScaler {
-    daqmx_data_type: DataTypeRaw,
-    daqmx_rawbuff_indx: u32,
-    daqmx_raw_byte_offset: u32,
-    sample_format_bitmap: u32,
-    scale_id: u32,
-}
+    /// Returns the dimension of a channel's raw data. Values for channels with a dimension
+    /// greater than 1 are loaded flattened, dimension values per raw value.
+    pub fn channel_dimension<'a, P: Into<ObjectPathRef<'a>>>(&self, path: P) -> Result<u32> {
+        let object_map = self.tdms_map.object_map(path.into().as_str())?;
 
-impl DAQMxScaler {
-    pub fn new<R: Read + Seek, O: ByteOrder>(reader: &mut R) -> Result<DAQMxScaler> {
-        let scaler = DAQMxScaler {
-            daqmx_data_type: DataTypeRaw::from_u32(reader.read_u32::<O>()?)?,
-            daqmx_rawbuff_indx: reader.read_u32::<O>()?,
-            daqmx_raw_byte_offset: reader.read_u32::<O>()?,
-            sample_format_bitmap: reader.read_u32::<O>()?,
-            scale_id: reader.read_u32::<O>()?,
-        };
-        Ok(scaler)
+        Ok(object_map.dimension())
     }
-}
 
-impl fmt::Display for TdmsObject {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        writeln!(f, "Obj path:\t{}", self.object_path)?;
-        writeln!(f, "Index info length:\t{:x}", self.index_info_len)?;
-        writeln!(f, "Raw data type:\t{:?}", self.raw_data_type)?;
-        writeln!(f, "Raw data dim:\t{:?}", self.raw_data_dim)?;
-        writeln!(f, "No. raw vals:\t{:?}", self.no_raw_vals)?;
-        writeln!(f, "Total size:\t{:?}", self.no_bytes)?;
-        writeln!(f, "No. properties:\t{:?}", self.no_properties)?;
-        writeln!(f, "Actual property count:\t{:?}", self.properties.len())?;
-        for (_key, property) in self.properties.iter() {
-            writeln!(f, "__Property__")?;
-            write!(f, "{}", property)?;
+    /// The number of values in a channel, as load_data would return, taken from the map
+    /// without reading the file. Objects without data, e.g. groups, have none. Fails with
+    /// `MetadataOnly` if the file was opened metadata only, as no lengths are mapped.
+    pub fn channel_len<'a, P: Into<ObjectPathRef<'a>>>(&self, path: P) -> Result<u64> {
+        let object_map = self.tdms_map.object_map(path.into().as_str())?;
+        if self.tdms_map.options.metadata_only {
+            return Err(TdmsError::MetadataOnly);
         }
+        Ok(object_map.total_values())
+    }
 
-        Ok(())
+    /// Find a channel with data by its channel name alone, ignoring case and surrounding
+    /// whitespace. Returns the full path only if exactly one channel matches.
+    pub fn find_channel(&self, name: &str) -> Option<&str> {
+        let name = name.trim().to_lowercase();
+        let mut matches = self.data_objects().into_iter().filter(|path| {
+            path_components(path)
+                .get(1)
+                .is_some_and(|channel| channel.trim().to_lowercase() == name)
+        });
+
+        match (matches.next(), matches.next()) {
+            (Some(path), None) => Some(path),
+            _ => None,
+        }
     }
-}
 
-impl TdmsObject {
-    /// Read an object from file including its properties, update the object's information
-    /// in the all_objects map.
-    pub fn update_read_object<R: Read + Seek, O: ByteOrder>(
-        tdms_map: &mut TdmsMap,
-        path: String,
-        reader: &mut R,
-    ) -> Result<()> {
-        // check existence now for later use
-        let prior_object = tdms_map.all_objects.contains_key(&path);
+    /// Returns true if the path is a channel with data
+    pub fn has_channel<'a, P: Into<ObjectPathRef<'a>>>(&self, path: P) -> bool {
+        self.tdms_map
+            .all_objects
+            .get(path.into().as_str())
+            .is_some_and(|object_map| {
+                let object = &object_map.last_object;
+                object.no_bytes > 0 && object.raw_data_type.is_some()
+            })
+    }
+
+    /// Returns the number of channels with data
+    pub fn channel_count(&self) -> usize {
+        self.data_objects().len()
+    }
 
-        // Try to obtain a reference to the last record of the objects
-        // to update in place, create a default entry if none present
-        let new_object = &mut tdms_map
+    /// Returns the number of group objects in the file
+    pub fn group_count(&self) -> usize {
+        self.tdms_map
             .all_objects
-            .entry(path.clone())
-            .or_default()
-            .last_object;
+            .keys()
+            .filter(|path| path_components(path).len() == 1)
+            .count()
+    }
 
-        debug!("object_path: {}", path);
-        new_object.object_path = path;
-        for live in &tdms_map.live_objects {
-            debug!("Map object: {}", live);
-        }
+    /// The file level properties held by the root object "/", such as `name` and `Author`,
+    /// keyed by name. None if the file has no root object.
+    pub fn file_properties(&self) -> Option<&IndexMap<String, ObjectProperty>> {
+        self.tdms_map
+            .all_objects
+            .get("/")
+            .and_then(|object_map| self.properties_of(object_map).ok())
+    }
 
-        new_object.index_info_len = reader.read_u32::<O>()?;
+    /// Look a property up on a channel, then its group, then the root object, returning the
+    /// first found. This is how properties set at a higher level apply to channels, e.g. a
+    /// start time set once for a whole group.
+    pub fn resolve_property<'a, P: Into<ObjectPathRef<'a>>>(
+        &self,
+        channel_path: P,
+        name: &str,
+    ) -> Option<&DataType> {
+        let channel_path = channel_path.into();
+        let (group, _) = parse_object_path(channel_path.as_str()).ok()?;
+        let group_path = group.map(|group| object_path(Some(&group), None));
 
-        debug!("index len: {}", new_object.index_info_len);
-        if new_object.index_info_len == NO_RAW_DATA {
-            new_object.update_properties::<R, O>(reader)?;
-        } else if new_object.index_info_len == DATA_INDEX_MATCHES_PREVIOUS {
-            // raw data index for this object should be identical to previous segments.
-            if !prior_object {
-                return Err(TdmsError::NoPreviousObject);
-            } else {
-                new_object.update_properties::<R, O>(reader)?;
+        let property = [
+            Some(channel_path.as_str()),
+            group_path.as_deref(),
+            Some("/"),
+        ]
+        .into_iter()
+        .flatten()
+        .filter_map(|path| self.tdms_map.all_objects.get(path))
+        .filter_map(|object_map| self.properties_of(object_map).ok())
+        .find_map(|properties| properties.get(name));
+        property.map(ObjectProperty::value)
+    }
+
+    /// A property of an object as T, see `TdmsObject::get_property_as`
+    pub fn get_property_as<'a, T: PropertyValue>(
+        &self,
+        path: impl Into<ObjectPathRef<'a>>,
+        name: &str,
+    ) -> Result<T> {
+        let object_map = self.tdms_map.object_map(path.into().as_str())?;
+        let property = self.properties_of(object_map)?.get(name).ok_or_else(|| {
+            TdmsError::MissingProperty {
+                path: object_map.last_object.object_path.clone(),
+                property: name.to_string(),
             }
-        } else if new_object.index_info_len == FORMAT_CHANGING_SCALER {
-            new_object.read_sizeinfo::<R, O>(reader)?;
-            new_object.read_daqmxinfo::<R, O>(reader)?;
-            new_object.update_properties::<R, O>(reader)?;
-        } else if new_object.index_info_len == DIGITAL_LINE_SCALER {
-            new_object.read_sizeinfo::<R, O>(reader)?;
-            new_object.read_daqmxinfo::<R, O>(reader)?;
-            new_object.update_properties::<R, O>(reader)?;
-        } else {
-            // This is a fresh, non DAQmx object, or amount of data has changed
-            new_object.read_sizeinfo::<R, O>(reader)?;
-            new_object.update_properties::<R, O>(reader)?;
-        }
-        Ok(())
+        })?;
+        T::try_from(property.value())
     }
 
-    fn read_sizeinfo<R: Read + Seek, O: ByteOrder>(&mut self, reader: &mut R) -> Result<&mut Self> {
-        let raw_data_type = DataTypeRaw::from_u32(reader.read_u32::<O>()?)?;
-        let dim = reader.read_u32::<O>()?;
-        let no_vals = reader.read_u64::<O>()?;
+    /// Log an objects properties at info level, the library never writes to stdout itself
+    pub fn object_properties<'a, P: Into<ObjectPathRef<'a>>>(&self, path: P) -> Result<()> {
+        let object = self.tdms_map.object_map(path.into().as_str())?;
 
-        // total_bytes (bytes) is either recorded in the file if data is TdmsString or else
-        // must be computed. Size() will return an error if called on DataTypeRaw::TdmsString
-        // which is why there is a guard clause here.
-        self.no_bytes = match raw_data_type {
-            DataTypeRaw::TdmsString => reader.read_u64::<O>()?,
-            other => other.size()? * no_vals * dim as u64,
-        };
-        debug!("Object total bytes: {}", self.no_bytes);
-        debug!("Data Dim: {}", dim);
-        debug!("No Raw Vals: {}", no_vals);
-        self.raw_data_type = Some(raw_data_type);
-        self.raw_data_dim = Some(dim);
-        self.no_raw_vals = Some(no_vals);
+        info!("{}", object.last_object);
 
-        Ok(self)
+        Ok(())
     }
 
-    fn read_daqmxinfo<R: Read + Seek, O: ByteOrder>(
-        &mut self,
-        reader: &mut R,
-    ) -> Result<&mut Self> {
-        let daqmx_formatvec_size = reader.read_u32::<O>()?;
+    /// Return a channel's read pairs as (start_index, no_values, interleaved) tuples, one per
+    /// raw data chunk the channel appears in, describing where its data lies in the file.
+    pub fn channel_read_pairs<'a, P: Into<ObjectPathRef<'a>>>(
+        &self,
+        path: P,
+    ) -> Result<Vec<(u64, u64, bool)>> {
+        let object = self.tdms_map.object_map(path.into().as_str())?;
 
-        let mut scalers: Vec<DAQMxScaler> = Vec::new();
-        for _i in 0..daqmx_formatvec_size {
-            let scaler = DAQMxScaler::new::<R, O>(reader)?;
-            scalers.push(scaler);
-        }
+        Ok(object
+            .read_map
+            .iter()
+            .flat_map(ReadPair::chunks)
+            .map(|pair| (pair.start_index, pair.no_values, pair.interleaved))
+            .collect())
+    }
+}
 
-        let daqmx_datawidthvec_size = reader.read_u32::<O>()?;
-        let mut daqmx_data_width_vec = Vec::with_capacity(daqmx_datawidthvec_size as usize);
-        for _i in 0..daqmx_datawidthvec_size {
-            daqmx_data_width_vec.push(reader.read_u32::<O>()?);
+/// Diagnostic function to log current location for debugging purposes
+pub fn current_loc<R: Read + Seek>(reader: &mut R) {
+    debug!("{:?}", reader.stream_position());
+}
+
+/// Levenshtein distance between two strings, counted in chars
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, a_char) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + (a_char != *b_char) as usize;
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
         }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
 
-        self.daqmx_info = Some(DAQMxInfo {
-            formatvec_size: daqmx_formatvec_size,
-            scalers,
-            widthvec_size: daqmx_datawidthvec_size,
-            widthvec: daqmx_data_width_vec,
-        });
+/// Sum the raw data bytes the objects contribute to a chunk, and the sizes of their data types
+/// which give the stride of interleaved data. DAQmx objects all share one raw buffer, so it's
+/// only counted once.
+fn chunk_sizes<'a>(objects: impl Iterator<Item = &'a TdmsObject> + Clone) -> Result<(u64, u64)> {
+    let mut chunk_size = 0;
+    let mut channels_size = 0;
+    let daqmx_size: u64 = daqmx_buffer_sizes(objects.clone()).iter().sum();
 
-        Ok(self)
+    for obj in objects {
+        // Objects without values in this segment take up no space between the others' values
+        if obj.daqmx_info.is_some() || obj.segment_bytes() == 0 {
+            continue;
+        }
+        chunk_size += obj.no_bytes;
+        // Strings have no fixed size so can't be interleaved, update_indexes rejects them
+        if let Some(raw_type) = obj.raw_data_type {
+            channels_size += match raw_type {
+                DataTypeRaw::TdmsString => 0,
+                other => other.size()?,
+            };
+        };
     }
+    Ok((chunk_size + daqmx_size, channels_size))
+}
 
-    /// Read the object properties, update if that property already exists for that object
-    fn update_properties<R: Read + Seek, O: ByteOrder>(
-        &mut self,
-        reader: &mut R,
-    ) -> Result<&mut Self> {
-        self.no_properties = reader.read_u32::<O>()?;
-        if self.no_properties > 0 {
-            for _i in 0..self.no_properties {
-                let property = ObjectProperty::read_property::<R, O>(reader)?;
-                // overwrite the previous version of the property or else insert new property
-                self.properties.insert(property.prop_name.clone(), property);
+/// Sizes in bytes of the raw buffers shared by the DAQmx objects, in the order they're laid out
+/// in a chunk. Each buffer holds a row of its width for every sample of the objects reading from
+/// it, a buffer no object reads from is assumed to hold as many samples as the first object.
+fn daqmx_buffer_sizes<'a>(objects: impl Iterator<Item = &'a TdmsObject>) -> Vec<u64> {
+    let mut sizes: Vec<Option<u64>> = Vec::new();
+    let mut widths: &[u32] = &[];
+    let mut default_samples = 0;
+
+    for obj in objects.filter(|obj| obj.has_raw_data) {
+        if let Some(info) = &obj.daqmx_info {
+            if widths.is_empty() {
+                widths = &info.widthvec;
+                sizes = vec![None; widths.len()];
+                default_samples = obj.no_raw_vals.unwrap_or(0);
+            }
+            if let Some(size) = sizes.get_mut(info.raw_buffer()) {
+                *size = Some(size.unwrap_or(0).max(obj.no_bytes));
             }
         }
+    }
 
-        Ok(self)
+    sizes
+        .iter()
+        .zip(widths)
+        .map(|(size, &width)| size.unwrap_or(width as u64 * default_samples))
+        .collect()
+}
+
+/// Update the order objects appear in raw data with the objects listed in a segment's meta
+/// data. A new object list replaces it, otherwise newly listed objects are added to the end.
+fn update_live_objects(live_objects: &mut Vec<String>, listed: &[String], new_object_list: bool) {
+    if new_object_list {
+        // if new_obj list has been set, then the objects listed are everything and we could
+        // have a totally new ordering of data for this segment
+        *live_objects = listed.to_vec();
+    } else {
+        // Need to iterate over the new list of objects in the segment, this list should only contain newly added objects
+        for object_path in listed {
+            // If the object isn't in the live objects then it is truly new, so push it. If it is there
+            // then something about the object has changed but its order is still correct.
+            if !live_objects.contains(object_path) {
+                live_objects.push(object_path.clone());
+            }
+        }
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct ObjectProperty {
-    prop_name: String,
-    data_type: DataTypeRaw,
-    property: DataType,
+/// Returns true if the tag is one that starts a segment of a .tdms or .tdms_index file
+pub fn is_segment_tag(tag: u32) -> bool {
+    tag == TDMS_TAG || tag == TDMS_INDEX_TAG
 }
 
-impl fmt::Display for ObjectProperty {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        writeln!(f, "Property name: {}", self.prop_name)?;
-        writeln!(f, "Property datatype: {:?}", self.data_type)?;
-        writeln!(f, "Property val: {:?}", self.property)?;
-        Ok(())
+/// Find the address of the first segment tag at or after from and before end, if any
+fn find_segment_tag<R: Read + Seek>(reader: &mut R, from: u64, end: u64) -> Result<Option<u64>> {
+    const BLOCK_BYTES: u64 = 64 * 1024;
+    let tag = TDMS_TAG.to_le_bytes();
+    let mut position = from;
+    let mut block = Vec::new();
+    while position + tag.len() as u64 <= end {
+        let len = BLOCK_BYTES.min(end - position);
+        block.resize(len as usize, 0);
+        reader.seek(SeekFrom::Start(position))?;
+        reader.read_exact(&mut block)?;
+        if let Some(i) = block.windows(tag.len()).position(|window| window == tag) {
+            return Ok(Some(position + i as u64));
+        }
+        // Overlap the blocks so a tag straddling two is still found
+        position += len - (tag.len() as u64 - 1);
     }
+    Ok(None)
 }
 
-impl ObjectProperty {
-    /// Instantiate a property and read into it.
-    pub fn read_property<R: Read + Seek, O: ByteOrder>(reader: &mut R) -> Result<ObjectProperty> {
-        let prop_name = read_string::<R, O>(reader)?;
+/// Treat an unexpected end of file while reading a segment as there being no segment, any
+/// other error is passed on
+fn eof_as_none(result: Result<TdmsSegment>) -> Result<Option<TdmsSegment>> {
+    match result {
+        Ok(segment) => Ok(Some(segment)),
+        Err(err) => match err.root_cause() {
+            TdmsError::Io(e) if e.kind() == ErrorKind::UnexpectedEof => Ok(None),
+            _ => Err(err),
+        },
+    }
+}
+
+/// Represents the contents of a Tdms file which consists of a series  of segments + ancillary data which is created to index those segments.
+#[derive(Debug, Clone)]
+pub struct TdmsMap {
+    segments: Vec<TdmsSegment>,
+    pub all_objects: IndexMap<String, ObjectMap>, // Keeps track of all objects in file and their read maps, order not important for this one, using indexmap to avoid running multiple hashmap types.
+    live_objects: Vec<String>, // Keeps track of order of objects accumulated over segments, is reset when kToCNewObjectList flag is detected
+    next_segment_address: u64, // Address the next segment is expected at, mapping resumes from here on refresh
+    channel_filter: Option<String>, // If set, read maps are only built for this object path
+    toc_flags: u32,            // Every ToC flag set in any segment mapped so far
+    raw_bytes: u64,            // Raw data in all segments mapped so far
+    truncated: bool, // Whether the last segment runs past the end of the file or couldn't be mapped
+    recovery: RecoveryInfo,
+    warnings: Vec<TdmsWarning>, // What breaks the spec that mapping carried on past
+    from_index: bool,           // Whether the segments were mapped from a .tdms_index file
+    before_unfinished: Option<Box<TdmsMap>>, // The map before a final segment still being written, refresh maps from it
+    options: ReadOptions,
+}
+
+impl TdmsMap {
+    fn new(options: ReadOptions) -> TdmsMap {
+        TdmsMap {
+            segments: Vec::new(),
+            all_objects: IndexMap::new(),
+            live_objects: Vec::new(),
+            next_segment_address: 0,
+            channel_filter: None,
+            toc_flags: 0,
+            raw_bytes: 0,
+            truncated: false,
+            recovery: RecoveryInfo::default(),
+            warnings: Vec::new(),
+            from_index: false,
+            before_unfinished: None,
+            options: options.resolved(),
+        }
+    }
+
+    /// Handle something breaking the spec in the segment being mapped. It's an error in strict
+    /// mode or unless tolerated, otherwise it's logged and kept as a warning and mapping
+    /// carries on.
+    fn anomaly(&mut self, reason: WarningReason, offset: u64, tolerated: bool) -> Result<()> {
+        if !tolerated || self.options.parse_mode == Some(ParseMode::Strict) {
+            return Err(reason.into());
+        }
+        let warning = TdmsWarning {
+            segment: self.segments.len(),
+            offset,
+            reason,
+        };
+        warn!("{}", warning);
+        self.warnings.push(warning);
+        Ok(())
+    }
+
+    /// Look up an object's map by path. If it isn't present the error carries the closest
+    /// matching paths to help track down typos and escaping mistakes.
+    fn object_map(&self, path: &str) -> Result<&ObjectMap> {
+        self.all_objects
+            .get(path)
+            .ok_or_else(|| TdmsError::ChannelNotFound {
+                path: path.to_string(),
+                closest: self.closest_paths(path, 3),
+            })
+    }
+
+    /// Return up to n object paths ordered by edit distance to the given path
+    fn closest_paths(&self, path: &str, n: usize) -> Vec<String> {
+        let mut scored: Vec<(usize, &String)> = self
+            .all_objects
+            .keys()
+            .map(|key| (edit_distance(path, key), key))
+            .collect();
+        scored.sort();
+        scored
+            .into_iter()
+            .take(n)
+            .map(|(_, key)| key.clone())
+            .collect()
+    }
+
+    /// Walk the file attempting to load the segment meta data and objects.
+    /// Raw data is not loaded during these reads in the interest of Lazy Loading
+    /// i.e. memory efficienct handling of very large files.
+    /// Mapping starts from the last known segment address, so calling this again after the file
+    /// has grown only maps the new segments.
+    fn map_segments<R: Read + Seek>(
+        &mut self,
+        reader: &mut R,
+        file_length: u64,
+    ) -> Result<&mut Self> {
+        self.map_segments_with_progress(reader, file_length, &mut |_, _| {})
+    }
+
+    /// Map segments as map_segments, calling progress with the next segment address and the
+    /// file length after each segment
+    fn map_segments_with_progress<R: Read + Seek>(
+        &mut self,
+        reader: &mut R,
+        file_length: u64,
+        progress: &mut dyn FnMut(u64, u64),
+    ) -> Result<&mut Self> {
+        // If the file is corrupted, the last segment will contain 0xFFFF_FFFF for the "next segment offset".
+        // In this case the reader will attempt to map the segment but will hit an Unexpected end of file error
+        // while doing so. The segment address is left pointing at the corrupted segment so that a later
+        // call can pick it up again once a writer has finished it. The same goes for a segment that
+        // maps but is still being written, which is mapped again once the file has grown.
+        while self.next_segment_address < file_length {
+            let address = self.next_segment_address;
+            let result = eof_as_none(self.read_segment(reader, address, address, file_length));
+            // Carry on from the next segment tag after one whose lead in can't be trusted
+            if let (true, Err(e)) = (self.options.skip_overlapping_segments, &result) {
+                if let TdmsError::OverlappingSegments { first, second } = *e.root_cause() {
+                    let reason = WarningReason::OverlappingSegments { first, second };
+                    self.anomaly(reason, address, true)?;
+                    self.recovery.overlapping_segments.push((first, second));
+                    self.next_segment_address =
+                        find_segment_tag(reader, address + HEADER_LEN, file_length)?
+                            .unwrap_or(file_length);
+                    continue;
+                }
+            }
+            let segment = match result? {
+                Some(segment) => segment,
+                None => {
+                    // anomaly logs this as a warning, there is nothing more to report
+                    let reason = WarningReason::UnreadableSegment {
+                        start_index: address,
+                    };
+                    self.anomaly(reason, address, true)?;
+                    self.truncated = true;
+                    return Ok(self);
+                }
+            };
+
+            // A corrupt offset running past the end of the file could overflow
+            let unfinished = segment.unfinished;
+            if !unfinished {
+                self.next_segment_address = self
+                    .next_segment_address
+                    .saturating_add(segment.next_seg_offset.saturating_add(HEADER_LEN));
+            }
+
+            self.push_segment(segment);
+            if unfinished {
+                progress(file_length, file_length);
+                break;
+            }
+            progress(self.next_segment_address.min(file_length), file_length);
+        }
+        debug!("Completed read");
+        Ok(self)
+    }
+
+    /// Map the file from the `.tdms_index` file at index_path
+    fn map_index_file(
+        &mut self,
+        index_path: &path::Path,
+        capacity: usize,
+        file_length: u64,
+    ) -> Result<&mut Self> {
+        let index_fh = fs::File::open(index_path)?;
+        let mut index_reader = io::BufReader::with_capacity(capacity, index_fh);
+        let index_length = index_reader.seek(SeekFrom::End(0))?;
+        self.map_index_segments(&mut index_reader, index_length, file_length)?;
+        self.from_index = true;
+        Ok(self)
+    }
+
+    /// Walk a `.tdms_index` file, which mirrors the file's segments with the raw data left out,
+    /// so each index segment is only a lead in and meta data. Segment addresses are still
+    /// tracked in the file itself so the read pairs point at its raw data.
+    fn map_index_segments<R: Read + Seek>(
+        &mut self,
+        index_reader: &mut R,
+        index_length: u64,
+        file_length: u64,
+    ) -> Result<&mut Self> {
+        let mut index_address = 0;
+        while index_address < index_length && self.next_segment_address < file_length {
+            let address = self.next_segment_address;
+            let segment = match eof_as_none(self.read_segment(
+                index_reader,
+                index_address,
+                address,
+                file_length,
+            ))? {
+                Some(segment) => segment,
+                None => {
+                    debug!("Completed index read, final index segment is corrupted");
+                    let reason = WarningReason::UnreadableSegment {
+                        start_index: address,
+                    };
+                    self.anomaly(reason, index_address, true)?;
+                    self.truncated = true;
+                    return Ok(self);
+                }
+            };
+
+            let unfinished = segment.unfinished;
+            index_address += segment.raw_data_offset + HEADER_LEN;
+            if !unfinished {
+                self.next_segment_address = self
+                    .next_segment_address
+                    .saturating_add(segment.next_seg_offset.saturating_add(HEADER_LEN));
+            }
+
+            self.push_segment(segment);
+            if unfinished {
+                break;
+            }
+        }
+        debug!("Completed index read");
+        Ok(self)
+    }
+
+    /// Add a mapped segment, accumulating the file summary as it goes
+    fn push_segment(&mut self, segment: TdmsSegment) {
+        self.toc_flags |= segment.toc_mask.flags;
+        if segment.toc_mask.has_flag(TocProperties::KTocRawData) {
+            self.raw_bytes += segment
+                .next_seg_offset
+                .saturating_sub(segment.raw_data_offset);
+        }
+        // Only the last segment can be truncated, a later one means the file has since grown
+        self.truncated = segment.truncated;
+        self.segments.push(segment);
+    }
+
+    /// Load in a segment and parse all objects and properties, does not load raw data.
+    /// This allows lazy loading to handle very large files. The segment is read from
+    /// read_address, which differs from its start_index in the file when reading an index file.
+    /// Errors carry the index of the segment and the offset that was being parsed.
+    fn read_segment<R: Read + Seek>(
+        &mut self,
+        reader: &mut R,
+        read_address: u64,
+        start_index: u64,
+        file_length: u64,
+    ) -> Result<TdmsSegment> {
+        let segment_index = self.segments.len();
+        self.read_lead_in(reader, read_address, start_index, file_length)
+            .map_err(|e| {
+                let offset = reader.stream_position().unwrap_or(read_address);
+                e.with_context(None, Some(segment_index), offset)
+            })
+    }
+
+    fn read_lead_in<R: Read + Seek>(
+        &mut self,
+        reader: &mut R,
+        read_address: u64,
+        start_index: u64,
+        file_length: u64,
+    ) -> Result<TdmsSegment> {
+        // Seek to the "absolute index" (relative to start) This index has to be built up for each segment as we go.
+        // This is handled in the map_segments function
+        reader.seek(SeekFrom::Start(read_address))?;
+        let mut lead_in = [0u8; HEADER_LEN as usize];
+        reader.read_exact(&mut lead_in)?;
+        let segment = TdmsSegment::parse_lead_in(&lead_in, start_index)?;
+        // Mapping a segment still being written is undone once it's finished, keep the map as
+        // it was to map it again from
+        if segment.next_seg_offset == UNFINISHED_SEGMENT {
+            self.before_unfinished = Some(Box::new(self.clone()));
+        }
+        self.check_version(&segment, read_address)?;
+
+        if segment.toc_mask.has_flag(TocProperties::KTocBigEndian) {
+            self.read_segment_metadata::<R, BE>(reader, segment, file_length)
+        } else {
+            self.read_segment_metadata::<R, LE>(reader, segment, file_length)
+        }
+    }
+
+    /// Version 1.0 files don't carry DAQmx data and may differ in how raw data is indexed,
+    /// they're parsed as 2.0 files which works for the common cases. Any other version is
+    /// more likely a corrupt lead in than a newer format, so isn't parsed at all.
+    fn check_version(&mut self, segment: &TdmsSegment, read_address: u64) -> Result<()> {
+        if segment.version_no != TDMS_VERSION_2 {
+            let reason = WarningReason::UnexpectedVersion {
+                version: segment.version_no,
+            };
+            let tolerated = segment.version_no == TDMS_VERSION_1;
+            self.anomaly(reason, read_address + 8, tolerated)?;
+        }
+        Ok(())
+    }
+
+    fn read_segment_metadata<R: Read + Seek, O: ByteOrder>(
+        &mut self,
+        reader: &mut R,
+        segment: TdmsSegment,
+        file_length: u64,
+    ) -> Result<TdmsSegment> {
+        debug!("_______ENTERING SEGMENT________");
+        let segment = self.fit_to_file(segment, file_length)?;
+
+        debug!(
+            "NewObjFlag?: {}",
+            segment.toc_mask.has_flag(TocProperties::KTocNewObjList)
+        );
+
+        // Load the meta_data for this segment, parsing objects that appear in this segment. A
+        // segment without meta data has its raw data laid out exactly as the previous segment.
+        let has_meta_data = segment.toc_mask.has_flag(TocProperties::KTocMetaData);
+        let meta_data = if has_meta_data {
+            TdmsMetaData::read_metadata::<R, O>(self, reader, segment.raw_data_offset)?
+        } else {
+            TdmsMetaData::default()
+        };
+
+        self.place_raw_data(segment, &meta_data)
+    }
+
+    /// Cut back a segment that runs past the end of the file, or is still being written, to
+    /// the data that's there
+    fn fit_to_file(&mut self, mut segment: TdmsSegment, file_length: u64) -> Result<TdmsSegment> {
+        // A writer that stopped part way through leaves the segment running past the end of the
+        // file, cut it back to what's there so only complete chunks are mapped. One still writing
+        // the segment doesn't give its length, its raw data so far runs to the end of the file.
+        let available = file_length.saturating_sub(segment.start_index + HEADER_LEN);
+        segment.unfinished = segment.next_seg_offset == UNFINISHED_SEGMENT;
+        if segment.unfinished {
+            segment.next_seg_offset = available;
+        }
+        segment.truncated = segment.next_seg_offset > available;
+        if segment.truncated {
+            let reason = WarningReason::TruncatedSegment {
+                start_index: segment.start_index,
+                repaired: self.options.repair_truncated,
+            };
+            self.anomaly(reason, segment.start_index, true)?;
+            if self.options.repair_truncated {
+                segment.next_seg_offset = available;
+            }
+        }
+        Ok(segment)
+    }
+
+    /// Place the raw data of a segment whose meta data has been read, giving the objects it
+    /// lists, building read pairs for each of its chunks
+    fn place_raw_data(
+        &mut self,
+        mut segment: TdmsSegment,
+        meta_data: &TdmsMetaData,
+    ) -> Result<TdmsSegment> {
+        // Update the object maps
+        update_live_objects(
+            &mut self.live_objects,
+            &meta_data.objects,
+            segment.has_new_object_list(),
+        );
+
+        // All of the segment's objects are known now, fix how its raw data is laid out before
+        // building any read pairs from it
+        let layout = SegmentLayout::new(
+            self.live_objects
+                .iter()
+                .map(|key| &self.all_objects[key].last_object),
+        )?;
+
+        // Nothing after an object of unknown type and size can be placed, nor can anything
+        // beside it in interleaved data, so such segments contribute no read pairs either
+        let interleaved = segment
+            .toc_mask
+            .has_flag(TocProperties::KTocInterleavedData);
+        let placeable = !self.live_objects.iter().any(|key| {
+            self.all_objects[key]
+                .last_object
+                .has_unplaceable_data(interleaved)
+        });
+
+        // Segments without raw data contribute no read pairs
+        if segment.toc_mask.has_flag(TocProperties::KTocRawData) && placeable {
+            segment.no_chunks = segment.chunk_count(layout.chunk_size)?;
+
+            // Now we can go over it again and calculate the new read_map points for the segment,
+            // read maps are the bulk of the map so are skipped if data won't be loaded
+            if !self.options.metadata_only {
+                self.update_indexes(&segment, &layout)?;
+            }
+        }
+
+        Ok(segment)
+    }
+
+    fn update_indexes(&mut self, segment: &TdmsSegment, layout: &SegmentLayout) -> Result<()> {
+        let mut relative_position: u64 = 0; // Used in computing read pairs as we go
+        let mut daqmx_position: Option<u64> = None; // Where the shared DAQmx raw buffers start
+        let interleaved_segment = segment
+            .toc_mask
+            .has_flag(TocProperties::KTocInterleavedData);
+        // Where values of variable size fall in a row of interleaved data is unknowable, and
+        // the spec doesn't allow it, so every channel's values would be misplaced
+        if interleaved_segment && layout.objects.iter().any(|object| object.has_strings) {
+            return Err(TdmsError::UnsupportedLayout(
+                "interleaved string channel".to_string(),
+            ));
+        }
+        for object in &layout.objects {
+            let object_map = self.all_objects.get_mut(&object.path).unwrap();
+
+            // DAQmx objects read from raw buffers laid out one after another where the first of
+            // them appears, which only take up space once. Each object's data is in its buffer.
+            let (object_position, advance) = match object.daqmx_buffer {
+                Some(buffer) => {
+                    let buffer = buffer.min(layout.daqmx_buffers.len());
+                    let offset: u64 = layout.daqmx_buffers[..buffer].iter().sum();
+                    match daqmx_position {
+                        Some(position) => (position + offset, false),
+                        None => {
+                            daqmx_position = Some(relative_position);
+                            (relative_position + offset, true)
+                        }
+                    }
+                }
+                None => (relative_position, true),
+            };
+            debug!("Type Size: {}", object.type_size);
+
+            // A single value can't be wider than all the values in the chunk together, if it is the
+            // stride would underflow
+            let stride = layout
+                .channels_size
+                .checked_sub(object.type_size)
+                .ok_or(TdmsError::MalformedSegment)?;
+
+            // Objects excluded by the channel filter, or of unknown type, still take up space in
+            // the chunk, so only skip building their read pairs
+            let wanted = match &self.channel_filter {
+                Some(channel) => *channel == object.path,
+                None => true,
+            };
+
+            //compute read pairs as we go to save double iteration over the objects map,
+            // only compute if size here is > 0
+            let known_type = object_map.last_object.raw_data_type.is_some();
+            if wanted && known_type && object.no_bytes > 0 {
+                // With a single channel interleaved data has nothing between values, it's laid
+                // out contiguously and read in one go rather than value by value
+                let interleaved = interleaved_segment && stride > 0;
+                let bigendian = segment.toc_mask.has_flag(TocProperties::KTocBigEndian);
+
+                // One pair covers all the segment's chunks, and extends the previous pair
+                // where this segment continues a run of alike segments
+                if segment.no_chunks > 0 {
+                    let pair = ReadPair {
+                        start_index: segment.start_index
+                            + HEADER_LEN
+                            + segment.raw_data_offset
+                            + object_position,
+                        no_values: object.no_values,
+                        no_bytes: object.no_bytes,
+                        interleaved,
+                        stride: Some(stride),
+                        bigendian,
+                        segment_index: self.segments.len(),
+                        no_chunks: segment.no_chunks,
+                        chunk_stride: layout.chunk_size,
+                        segment_count: 1,
+                    };
+
+                    debug!("Read Pair {:?}", pair);
+
+                    let extended = match object_map.read_map.last_mut() {
+                        Some(last) => last.try_extend(&pair),
+                        None => false,
+                    };
+                    if !extended {
+                        object_map.read_map.push(pair);
+                    }
+                }
+
+                object_map.total_bytes = object
+                    .no_bytes
+                    .checked_mul(segment.no_chunks)
+                    .and_then(|bytes| object_map.total_bytes.checked_add(bytes))
+                    .ok_or(TdmsError::MalformedSegment)?;
+            };
+
+            debug!("Accum Obj Size: {}", object_map.total_bytes);
+
+            // If interleaved then the start position depends on the item sizes, if continuous
+            // then it's the number of values x type size i.e. "total_bytes"
+            debug!("Interleaved data: {}", interleaved_segment);
+            debug!("Flags: {:b}", segment.toc_mask.flags);
+            if !advance {
+                // sharing the DAQmx buffers which have already been accounted for
+            } else if object.daqmx_buffer.is_some() {
+                relative_position += layout.daqmx_buffers.iter().sum::<u64>();
+            } else if interleaved_segment {
+                relative_position += object.type_size;
+            } else {
+                relative_position += object.no_bytes;
+            }
+            debug!("relative position: {}", relative_position);
+        }
+        Ok(())
+    }
+}
+
+/// How a segment's raw data is laid out, captured from its objects once all of its meta data
+/// has been read. Read pairs are built from this snapshot alone, so they can't pick up
+/// changes made to the objects while the snapshot is in use.
+#[derive(Debug)]
+struct SegmentLayout {
+    objects: Vec<ObjectLayout>,
+    /// The bytes of raw data in a chunk, the stride between the segment's chunks
+    chunk_size: u64,
+    /// The sum of the sizes of the objects' data types, a row of interleaved data
+    channels_size: u64,
+    /// Sizes of the raw buffers shared by DAQmx objects, in the order they're laid out
+    daqmx_buffers: Vec<u64>,
+}
+
+/// An object's place in a segment's layout, in the order objects appear in each chunk
+#[derive(Debug)]
+struct ObjectLayout {
+    path: String,
+    /// Values in a chunk, flattened for multidimensional data
+    no_values: u64,
+    /// Bytes in a chunk, of the object's raw buffer for DAQmx objects
+    no_bytes: u64,
+    /// Bytes of a value, the space the object takes in a row of interleaved data. 0 for
+    /// strings, which have no fixed size.
+    type_size: u64,
+    /// Whether the object has string values in the segment
+    has_strings: bool,
+    /// The raw buffer a DAQmx object reads from
+    daqmx_buffer: Option<usize>,
+}
+
+impl SegmentLayout {
+    /// The layout of a segment holding the given objects in order
+    fn new<'a>(objects: impl Iterator<Item = &'a TdmsObject> + Clone) -> Result<SegmentLayout> {
+        let (chunk_size, channels_size) = chunk_sizes(objects.clone())?;
+        let daqmx_buffers = daqmx_buffer_sizes(objects.clone());
+
+        let objects = objects
+            .map(|object| {
+                // As in chunk_sizes, objects without values in this segment take up no space
+                let no_bytes = object.segment_bytes();
+                let type_size = match object.raw_data_type {
+                    Some(_) if no_bytes == 0 => 0,
+                    Some(DataTypeRaw::TdmsString) => 0,
+                    Some(other) => other.size()?,
+                    None => 0,
+                };
+                // Multidimensional data stores dim values per raw value, read them all flattened
+                let no_values = if no_bytes > 0 {
+                    object.flat_value_count()?
+                } else {
+                    0
+                };
+                Ok(ObjectLayout {
+                    path: object.object_path.clone(),
+                    no_values,
+                    no_bytes,
+                    type_size,
+                    has_strings: no_bytes > 0
+                        && object.raw_data_type == Some(DataTypeRaw::TdmsString),
+                    daqmx_buffer: object.daqmx_info.as_ref().map(DAQMxInfo::raw_buffer),
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(SegmentLayout {
+            objects,
+            chunk_size,
+            channels_size,
+            daqmx_buffers,
+        })
+    }
+}
+
+/// A TdmsSegment consists of a 28 byte lead in followed by a series of optional MetaData
+/// properties. This is followed in turn by raw data if it exists.
+#[derive(Debug, Clone)]
+pub struct TdmsSegment {
+    // Segment lead in data is 28 bytes long
+    file_tag: u32, // "TDSm" always the same
+    toc_mask: TocMask,
+    version_no: u32,
+    next_seg_offset: u64,
+    raw_data_offset: u64,
+    // Ancillary helper fields
+    start_index: u64,
+    no_chunks: u64,
+    truncated: bool, // Runs past the end of the file, cut back to it by repair_truncated
+    unfinished: bool, // Still being written, next_seg_offset is cut back to the end of the file
+}
+
+impl fmt::Display for TdmsSegment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Segment filetag:\t{:X}", self.file_tag)?;
+        writeln!(f, "Segment flags:\t{:?}", self.toc_mask)?;
+        writeln!(f, "Version no.:\t\t{}", self.version_no)?;
+        writeln!(f, "Next segment offset:\t{}", self.next_seg_offset)?;
+        writeln!(f, "Raw data offset:\t{}", self.raw_data_offset)?;
+        writeln!(f, "No_chunks:\t{}", self.no_chunks)?;
+        Ok(())
+    }
+}
+
+impl TdmsSegment {
+    pub fn new(start_index: u64) -> TdmsSegment {
+        TdmsSegment {
+            start_index,
+            file_tag: 0,
+            toc_mask: TocMask::from_flags(0),
+            version_no: 0,
+            next_seg_offset: 0,
+            raw_data_offset: 0,
+            no_chunks: 0,
+            truncated: false,
+            unfinished: false,
+        }
+    }
+    /// Parse a segment's 28 byte lead in. The tag and ToC are always little endian, the rest is
+    /// in the byte order the ToC gives.
+    fn parse_lead_in(bytes: &[u8], start_index: u64) -> Result<TdmsSegment> {
+        let mut reader = Cursor::new(bytes);
+        let mut segment = TdmsSegment::new(start_index);
+
+        // Convert the critical lead in information to appropriate representation, we know the
+        // first part of the lead in is little endian so we save a check here.
+        segment.file_tag = reader.read_u32::<LE>()?;
+        if !is_segment_tag(segment.file_tag) {
+            return Err(TdmsError::InvalidSegmentTag {
+                found: segment.file_tag,
+            });
+        }
+        segment.toc_mask = TocMask::from_flags(reader.read_u32::<LE>()?);
+        if segment.toc_mask.has_flag(TocProperties::KTocBigEndian) {
+            segment.parse_offsets::<BE>(&mut reader)?;
+        } else {
+            segment.parse_offsets::<LE>(&mut reader)?;
+        }
+
+        // The next segment can't start before this one's raw data, it would be inside the
+        // meta data, so whatever comes next would be misread
+        if segment.next_seg_offset < segment.raw_data_offset {
+            return Err(TdmsError::OverlappingSegments {
+                first: start_index,
+                second: start_index + HEADER_LEN + segment.next_seg_offset,
+            });
+        }
+        Ok(segment)
+    }
+
+    /// Finish out the lead in, the version and offsets that follow the ToC
+    fn parse_offsets<O: ByteOrder>(&mut self, reader: &mut Cursor<&[u8]>) -> Result<()> {
+        self.version_no = reader.read_u32::<O>()?;
+        self.next_seg_offset = reader.read_u64::<O>()?;
+        self.raw_data_offset = reader.read_u64::<O>()?;
+        Ok(())
+    }
+
+    /// The segment's lead in as bytes, each field's next to its name and value
+    fn dump_lead_in(&self) -> String {
+        let big_endian = self.toc_mask.has_flag(TocProperties::KTocBigEndian);
+        let u32_bytes = |value: u32| {
+            if big_endian {
+                value.to_be_bytes().to_vec()
+            } else {
+                value.to_le_bytes().to_vec()
+            }
+        };
+        let u64_bytes = |value: u64| {
+            if big_endian {
+                value.to_be_bytes().to_vec()
+            } else {
+                value.to_le_bytes().to_vec()
+            }
+        };
+
+        let flags: Vec<String> = [
+            TocProperties::KTocMetaData,
+            TocProperties::KTocNewObjList,
+            TocProperties::KTocRawData,
+            TocProperties::KTocInterleavedData,
+            TocProperties::KTocBigEndian,
+            TocProperties::KTocDAQmxRawData,
+        ]
+        .into_iter()
+        .filter(|flag| self.toc_mask.has_flag(*flag))
+        .map(|flag| format!("{:?}", flag))
+        .collect();
+        let next_seg_offset = if self.unfinished {
+            UNFINISHED_SEGMENT
+        } else {
+            self.next_seg_offset
+        };
+        let mut next_segment_offset = next_seg_offset.to_string();
+        if self.truncated {
+            next_segment_offset.push_str(" (runs past the end of the file)");
+        }
+        if self.unfinished {
+            next_segment_offset.push_str(" (still being written)");
+        }
+        // The tag and ToC are always little endian
+        let tag = self.file_tag.to_le_bytes();
+        let fields = [
+            (
+                tag.to_vec(),
+                "tag",
+                format!("{:?}", String::from_utf8_lossy(&tag)),
+            ),
+            (
+                self.toc_mask.flags.to_le_bytes().to_vec(),
+                "ToC mask",
+                format!("{:#010X} {}", self.toc_mask.flags, flags.join(" | ")),
+            ),
+            (
+                u32_bytes(self.version_no),
+                "version",
+                self.version_no.to_string(),
+            ),
+            (
+                u64_bytes(next_seg_offset),
+                "next segment offset",
+                next_segment_offset,
+            ),
+            (
+                u64_bytes(self.raw_data_offset),
+                "raw data offset",
+                self.raw_data_offset.to_string(),
+            ),
+        ];
+
+        let mut dump = String::new();
+        for (bytes, name, value) in fields {
+            let hex: Vec<String> = bytes.iter().map(|byte| format!("{:02X}", byte)).collect();
+            dump.push_str(&format!("{:<23}  {:<19}  {}\n", hex.join(" "), name, value));
+        }
+        dump
+    }
+
+    /// The segment's lead in as a SegmentInfo
+    pub fn info(&self) -> SegmentInfo {
+        SegmentInfo {
+            start_index: self.start_index,
+            toc_flags: self.toc_mask.flags,
+            version: self.version_no,
+            next_segment_offset: self.next_seg_offset,
+            raw_data_offset: self.raw_data_offset,
+            no_chunks: self.no_chunks,
+        }
+    }
+
+    /// Whether the objects the segment's meta data lists replace those of earlier segments,
+    /// rather than adding to them
+    fn has_new_object_list(&self) -> bool {
+        self.toc_mask.has_flag(TocProperties::KTocMetaData)
+            && self.toc_mask.has_flag(TocProperties::KTocNewObjList)
+    }
+
+    /// Compute the number of raw data chunks of the given size in the segment. Returns an error
+    /// rather than underflowing if the raw data offset lies beyond the end of the segment.
+    fn chunk_count(&self, chunk_size: u64) -> Result<u64> {
+        let raw_data_size = self
+            .next_seg_offset
+            .checked_sub(self.raw_data_offset)
+            .ok_or(TdmsError::MalformedSegment)?;
+
+        Ok(raw_data_size.checked_div(chunk_size).unwrap_or(0))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TdmsMetaData {
+    no_objects: u32,
+    objects: Vec<String>,
+}
+
+impl fmt::Display for TdmsMetaData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "No. objects:\t{}", self.no_objects)?;
+        for obj in &self.objects {
+            writeln!(f, "__Object__")?;
+            write!(f, "{}", obj)?;
+        }
+        Ok(())
+    }
+}
+
+impl TdmsMetaData {
+    /// Read the len bytes of a segment's meta data and parse the objects it lists, updating
+    /// them in the map. How the segment's raw data is laid out is worked out from the objects
+    /// once they've all been read, see SegmentLayout.
+    pub fn read_metadata<R: Read + Seek, O: ByteOrder>(
+        tdms_map: &mut TdmsMap,
+        reader: &mut R,
+        len: u64,
+    ) -> Result<TdmsMetaData> {
+        let base = reader.stream_position()?;
+        let mut bytes = Vec::new();
+        reader.take(len).read_to_end(&mut bytes)?;
+        if (bytes.len() as u64) < len {
+            return Err(io::Error::from(ErrorKind::UnexpectedEof).into());
+        }
+
+        let all_objects = &tdms_map.all_objects;
+        let (no_objects, objects, end) = parse_objects::<O>(
+            &bytes,
+            base,
+            |path| {
+                all_objects
+                    .get(path)
+                    .map(|object_map| &object_map.last_object)
+            },
+            &tdms_map.options,
+        )
+        .map_err(|e| match e.root_cause() {
+            // The whole meta data was there, so running out means the objects overrun it
+            TdmsError::Io(io_error) if io_error.kind() == ErrorKind::UnexpectedEof => {
+                TdmsError::MalformedSegment.with_context(None, None, base + len)
+            }
+            _ => e,
+        })?;
+
+        // Raw data starts straight after the meta data, objects ending anywhere else have been
+        // misparsed, e.g. an unhandled DAQmx index
+        if end != len {
+            let reason = WarningReason::MetadataLengthMismatch {
+                expected: base + len,
+                actual: base + end,
+            };
+            let tolerated = tdms_map.options.tolerate_metadata_mismatch;
+            tdms_map.anomaly(reason, base + end, tolerated)?;
+        }
+
+        let mut paths = Vec::with_capacity(objects.len());
+        for mut object in objects {
+            let path = object.object_path.clone();
+            let listed_before = tdms_map.all_objects.get(&path).map_or(0, |object_map| {
+                object_map.last_object.duplicate_properties.len()
+            });
+            for property in &object.duplicate_properties[listed_before..] {
+                let reason = WarningReason::DuplicateProperty {
+                    path: path.clone(),
+                    property: property.clone(),
+                };
+                tdms_map.anomaly(reason, base, true)?;
+            }
+            if !object.has_expected_index_len() {
+                let reason = WarningReason::UnexpectedIndexLength {
+                    path: path.clone(),
+                    index_info_len: object.index_info_len,
+                };
+                tdms_map.anomaly(reason, base, true)?;
+            }
+            if let Some(type_id) = object.unknown_type {
+                // Warned of once, not again in each segment listing the object with the type
+                let newly_unknown = match tdms_map.all_objects.get(&path) {
+                    Some(object_map) => object_map.last_object.unknown_type != Some(type_id),
+                    None => true,
+                };
+                if newly_unknown {
+                    let reason = WarningReason::UnknownDataType {
+                        path: path.clone(),
+                        type_id,
+                    };
+                    tdms_map.anomaly(reason, base, true)?;
+                }
+            }
+            paths.push(path.clone());
+            let segment_index = tdms_map.segments.len();
+            let object_map = tdms_map.all_objects.entry(path).or_default();
+            object_map.record_timing(&object, segment_index);
+            object_map.defer_properties(&mut object, segment_index);
+            object_map.last_object = object;
+        }
+        Ok(TdmsMetaData {
+            no_objects,
+            objects: paths,
+        })
+    }
+}
+
+/// Parse the objects listed in a segment's meta data, held in bytes. Each object starts from
+/// its state after earlier segments, looked up with prior, and the objects are returned as this
+/// segment leaves them in the order first listed, along with the number of objects listed and
+/// the number of bytes they took up. Errors give offsets from base, the position of bytes in
+/// whatever they were read from.
+pub(crate) fn parse_objects<'a, O: ByteOrder>(
+    bytes: &[u8],
+    base: u64,
+    prior: impl Fn(&str) -> Option<&'a TdmsObject>,
+    options: &ReadOptions,
+) -> Result<(u32, Vec<TdmsObject>, u64)> {
+    let mut reader = Cursor::new(bytes);
+    // Properties of unknown size can run no further than the end of the meta data
+    let unknown_end = if options.keep_unknown_properties {
+        Some(bytes.len() as u64)
+    } else {
+        None
+    };
+
+    let no_objects = reader.read_u32::<O>()?;
+    let mut objects: Vec<TdmsObject> = Vec::new();
+    for _i in 0..no_objects {
+        let path = lossy_string(read_string::<_, O>(&mut reader), options.lossy_strings)?;
+        // An object listed twice picks up where its first listing left it
+        let listed = objects.iter().position(|object| object.object_path == path);
+        let previous = match listed {
+            Some(i) => Some(&objects[i]),
+            None => prior(&path),
+        };
+        // Read in an object including properties
+        let object = TdmsObject::read_object::<_, O>(
+            previous.cloned(),
+            path.clone(),
+            &mut reader,
+            options,
+            unknown_end,
+        )
+        .map_err(|e| e.with_context(Some(&path), None, base + reader.position()))?;
+        match listed {
+            Some(i) => objects[i] = object,
+            None => objects.push(object),
+        }
+    }
+    Ok((no_objects, objects, reader.position()))
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TdmsObject {
+    object_path: String,
+    index_info_len: u32, // The length in bytes of the indexing info for raw data, including the length of this field. Should always be 20 (defined length) or 28 (variable length)
+    raw_data_type: Option<DataTypeRaw>, // appears in file as u32.
+    raw_data_dim: Option<u32>,
+    no_raw_vals: Option<u64>,
+    no_bytes: u64, // of raw data in bytes, appears in file for variable length types (String) only. comptued otherwise
+    // Whether the segment that last listed the object gave it raw data. The last raw data index
+    // is kept regardless, as a later segment may give it again as matching the previous index
+    has_raw_data: bool,
+    no_properties: u32,
+    daqmx_info: Option<DAQMxInfo>,
+    properties: IndexMap<String, ObjectProperty>,
+    duplicate_properties: Vec<String>, // Names listed more than once in a single listing of the object
+    deferred_properties: Vec<(u64, u32)>, // Offsets into the meta data and counts of property listings skipped
+    unknown_type: Option<u32>, // The type id given where it isn't a known type, raw_data_type is then None
+}
+
+// Not every field read is needed to lay out the data, they're kept for debugging
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub struct DAQMxInfo {
+    formatvec_size: u32,
+    scalers: Vec<DAQMxScaler>,
+    widthvec_size: u32,
+    widthvec: Vec<u32>,
+}
+
+impl DAQMxInfo {
+    /// Index of the raw buffer the object's data is read from, given by its first scaler
+    fn raw_buffer(&self) -> usize {
+        self.scalers
+            .first()
+            .map_or(0, |scaler| scaler.daqmx_rawbuff_indx as usize)
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub struct DAQMxScaler {
+    daqmx_data_type: DataTypeRaw,
+    daqmx_rawbuff_indx: u32,
+    daqmx_raw_byte_offset: u32,
+    sample_format_bitmap: u32,
+    scale_id: u32,
+}
+
+impl DAQMxScaler {
+    pub fn new<R: Read + Seek, O: ByteOrder>(reader: &mut R) -> Result<DAQMxScaler> {
+        let scaler = DAQMxScaler {
+            daqmx_data_type: DataTypeRaw::from_u32(reader.read_u32::<O>()?)?,
+            daqmx_rawbuff_indx: reader.read_u32::<O>()?,
+            daqmx_raw_byte_offset: reader.read_u32::<O>()?,
+            sample_format_bitmap: reader.read_u32::<O>()?,
+            scale_id: reader.read_u32::<O>()?,
+        };
+        Ok(scaler)
+    }
+}
+
+impl fmt::Display for TdmsObject {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Obj path:\t{}", self.object_path)?;
+        writeln!(f, "Index info length:\t{:x}", self.index_info_len)?;
+        writeln!(f, "Raw data type:\t{:?}", self.raw_data_type)?;
+        writeln!(f, "Raw data dim:\t{:?}", self.raw_data_dim)?;
+        writeln!(f, "No. raw vals:\t{:?}", self.no_raw_vals)?;
+        writeln!(f, "Total size:\t{:?}", self.no_bytes)?;
+        writeln!(f, "No. properties:\t{:?}", self.no_properties)?;
+        writeln!(f, "Actual property count:\t{:?}", self.properties.len())?;
+        for (_key, property) in self.properties.iter() {
+            writeln!(f, "__Property__")?;
+            write!(f, "{}", property)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl TdmsObject {
+    /// The object's path
+    pub fn path(&self) -> &str {
+        &self.object_path
+    }
+
+    /// The object's properties, keyed by name
+    pub fn properties(&self) -> &IndexMap<String, ObjectProperty> {
+        &self.properties
+    }
+
+    /// A property's value as T, e.g. `object.get_property_as::<f64>("wf_increment")`.
+    /// Integers widen to f64 or i64 where that's exact, but nothing narrows. Fails with
+    /// `MissingProperty` if the object has no such property, or `WrongPropertyType`.
+    pub fn get_property_as<T: PropertyValue>(&self, name: &str) -> Result<T> {
+        let property = self
+            .properties
+            .get(name)
+            .ok_or_else(|| TdmsError::MissingProperty {
+                path: self.object_path.clone(),
+                property: name.to_string(),
+            })?;
+        T::try_from(property.value())
+    }
+
+    /// The bytes of raw data the object has in a chunk of the segment that last listed it
+    fn segment_bytes(&self) -> u64 {
+        if self.has_raw_data {
+            self.no_bytes
+        } else {
+            0
+        }
+    }
+
+    /// Whether the raw data index length is one the spec gives, a marker or the length of a
+    /// full index for the object's data type
+    fn has_expected_index_len(&self) -> bool {
+        let markers = [
+            NO_RAW_DATA,
+            DATA_INDEX_MATCHES_PREVIOUS,
+            FORMAT_CHANGING_SCALER,
+            DIGITAL_LINE_SCALER,
+        ];
+        if markers.contains(&self.index_info_len) {
+            return true;
+        }
+        match self.raw_data_type {
+            Some(DataTypeRaw::TdmsString) => self.index_info_len == 28,
+            None if self.unknown_type.is_some() => [20, 28].contains(&self.index_info_len),
+            _ => self.index_info_len == 20,
+        }
+    }
+
+    /// The number of values in the object's raw data in a segment, with dim values per raw
+    /// value for multidimensional data. Where the count was never read, e.g. an object first
+    /// given as matching a previous index that had none, it's worked out from the size of the
+    /// data if its type has a fixed size.
+    fn flat_value_count(&self) -> Result<u64> {
+        let dim = self.raw_data_dim.unwrap_or(1) as u64;
+        if let Some(no_raw_vals) = self.no_raw_vals {
+            return no_raw_vals
+                .checked_mul(dim)
+                .ok_or(TdmsError::MalformedSegment);
+        }
+        let missing = || TdmsError::MissingValueCount {
+            path: self.object_path.clone(),
+        };
+        let size = match self.raw_data_type {
+            Some(data_type) => data_type.size().map_err(|_| missing())?,
+            None => return Err(missing()),
+        };
+        self.no_bytes
+            .checked_div(size)
+            .filter(|no_values| no_values * size == self.no_bytes)
+            .ok_or_else(missing)
+    }
+
+    /// Read an object's raw data index and properties as listed in a segment's meta data.
+    /// They update previous, the object as earlier segments left it, or a new object if it
+    /// hasn't been seen before.
+    fn read_object<R: Read + Seek, O: ByteOrder>(
+        previous: Option<TdmsObject>,
+        path: String,
+        reader: &mut R,
+        options: &ReadOptions,
+        unknown_end: Option<u64>,
+    ) -> Result<TdmsObject> {
+        // check existence now for later use
+        let prior_object = previous.is_some();
+        let mut new_object = previous.unwrap_or_default();
+
+        debug!("object_path: {}", path);
+        new_object.object_path = path;
+
+        new_object.index_info_len = reader.read_u32::<O>()?;
+
+        debug!("index len: {}", new_object.index_info_len);
+        new_object.has_raw_data = new_object.index_info_len != NO_RAW_DATA;
+        if new_object.index_info_len == NO_RAW_DATA {
+            // A channel that had data before has none in this segment
+            new_object.update_properties::<R, O>(reader, options, unknown_end)?;
+        } else if new_object.index_info_len == DATA_INDEX_MATCHES_PREVIOUS {
+            // raw data index for this object should be identical to previous segments.
+            if !prior_object {
+                return Err(TdmsError::NoPreviousObject);
+            } else {
+                new_object.update_properties::<R, O>(reader, options, unknown_end)?;
+            }
+        } else if new_object.index_info_len == FORMAT_CHANGING_SCALER
+            || new_object.index_info_len == DIGITAL_LINE_SCALER
+        {
+            new_object.read_sizeinfo::<R, O>(reader, options)?;
+            new_object.read_daqmxinfo::<R, O>(reader)?;
+            new_object.update_properties::<R, O>(reader, options, unknown_end)?;
+        } else {
+            // This is a fresh, non DAQmx object, or amount of data has changed
+            new_object.read_sizeinfo::<R, O>(reader, options)?;
+            new_object.update_properties::<R, O>(reader, options, unknown_end)?;
+        }
+        Ok(new_object)
+    }
+
+    /// Whether the object's raw data in the segment that last listed it is of an unknown type
+    /// and takes up space that can't be worked out, its size not having been given or the
+    /// segment being interleaved
+    fn has_unplaceable_data(&self, interleaved: bool) -> bool {
+        let has_values = self.has_raw_data && self.no_raw_vals.unwrap_or(0) > 0;
+        self.unknown_type.is_some() && has_values && (self.no_bytes == 0 || interleaved)
+    }
+
+    /// Read the data type, dimension and value count of a raw data index, and the total size
+    /// where given. With skip_unknown_types a type id that isn't known is kept as unknown_type.
+    fn read_sizeinfo<R: Read + Seek, O: ByteOrder>(
+        &mut self,
+        reader: &mut R,
+        options: &ReadOptions,
+    ) -> Result<&mut Self> {
+        let type_id = reader.read_u32::<O>()?;
+        let raw_data_type = match DataTypeRaw::from_u32(type_id) {
+            Ok(raw_data_type) => Some(raw_data_type),
+            Err(_) if options.skip_unknown_types => None,
+            Err(e) => return Err(e),
+        };
+        let dim = reader.read_u32::<O>()?;
+        let no_vals = reader.read_u64::<O>()?;
+
+        // total_bytes (bytes) is either recorded in the file if data is TdmsString or else
+        // must be computed. Size() will return an error if called on DataTypeRaw::TdmsString
+        // which is why there is a guard clause here. An unknown type's size is only known if
+        // the index gives it, as it does for strings.
+        self.no_bytes = match raw_data_type {
+            Some(DataTypeRaw::TdmsString) => reader.read_u64::<O>()?,
+            // A crafted count could overflow, as no real segment holds this many bytes
+            Some(other) => other
+                .size()?
+                .checked_mul(no_vals)
+                .and_then(|bytes| bytes.checked_mul(dim as u64))
+                .ok_or(TdmsError::MalformedSegment)?,
+            None if self.index_info_len == 28 => reader.read_u64::<O>()?,
+            None => 0,
+        };
+        debug!("Object total bytes: {}", self.no_bytes);
+        debug!("Data Dim: {}", dim);
+        debug!("No Raw Vals: {}", no_vals);
+        self.raw_data_type = raw_data_type;
+        self.unknown_type = raw_data_type.is_none().then_some(type_id);
+        self.raw_data_dim = Some(dim);
+        self.no_raw_vals = Some(no_vals);
+
+        Ok(self)
+    }
+
+    fn read_daqmxinfo<R: Read + Seek, O: ByteOrder>(
+        &mut self,
+        reader: &mut R,
+    ) -> Result<&mut Self> {
+        let daqmx_formatvec_size = reader.read_u32::<O>()?;
+
+        let mut scalers: Vec<DAQMxScaler> = Vec::new();
+        for _i in 0..daqmx_formatvec_size {
+            let scaler = DAQMxScaler::new::<R, O>(reader)?;
+            scalers.push(scaler);
+        }
+
+        let daqmx_datawidthvec_size = reader.read_u32::<O>()?;
+        let mut daqmx_data_width_vec = Vec::with_capacity(daqmx_datawidthvec_size as usize);
+        for _i in 0..daqmx_datawidthvec_size {
+            daqmx_data_width_vec.push(reader.read_u32::<O>()?);
+        }
+
+        let info = DAQMxInfo {
+            formatvec_size: daqmx_formatvec_size,
+            scalers,
+            widthvec_size: daqmx_datawidthvec_size,
+            widthvec: daqmx_data_width_vec,
+        };
+
+        // The object's raw buffer holds a row of the buffer's width for every value, shared
+        // with the other DAQmx objects reading from it
+        let width = info
+            .widthvec
+            .get(info.raw_buffer())
+            .ok_or(TdmsError::MalformedSegment)?;
+        self.no_bytes = *width as u64 * self.no_raw_vals.unwrap_or(0);
+        self.daqmx_info = Some(info);
+
+        Ok(self)
+    }
+
+    /// Read the object's properties. If unknown_end is given, properties of unknown types are
+    /// kept, skipping no further than that address to find the start of what follows. With
+    /// defer_properties they're skipped, noting where they're listed to parse later, all but
+    /// those giving waveform timing.
+    fn update_properties<R: Read + Seek, O: ByteOrder>(
+        &mut self,
+        reader: &mut R,
+        options: &ReadOptions,
+        unknown_end: Option<u64>,
+    ) -> Result<&mut Self> {
+        let lossy_strings = options.lossy_strings;
+        self.no_properties = reader.read_u32::<O>()?;
+        if options.defer_properties {
+            if self.no_properties > 0 {
+                let offset = reader.stream_position()?;
+                self.deferred_properties.push((offset, self.no_properties));
+            }
+            for i in 0..self.no_properties {
+                let remaining = self.no_properties - i - 1;
+                if let Some(property) =
+                    skip_property::<R, O>(reader, remaining, unknown_end, lossy_strings)?
+                {
+                    self.properties.insert(property.prop_name.clone(), property);
+                }
+            }
+            return Ok(self);
+        }
+        let mut listed: Vec<String> = Vec::new();
+        if self.no_properties > 0 {
+            for i in 0..self.no_properties {
+                let property = match unknown_end {
+                    Some(end) => {
+                        let remaining = self.no_properties - i - 1;
+                        ObjectProperty::read_property_or_unknown::<R, O>(
+                            reader,
+                            lossy_strings,
+                            remaining,
+                            end,
+                        )?
+                    }
+                    None => ObjectProperty::read_property::<R, O>(reader, lossy_strings)?,
+                };
+                // A name listed twice in one listing breaks the spec, the last value listed
+                // wins as it would in a later segment. Either way the previous version of the
+                // property is overwritten or else the new property inserted.
+                if listed.contains(&property.prop_name) {
+                    if !self.duplicate_properties.contains(&property.prop_name) {
+                        self.duplicate_properties.push(property.prop_name.clone());
+                    }
+                } else {
+                    listed.push(property.prop_name.clone());
+                }
+                self.properties.insert(property.prop_name.clone(), property);
+            }
+        }
+
+        Ok(self)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectProperty {
+    prop_name: String,
+    data_type: DataTypeRaw,
+    property: DataType,
+}
+
+impl fmt::Display for ObjectProperty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Property name: {}", self.prop_name)?;
+        writeln!(f, "Property datatype: {:?}", self.data_type)?;
+        writeln!(f, "Property val: {:?}", self.property)?;
+        Ok(())
+    }
+}
+
+impl ObjectProperty {
+    /// The property's name
+    pub fn name(&self) -> &str {
+        &self.prop_name
+    }
+
+    /// The data type the property was stored as
+    pub fn data_type(&self) -> DataTypeRaw {
+        self.data_type
+    }
+
+    /// The property's value
+    pub fn value(&self) -> &DataType {
+        &self.property
+    }
+
+    /// Instantiate a property and read into it. If lossy_strings is set invalid UTF-8 in the
+    /// name or a string value is replaced rather than returning an error.
+    pub fn read_property<R: Read + Seek, O: ByteOrder>(
+        reader: &mut R,
+        lossy_strings: bool,
+    ) -> Result<ObjectProperty> {
+        let prop_name = lossy_string(read_string::<R, O>(reader), lossy_strings)?;
         let data_type = DataTypeRaw::from_u32(reader.read_u32::<O>()?)?;
-        let property = read_datatype::<R, O>(reader, data_type)?;
+        let property = ObjectProperty::read_value::<R, O>(reader, data_type, lossy_strings)?;
+        Ok(ObjectProperty {
+            prop_name,
+            data_type,
+            property,
+        })
+    }
+
+    /// Read a property as read_property, keeping one of an unknown type as
+    /// `DataType::Unknown`. Its value is taken to run up to where the next of the object's
+    /// remaining properties appears to start, or for the last property the next object or
+    /// meta_data_end.
+    fn read_property_or_unknown<R: Read + Seek, O: ByteOrder>(
+        reader: &mut R,
+        lossy_strings: bool,
+        remaining: u32,
+        meta_data_end: u64,
+    ) -> Result<ObjectProperty> {
+        let prop_name = lossy_string(read_string::<R, O>(reader), lossy_strings)?;
+        let type_id = reader.read_u32::<O>()?;
+        let data_type = match DataTypeRaw::from_u32(type_id) {
+            Ok(data_type) => data_type,
+            Err(_) => {
+                let bytes = read_unknown_value::<R, O>(reader, remaining > 0, meta_data_end)?;
+                warn!(
+                    "Property {:?} has unknown type {:#x}, keeping its {} bytes",
+                    prop_name,
+                    type_id,
+                    bytes.len()
+                );
+                return Ok(ObjectProperty {
+                    prop_name,
+                    data_type: DataTypeRaw::Void,
+                    property: DataType::Unknown { type_id, bytes },
+                });
+            }
+        };
+        let property = ObjectProperty::read_value::<R, O>(reader, data_type, lossy_strings)?;
         Ok(ObjectProperty {
             prop_name,
             data_type,
             property,
         })
     }
+
+    fn read_value<R: Read + Seek, O: ByteOrder>(
+        reader: &mut R,
+        data_type: DataTypeRaw,
+        lossy_strings: bool,
+    ) -> Result<DataType> {
+        match data_type {
+            DataTypeRaw::TdmsString => Ok(DataType::TdmsString(lossy_string(
+                read_string::<R, O>(reader),
+                lossy_strings,
+            )?)),
+            other => read_datatype::<R, O>(reader, other),
+        }
+    }
+}
+
+/// Move the reader past a property without parsing it. Its value is skipped by the size of its
+/// type, or for unknown types as read_unknown_value would read it if unknown_end is given.
+/// One of the TIMING_PROPERTIES is read and returned instead, as timing is recorded while
+/// mapping.
+fn skip_property<R: Read + Seek, O: ByteOrder>(
+    reader: &mut R,
+    remaining: u32,
+    unknown_end: Option<u64>,
+    lossy_strings: bool,
+) -> Result<Option<ObjectProperty>> {
+    let name_len = reader.read_u32::<O>()?;
+    // Only names as long as a timing property's need reading to tell
+    let timing = if TIMING_PROPERTIES
+        .iter()
+        .any(|property| property.name().len() == name_len as usize)
+    {
+        let mut name = vec![0u8; name_len as usize];
+        reader.read_exact(&mut name)?;
+        TIMING_PROPERTIES
+            .iter()
+            .find(|property| property.name().as_bytes() == name)
+    } else {
+        reader.seek(SeekFrom::Current(name_len as i64))?;
+        None
+    };
+    let type_id = reader.read_u32::<O>()?;
+    if let (Some(timing), Ok(data_type)) = (timing, DataTypeRaw::from_u32(type_id)) {
+        let property = ObjectProperty::read_value::<R, O>(reader, data_type, lossy_strings)?;
+        return Ok(Some(ObjectProperty {
+            prop_name: timing.name().to_string(),
+            data_type,
+            property,
+        }));
+    }
+    let value_len = match (DataTypeRaw::from_u32(type_id), unknown_end) {
+        (Ok(DataTypeRaw::TdmsString), _) => reader.read_u32::<O>()? as u64,
+        (Ok(data_type), _) => data_type.size()?,
+        (Err(_), Some(end)) => {
+            read_unknown_value::<R, O>(reader, remaining > 0, end)?;
+            0
+        }
+        (Err(e), None) => return Err(e),
+    };
+    reader.seek(SeekFrom::Current(value_len as i64))?;
+    Ok(None)
+}
+
+/// Read the value of a property of unknown type. It runs up to the first position the next
+/// property starts at if more follow, otherwise the next object or the end of the meta data.
+fn read_unknown_value<R: Read + Seek, O: ByteOrder>(
+    reader: &mut R,
+    more_properties: bool,
+    meta_data_end: u64,
+) -> Result<Vec<u8>> {
+    let start = reader.stream_position()?;
+    let mut rest = vec![0u8; meta_data_end.saturating_sub(start) as usize];
+    reader.read_exact(&mut rest)?;
+
+    let len = (0..=rest.len())
+        .find(|&i| {
+            let next = &rest[i..];
+            if more_properties {
+                starts_property::<O>(next)
+            } else {
+                next.is_empty() || starts_object::<O>(next)
+            }
+        })
+        .ok_or(TdmsError::RawDataTypeNotFound)?;
+    reader.seek(SeekFrom::Start(start + len as u64))?;
+    rest.truncate(len);
+    Ok(rest)
+}
+
+/// Split a non-empty, length prefixed UTF-8 name off the front of bytes
+fn split_name<O: ByteOrder>(bytes: &[u8]) -> Option<(&str, &[u8])> {
+    let len = O::read_u32(bytes.get(..4)?) as usize;
+    let name = bytes.get(4..4 + len).filter(|name| !name.is_empty())?;
+    Some((std::str::from_utf8(name).ok()?, &bytes[4 + len..]))
+}
+
+/// Whether bytes plausibly start with a property, a name followed by a known type id
+fn starts_property<O: ByteOrder>(bytes: &[u8]) -> bool {
+    match split_name::<O>(bytes) {
+        Some((_, rest)) if rest.len() >= 4 => DataTypeRaw::from_u32(O::read_u32(rest)).is_ok(),
+        _ => false,
+    }
+}
+
+/// Whether bytes plausibly start with an object, a path followed by a raw data index length
+fn starts_object<O: ByteOrder>(bytes: &[u8]) -> bool {
+    match split_name::<O>(bytes) {
+        Some((path, rest)) if path.starts_with('/') && rest.len() >= 4 => matches!(
+            O::read_u32(rest),
+            NO_RAW_DATA | DATA_INDEX_MATCHES_PREVIOUS | 20 | 28 | FORMAT_CHANGING_SCALER
+        ),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{ramp, SegmentBuilder, TdmsBuilder};
+    use std::io::Write;
+
+    const CHANNEL: &str = "/'group'/'channel'";
+
+    fn i32_ramp(start: u64, len: usize) -> DataTypeVec {
+        ramp(DataTypeRaw::I32, start, len)
+    }
+
+    /// The values of i32_ramp for each (start, len) in turn
+    fn i32_values(ramps: &[(i32, i32)]) -> DataTypeVec {
+        DataTypeVec::I32(
+            ramps
+                .iter()
+                .flat_map(|&(start, len)| start..start + len)
+                .collect(),
+        )
+    }
+
+    /// Write bytes to a temporary file and open it with options, the file is removed when the
+    /// returned path is dropped
+    pub(crate) fn open_bytes(
+        bytes: &[u8],
+        options: ReadOptions,
+    ) -> (tempfile::TempPath, Result<TdmsFile>) {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(bytes).unwrap();
+        let path = file.into_temp_path();
+        let tdms = TdmsFile::open_with_options(&path, options);
+        (path, tdms)
+    }
+
+    /// Overwrite the raw data type in every listing of the object at path, in a little endian
+    /// file
+    fn set_raw_data_type(bytes: &mut [u8], path: &str, type_id: u32) {
+        let listings: Vec<usize> = bytes
+            .windows(path.len())
+            .enumerate()
+            .filter(|(_, window)| *window == path.as_bytes())
+            .map(|(at, _)| at)
+            .collect();
+        for at in listings {
+            // After the path comes the raw data index length, then the type
+            let type_at = at + path.len() + 4;
+            bytes[type_at..type_at + 4].copy_from_slice(&type_id.to_le_bytes());
+        }
+    }
+
+    #[test]
+    fn refresh_maps_a_segment_being_written_again() {
+        let first = SegmentBuilder::new().channel(CHANNEL, i32_ramp(0, 10));
+        let writing = |chunks| {
+            SegmentBuilder::new()
+                .channel(CHANNEL, i32_ramp(10, 10))
+                .chunks(chunks)
+        };
+        let last = SegmentBuilder::new().channel(CHANNEL, i32_ramp(20, 10));
+
+        // The writer has written one chunk of the second segment and part of the next
+        let unfinished = writing(2).next_segment_offset(Some(UNFINISHED_SEGMENT));
+        let mut bytes = TdmsBuilder::new()
+            .segment(first.clone())
+            .segment(unfinished)
+            .build();
+        bytes.truncate(bytes.len() - 12);
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(&bytes).unwrap();
+
+        let mut tdms = TdmsFile::open(file.path()).unwrap();
+        assert_eq!(tdms.segments().len(), 2);
+        assert_eq!(tdms.segments()[1].no_chunks, 1);
+        assert!(tdms.warnings().is_empty());
+        let data = tdms.load_data(CHANNEL).unwrap();
+        assert_eq!(data, i32_values(&[(0, 10), (10, 10)]));
+
+        // Two more chunks later it's still being written
+        let unfinished = writing(3).next_segment_offset(Some(UNFINISHED_SEGMENT));
+        let bytes = TdmsBuilder::new()
+            .segment(first.clone())
+            .segment(unfinished)
+            .build();
+        file.as_file_mut().seek(SeekFrom::Start(0)).unwrap();
+        file.write_all(&bytes).unwrap();
+
+        assert_eq!(tdms.refresh().unwrap(), 0);
+        assert_eq!(tdms.segments()[1].no_chunks, 3);
+        let data = tdms.load_data(CHANNEL).unwrap();
+        assert_eq!(data, i32_values(&[(0, 10), (10, 10), (10, 10), (10, 10)]));
+
+        // Then it's finished, its offset filled in, and another segment follows
+        let bytes = TdmsBuilder::new()
+            .segment(first)
+            .segment(writing(3))
+            .segment(last)
+            .build();
+        file.as_file_mut().seek(SeekFrom::Start(0)).unwrap();
+        file.write_all(&bytes).unwrap();
+
+        assert_eq!(tdms.refresh().unwrap(), 1);
+        assert_eq!(tdms.segments().len(), 3);
+        let data = tdms.load_data(CHANNEL).unwrap();
+        let expected = i32_values(&[(0, 10), (10, 10), (10, 10), (10, 10), (20, 10)]);
+        assert_eq!(data, expected);
+        assert!(!tdms.info().truncated);
+    }
+
+    #[test]
+    fn skip_unknown_types_keeps_the_data_of_other_channels() {
+        let unknown = "/'group'/'unknown'";
+        let strings = ramp(DataTypeRaw::TdmsString, 0, 5);
+        // Listed like strings, with the size of its data given
+        let mut bytes = TdmsBuilder::new()
+            .segment(
+                SegmentBuilder::new()
+                    .channel(unknown, strings.clone())
+                    .channel(CHANNEL, i32_ramp(0, 10)),
+            )
+            .segment(
+                SegmentBuilder::new()
+                    .channel(unknown, strings)
+                    .channel(CHANNEL, i32_ramp(10, 10)),
+            )
+            .build();
+        set_raw_data_type(&mut bytes, unknown, 0x99);
+
+        let error = TdmsFile::new(Cursor::new(bytes.clone())).err().unwrap();
+        assert!(matches!(error.root_cause(), TdmsError::RawDataTypeNotFound));
+
+        let options = ReadOptions::new().skip_unknown_types(true);
+        let (_path, tdms) = open_bytes(&bytes, options);
+        let tdms = tdms.unwrap();
+        assert_eq!(tdms.data_objects(), vec![CHANNEL]);
+        assert!(!tdms.has_channel(unknown));
+        assert!(tdms.all_objects().contains(&unknown));
+        let data = tdms.load_data(CHANNEL).unwrap();
+        assert_eq!(data, i32_values(&[(0, 10), (10, 10)]));
+        // Warned of once, not for each segment listing it
+        let warnings: Vec<&WarningReason> = tdms.warnings().iter().map(|w| &w.reason).collect();
+        assert_eq!(
+            warnings,
+            vec![&WarningReason::UnknownDataType {
+                path: unknown.to_string(),
+                type_id: 0x99
+            }]
+        );
+
+        // In strict mode the unknown type fails the file however it's listed
+        let options = ReadOptions::new()
+            .skip_unknown_types(true)
+            .parse_mode(Some(ParseMode::Strict));
+        let (_path, tdms) = open_bytes(&bytes, options);
+        let error = tdms.err().unwrap();
+        assert!(matches!(error.root_cause(), TdmsError::RawDataTypeNotFound));
+    }
+
+    #[test]
+    fn skip_unknown_types_skips_segments_it_cant_place_data_in() {
+        let unknown = "/'group'/'unknown'";
+        // Without the size of its data nothing after it can be placed
+        let mut bytes = TdmsBuilder::new()
+            .segment(
+                SegmentBuilder::new()
+                    .channel(unknown, ramp(DataTypeRaw::U32, 0, 10))
+                    .channel(CHANNEL, i32_ramp(0, 10)),
+            )
+            .segment(SegmentBuilder::new().channel(CHANNEL, i32_ramp(10, 10)))
+            .build();
+        set_raw_data_type(&mut bytes, unknown, 0x99);
+
+        let options = ReadOptions::new().skip_unknown_types(true);
+        let (_path, tdms) = open_bytes(&bytes, options);
+        let tdms = tdms.unwrap();
+        assert_eq!(tdms.segments()[0].no_chunks, 0);
+        let data = tdms.load_data(CHANNEL).unwrap();
+        assert_eq!(data, i32_values(&[(10, 10)]));
+    }
+
+    #[test]
+    fn channel_is_bigendian_follows_the_last_segment() {
+        let bytes = TdmsBuilder::new()
+            .segment(SegmentBuilder::new().channel(CHANNEL, i32_ramp(0, 10)))
+            .segment(
+                SegmentBuilder::new()
+                    .big_endian(true)
+                    .channel(CHANNEL, i32_ramp(10, 10)),
+            )
+            .build();
+        let tdms = TdmsFile::new(Cursor::new(bytes)).unwrap();
+        assert!(tdms.channel_is_bigendian(CHANNEL).unwrap());
+        // Each segment's values are decoded in its own byte order
+        let data = tdms.load_data(CHANNEL).unwrap();
+        assert_eq!(data, i32_values(&[(0, 20)]));
+
+        let error = tdms
+            .channel_is_bigendian("/'group'/'missing'")
+            .err()
+            .unwrap();
+        assert!(matches!(error, TdmsError::ChannelNotFound { .. }));
+    }
+
+    #[test]
+    fn segments_alternating_byte_order_decode_in_sequence() {
+        let other = "/'group'/'other'";
+        let mut builder = TdmsBuilder::new();
+        for segment in 0..4 {
+            let start = segment * 10;
+            builder = builder.segment(
+                SegmentBuilder::new()
+                    .big_endian(segment % 2 == 0)
+                    .interleaved(segment >= 2)
+                    .channel(CHANNEL, i32_ramp(start, 10))
+                    .channel(other, ramp(DataTypeRaw::DoubleFloat, start, 10)),
+            );
+        }
+        let tdms = TdmsFile::new(Cursor::new(builder.build())).unwrap();
+
+        let data = tdms.load_data(CHANNEL).unwrap();
+        assert_eq!(data, i32_values(&[(0, 40)]));
+        let data = tdms.load_data(other).unwrap();
+        assert_eq!(data, DataTypeVec::Double((0..40).map(f64::from).collect()));
+        assert!(!tdms.channel_is_bigendian(CHANNEL).unwrap());
+    }
+
+    #[test]
+    fn find_channel_ignores_case_and_whitespace() {
+        let bytes = TdmsBuilder::new()
+            .segment(
+                SegmentBuilder::new()
+                    .channel("/'a'/'Voltage '", i32_ramp(0, 1))
+                    .channel("/'a'/'current'", i32_ramp(0, 1))
+                    .channel("/'b'/'current'", i32_ramp(0, 1)),
+            )
+            .build();
+        let tdms = TdmsFile::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(tdms.find_channel(" voltage"), Some("/'a'/'Voltage '"));
+        // Ambiguous or missing names find nothing
+        assert_eq!(tdms.find_channel("Current"), None);
+        assert_eq!(tdms.find_channel("power"), None);
+
+        let error = tdms.load_data("/'a'/'Curent'").err().unwrap();
+        match error {
+            TdmsError::ChannelNotFound { closest, .. } => {
+                assert_eq!(closest[0], "/'a'/'current'")
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+    }
+
+    #[test]
+    fn segments_without_values_map_no_chunks() {
+        // A string channel alone, its size is the whole of each chunk
+        let strings = ramp(DataTypeRaw::TdmsString, 0, 3);
+        let bytes = TdmsBuilder::new()
+            .segment(
+                SegmentBuilder::new()
+                    .channel(CHANNEL, strings.clone())
+                    .chunks(2),
+            )
+            .segment(SegmentBuilder::new().channel(CHANNEL, DataTypeVec::TdmsString(Vec::new())))
+            .build();
+        let tdms = TdmsFile::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(tdms.segments()[0].no_chunks, 2);
+        assert_eq!(tdms.segments()[1].no_chunks, 0);
+
+        let mut expected = strings.clone();
+        expected.extend(strings).unwrap();
+        assert_eq!(tdms.load_data(CHANNEL).unwrap(), expected);
+    }
+
+    /// A file length bytes long, of bytes followed by zeros, so files can declare more values
+    /// than memory could hold
+    struct ZeroPadded {
+        bytes: Vec<u8>,
+        len: u64,
+        pos: u64,
+    }
+
+    impl Read for ZeroPadded {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = (buf.len() as u64).min(self.len.saturating_sub(self.pos)) as usize;
+            for (i, byte) in buf[..n].iter_mut().enumerate() {
+                let at = self.pos + i as u64;
+                *byte = usize::try_from(at)
+                    .ok()
+                    .and_then(|at| self.bytes.get(at))
+                    .copied()
+                    .unwrap_or(0);
+            }
+            self.pos += n as u64;
+            Ok(n)
+        }
+    }
+
+    impl Seek for ZeroPadded {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            self.pos = match pos {
+                SeekFrom::Start(pos) => pos,
+                SeekFrom::End(offset) => self.len.wrapping_add_signed(offset),
+                SeekFrom::Current(offset) => self.pos.wrapping_add_signed(offset),
+            };
+            Ok(self.pos)
+        }
+    }
+
+    #[test]
+    fn channels_too_large_to_allocate_fail_rather_than_truncate() {
+        const VALUES: u64 = 1 << 60;
+        let mut bytes = TdmsBuilder::new()
+            .segment(SegmentBuilder::new().channel(CHANNEL, DataTypeVec::Double(vec![1.0])))
+            .build();
+        // Declare 2^60 doubles in place of the one written, a segment of 2^63 raw bytes
+        let index = [
+            &10u32.to_le_bytes()[..],
+            &1u32.to_le_bytes(),
+            &1u64.to_le_bytes(),
+        ]
+        .concat();
+        let no_values_at = bytes
+            .windows(index.len())
+            .position(|window| window == index)
+            .unwrap()
+            + 8;
+        bytes[no_values_at..no_values_at + 8].copy_from_slice(&VALUES.to_le_bytes());
+        let raw_data_offset = u64::from_le_bytes(bytes[20..28].try_into().unwrap());
+        let raw_bytes = VALUES * 8;
+        bytes[12..20].copy_from_slice(&(raw_data_offset + raw_bytes).to_le_bytes());
+        bytes.truncate(HEADER_LEN as usize + raw_data_offset as usize);
+        let len = HEADER_LEN + raw_data_offset + raw_bytes;
+        let reader = ZeroPadded { bytes, len, pos: 0 };
+
+        let tdms = TdmsFile::from_reader(reader, len).unwrap();
+        assert!(tdms.warnings().is_empty());
+        assert_eq!(tdms.channel_len(CHANNEL).unwrap(), VALUES);
+        assert_eq!(tdms.channel_total_bytes(CHANNEL).unwrap(), raw_bytes);
+
+        let too_large = |error: TdmsError| {
+            matches!(
+                error.root_cause(),
+                TdmsError::ChannelTooLargeForPlatform { bytes, .. } if *bytes == raw_bytes
+            )
+        };
+        assert!(too_large(tdms.load_data(CHANNEL).err().unwrap()));
+        assert!(too_large(tdms.load_raw_bytes(CHANNEL).err().unwrap()));
+        assert!(too_large(
+            tdms.load_data_with(CHANNEL, |value| value).err().unwrap()
+        ));
+        let mut buffer = Vec::new();
+        assert!(too_large(
+            tdms.load_data_into(CHANNEL, &mut buffer).err().unwrap()
+        ));
+    }
+
+    #[test]
+    fn file_properties_convert_to_the_type_asked_for() {
+        let bytes = TdmsBuilder::new()
+            .segment(
+                SegmentBuilder::new()
+                    .channel(CHANNEL, i32_ramp(0, 1))
+                    .property("wf_increment", DataType::Double(0.5))
+                    .property("wf_samples", DataType::I32(1000))
+                    .property("unit_string", DataType::TdmsString("V".to_string())),
+            )
+            .build();
+        let tdms = TdmsFile::new(Cursor::new(bytes)).unwrap();
+
+        assert_eq!(
+            tdms.get_property_as::<f64>(CHANNEL, "wf_increment")
+                .unwrap(),
+            0.5
+        );
+        assert_eq!(
+            tdms.get_property_as::<i64>(CHANNEL, "wf_samples").unwrap(),
+            1000
+        );
+        assert_eq!(
+            tdms.get_property_as::<f64>(CHANNEL, "wf_samples").unwrap(),
+            1000.0
+        );
+        assert!(matches!(
+            tdms.get_property_as::<i64>(CHANNEL, "wf_increment"),
+            Err(TdmsError::WrongPropertyType {
+                expected: "i64",
+                found: DataTypeRaw::DoubleFloat
+            })
+        ));
+        assert!(matches!(
+            tdms.get_property_as::<f64>(CHANNEL, "unit_string"),
+            Err(TdmsError::WrongPropertyType { .. })
+        ));
+        assert!(tdms.get_property_as::<f64>(CHANNEL, "missing").is_err());
+    }
+
+    /// A property of the made up type 0x99
+    fn unknown_property() -> DataType {
+        DataType::Unknown {
+            type_id: 0x99,
+            bytes: vec![0, 7, 14, 21, 28, 35],
+        }
+    }
+
+    #[test]
+    fn unknown_property_types_dont_stop_other_channels_reading() {
+        let values = || i32_values(&[(1, 3)]);
+        let a_b_c = |big_endian| {
+            SegmentBuilder::new()
+                .big_endian(big_endian)
+                .object("/'group'")
+                .channel("/'group'/'a'", values())
+                .channel("/'group'/'b'", values())
+                .property("weird", unknown_property())
+        };
+        for big_endian in [false, true] {
+            // Followed by another property, then by another object
+            let files = [
+                (
+                    a_b_c(big_endian)
+                        .property("after", DataType::I32(5))
+                        .channel("/'group'/'c'", values()),
+                    Some(DataType::I32(5)),
+                ),
+                (a_b_c(big_endian).channel("/'group'/'c'", values()), None),
+            ];
+            for (segment, after) in files {
+                let bytes = TdmsBuilder::new().segment(segment).build();
+                let (_path, tdms) = open_bytes(&bytes, ReadOptions::new());
+                assert!(tdms.is_err());
+
+                let options = ReadOptions::new().keep_unknown_properties(true);
+                let (_path, tdms) = open_bytes(&bytes, options);
+                let tdms = tdms.unwrap();
+                for channel in ["/'group'/'a'", "/'group'/'b'", "/'group'/'c'"] {
+                    assert_eq!(tdms.load_data(channel).unwrap(), values());
+                }
+                let properties = tdms.tdms_map.object_map("/'group'/'b'").unwrap();
+                let properties = &properties.last_object.properties;
+                assert_eq!(properties["weird"].value(), &unknown_property());
+                assert_eq!(
+                    properties.get("after").map(ObjectProperty::value),
+                    after.as_ref()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn unknown_property_types_last_in_the_meta_data() {
+        let segment = |meta_data| {
+            SegmentBuilder::new()
+                .meta_data(meta_data)
+                .channel("/'group'/'a'", i32_ramp(0, 3))
+                .channel("/'group'/'b'", i32_ramp(0, 3))
+        };
+        let bytes = TdmsBuilder::new()
+            .segment(segment(true).property("weird", unknown_property()))
+            .segment(segment(false))
+            .build();
+
+        let options = ReadOptions::new().keep_unknown_properties(true);
+        let (_path, tdms) = open_bytes(&bytes, options);
+        let tdms = tdms.unwrap();
+        assert_eq!(
+            tdms.load_data("/'group'/'b'").unwrap(),
+            i32_values(&[(0, 3), (0, 3)])
+        );
+        let properties = tdms.tdms_map.object_map("/'group'/'b'").unwrap();
+        assert_eq!(
+            properties.last_object.properties["weird"].value(),
+            &unknown_property()
+        );
+    }
+
+    /// A file whose second segment's lead in gives the next segment offset inside it, with a
+    /// segment after it, and the address of the second segment
+    fn overlapping_file(next_segment_offset: u64, last: SegmentBuilder) -> (Vec<u8>, u64) {
+        let first = SegmentBuilder::new().channel(CHANNEL, i32_ramp(0, 10));
+        let second = SegmentBuilder::new()
+            .channel(CHANNEL, i32_ramp(10, 10))
+            .next_segment_offset(Some(next_segment_offset));
+        let address = TdmsBuilder::new().segment(first.clone()).build().len() as u64;
+        let bytes = TdmsBuilder::new()
+            .segment(first)
+            .segment(second)
+            .segment(last)
+            .build();
+        (bytes, address)
+    }
+
+    #[test]
+    fn overlapping_segments_fail_unless_skipped() {
+        // An offset of 0 would have the segment read again and again
+        for offset in [0, 8] {
+            let last = SegmentBuilder::new().channel(CHANNEL, i32_ramp(20, 10));
+            let (bytes, address) = overlapping_file(offset, last);
+            let overlap = (address, address + HEADER_LEN + offset);
+
+            let (_path, file) = open_bytes(&bytes, ReadOptions::new());
+            match file.err().unwrap().root_cause() {
+                TdmsError::OverlappingSegments { first, second } => {
+                    assert_eq!((*first, *second), overlap)
+                }
+                e => panic!("{:?}", e),
+            }
+
+            let options = ReadOptions::new().skip_overlapping_segments(true);
+            let (_path, file) = open_bytes(&bytes, options);
+            let file = file.unwrap();
+            assert_eq!(file.segments().len(), 2);
+            assert_eq!(file.recovery_info().overlapping_segments, vec![overlap]);
+            let data = file.load_data(CHANNEL).unwrap();
+            assert_eq!(data, i32_values(&[(0, 10), (20, 10)]));
+        }
+    }
+
+    #[test]
+    fn skipping_overlapping_segments_keeps_an_unfinished_last_segment() {
+        // The writer restarted and has written two chunks of the last segment and part of a
+        // third
+        let last = SegmentBuilder::new()
+            .channel(CHANNEL, i32_ramp(20, 10))
+            .chunks(3)
+            .next_segment_offset(Some(UNFINISHED_SEGMENT));
+        let (mut bytes, address) = overlapping_file(0, last);
+        bytes.truncate(bytes.len() - 12);
+
+        let options = ReadOptions::new().skip_overlapping_segments(true);
+        let (_path, file) = open_bytes(&bytes, options);
+        let file = file.unwrap();
+        assert_eq!(file.segments().len(), 2);
+        assert_eq!(file.segments()[1].no_chunks, 2);
+        let overlap = (address, address + HEADER_LEN);
+        assert_eq!(file.recovery_info().overlapping_segments, vec![overlap]);
+        assert_eq!(file.warnings().len(), 1);
+        assert_eq!(file.estimated_load_size(CHANNEL).unwrap(), 30 * 4);
+        let data = file.load_data(CHANNEL).unwrap();
+        assert_eq!(data, i32_values(&[(0, 10), (20, 10), (20, 10)]));
+    }
+
+    #[test]
+    fn values_per_chunk_changing_between_segments_load_in_order() {
+        let appended = "/'group'/'appended'";
+        let bytes = TdmsBuilder::new()
+            .segment(SegmentBuilder::new().channel(CHANNEL, i32_ramp(0, 100)))
+            // The channel is listed with more values per chunk beside an appended channel
+            .segment(
+                SegmentBuilder::new()
+                    .new_obj_list(false)
+                    .channel(CHANNEL, i32_ramp(100, 250))
+                    .channel(appended, i32_ramp(0, 250)),
+            )
+            // And its values carry on laid out as the last segment's
+            .segment(
+                SegmentBuilder::new()
+                    .meta_data(false)
+                    .channel(CHANNEL, i32_ramp(350, 250))
+                    .channel(appended, i32_ramp(250, 250))
+                    .chunks(2),
+            )
+            .build();
+        let file = TdmsFile::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(file.segments()[2].no_chunks, 2);
+
+        let data = file.load_data(CHANNEL).unwrap();
+        assert_eq!(data, i32_values(&[(0, 600), (350, 250)]));
+        let data = file.load_data(appended).unwrap();
+        assert_eq!(data, i32_values(&[(0, 500), (250, 250)]));
+        assert_eq!(file.estimated_load_size(CHANNEL).unwrap(), 850 * 4);
+    }
+
+    #[test]
+    fn objects_relisted_in_a_new_order_decode_in_that_order() {
+        let (a, b) = ("/'group'/'a'", "/'group'/'b'");
+        for interleaved in [false, true] {
+            let bytes = TdmsBuilder::new()
+                .segment(
+                    SegmentBuilder::new()
+                        .channel(a, i32_ramp(0, 10))
+                        .channel(b, ramp(DataTypeRaw::DoubleFloat, 100, 10))
+                        .interleaved(interleaved),
+                )
+                // A new object list giving B first, then A with a new raw data index
+                .segment(
+                    SegmentBuilder::new()
+                        .channel_matching_previous(b, ramp(DataTypeRaw::DoubleFloat, 110, 10))
+                        .channel(a, i32_ramp(10, 10))
+                        .interleaved(interleaved),
+                )
+                // Laid out as the last segment
+                .segment(
+                    SegmentBuilder::new()
+                        .meta_data(false)
+                        .channel(b, ramp(DataTypeRaw::DoubleFloat, 120, 10))
+                        .channel(a, i32_ramp(20, 10))
+                        .interleaved(interleaved),
+                )
+                .build();
+            let file = TdmsFile::new(Cursor::new(bytes)).unwrap();
+            assert_eq!(file.load_data(a).unwrap(), i32_values(&[(0, 30)]));
+            let expected = ramp(DataTypeRaw::DoubleFloat, 100, 30);
+            assert_eq!(file.load_data(b).unwrap(), expected, "{}", interleaved);
+        }
+    }
+
+    #[test]
+    fn channels_without_data_in_a_segment_skip_it() {
+        let other = "/'group'/'other'";
+        let void = "/'group'/'void'";
+        let bytes = TdmsBuilder::new()
+            .segment(
+                SegmentBuilder::new()
+                    .channel(CHANNEL, i32_ramp(0, 10))
+                    .channel(void, DataTypeVec::Void(vec![(); 10]))
+                    .channel(other, i32_ramp(100, 10)),
+            )
+            // Still listed, but without data in this segment
+            .segment(
+                SegmentBuilder::new()
+                    .object(CHANNEL)
+                    .channel_matching_previous(void, DataTypeVec::Void(vec![(); 10]))
+                    .channel_matching_previous(other, i32_ramp(110, 10)),
+            )
+            // Then with its data back
+            .segment(
+                SegmentBuilder::new()
+                    .channel_matching_previous(CHANNEL, i32_ramp(10, 10))
+                    .channel_matching_previous(void, DataTypeVec::Void(vec![(); 10]))
+                    .channel_matching_previous(other, i32_ramp(120, 10)),
+            )
+            .build();
+        let file = TdmsFile::new(Cursor::new(bytes)).unwrap();
+
+        assert_eq!(file.load_data(CHANNEL).unwrap(), i32_values(&[(0, 20)]));
+        assert_eq!(file.load_data(other).unwrap(), i32_values(&[(100, 30)]));
+        assert_eq!(file.channel_total_bytes(CHANNEL).unwrap(), 20 * 4);
+        assert_eq!(file.channel_total_bytes(void).unwrap(), 0);
+    }
+
+    #[test]
+    fn interleaved_string_channels_fail_rather_than_misread() {
+        let volts = "/'group'/'volts'";
+        let notes = "/'group'/'notes'";
+        let flags = [
+            TocProperties::KTocMetaData,
+            TocProperties::KTocNewObjList,
+            TocProperties::KTocRawData,
+            TocProperties::KTocInterleavedData,
+        ];
+        let toc = flags.into_iter().fold(0, |toc, flag| toc | u32::from(flag));
+        let bytes = TdmsBuilder::new()
+            .segment(SegmentBuilder::new().channel(CHANNEL, i32_ramp(0, 10)))
+            .segment(
+                SegmentBuilder::new()
+                    .channel(volts, ramp(DataTypeRaw::DoubleFloat, 0, 4))
+                    .channel(notes, ramp(DataTypeRaw::TdmsString, 0, 4))
+                    .toc(Some(toc)),
+            )
+            .build();
+
+        for mode in [None, Some(ParseMode::Lenient)] {
+            let (_path, file) = open_bytes(&bytes, ReadOptions::new().parse_mode(mode));
+            match file.err().unwrap().root_cause() {
+                TdmsError::UnsupportedLayout(layout) => {
+                    assert_eq!(layout, "interleaved string channel")
+                }
+                e => panic!("{:?}", e),
+            }
+        }
+    }
+
+    thread_local! {
+        static LOGGED: std::cell::RefCell<Vec<(log::Level, String)>> =
+            const { std::cell::RefCell::new(Vec::new()) };
+    }
+
+    /// Keeps the records logged on each thread for that thread, so tests running at once don't
+    /// see each other's
+    struct CapturingLogger;
+
+    impl log::Log for CapturingLogger {
+        fn enabled(&self, _: &log::Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record) {
+            let logged = (record.level(), record.args().to_string());
+            LOGGED.with(|records| records.borrow_mut().push(logged));
+        }
+
+        fn flush(&self) {}
+    }
+
+    /// The result of f with the records it logged on this thread
+    fn capture_logs<T>(f: impl FnOnce() -> T) -> (T, Vec<(log::Level, String)>) {
+        static INSTALL: std::sync::Once = std::sync::Once::new();
+        INSTALL.call_once(|| {
+            log::set_logger(&CapturingLogger).unwrap();
+            log::set_max_level(log::LevelFilter::Trace);
+        });
+        LOGGED.with(|records| records.borrow_mut().clear());
+        let result = f();
+        (result, LOGGED.with(|records| records.take()))
+    }
+
+    #[test]
+    fn opening_a_clean_file_logs_nothing_above_debug() {
+        let bytes = TdmsBuilder::new()
+            .segment(SegmentBuilder::new().channel(CHANNEL, i32_ramp(0, 10)))
+            .segment(SegmentBuilder::new().channel(CHANNEL, i32_ramp(10, 10)))
+            .build();
+
+        let (data, logged) = capture_logs(|| {
+            let tdms = TdmsFile::new(Cursor::new(bytes)).unwrap();
+            tdms.load_data(CHANNEL).unwrap()
+        });
+        assert_eq!(data, i32_values(&[(0, 10), (10, 10)]));
+        // What was printed on every open is now only there for a logger that asks for it
+        assert!(logged.contains(&(log::Level::Debug, "Completed read".to_string())));
+        let louder: Vec<_> = logged
+            .iter()
+            .filter(|(level, _)| *level <= log::Level::Info)
+            .collect();
+        assert!(louder.is_empty(), "{:?}", louder);
+    }
+
+    #[test]
+    fn a_corrupt_final_segment_logs_each_warning_once() {
+        let mut bytes = TdmsBuilder::new()
+            .segment(SegmentBuilder::new().channel(CHANNEL, i32_ramp(0, 10)))
+            .segment(SegmentBuilder::new().channel(CHANNEL, i32_ramp(10, 10)))
+            .build();
+        // Cut the second segment off part way through its meta data
+        let second = u64::from_le_bytes(bytes[12..20].try_into().unwrap()) + HEADER_LEN;
+        bytes.truncate(second as usize + HEADER_LEN as usize + 8);
+
+        let (tdms, logged) = capture_logs(|| TdmsFile::new(Cursor::new(bytes)).unwrap());
+        assert_eq!(tdms.load_data(CHANNEL).unwrap(), i32_values(&[(0, 10)]));
+        let warned: Vec<String> = logged
+            .into_iter()
+            .filter(|(level, _)| *level == log::Level::Warn)
+            .map(|(_, message)| message)
+            .collect();
+        let warnings: Vec<String> = tdms.warnings().iter().map(|w| w.to_string()).collect();
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(warned, warnings);
+    }
+
+    #[test]
+    fn load_all_channels_carries_on_past_a_channel_that_fails() {
+        let volts = "/'group'/'volts'";
+        let complex = "/'group'/'complex'";
+        let mut bytes = TdmsBuilder::new()
+            .segment(
+                SegmentBuilder::new()
+                    .channel(CHANNEL, i32_ramp(0, 10))
+                    .channel(complex, ramp(DataTypeRaw::U64, 0, 5))
+                    .channel(volts, ramp(DataTypeRaw::DoubleFloat, 0, 4)),
+            )
+            .segment(
+                SegmentBuilder::new()
+                    .channel(CHANNEL, i32_ramp(10, 10))
+                    .channel(complex, ramp(DataTypeRaw::U64, 5, 5))
+                    .channel(volts, ramp(DataTypeRaw::DoubleFloat, 4, 4)),
+            )
+            .build();
+        // Mapped like any other 8 byte type but not yet decoded
+        set_raw_data_type(&mut bytes, complex, DataTypeRaw::ComplexSingleFloat as u32);
+
+        let tdms = TdmsFile::new(Cursor::new(bytes)).unwrap();
+        let results = tdms.load_all_channels();
+        let paths: Vec<&str> = results.keys().map(String::as_str).collect();
+        assert_eq!(paths, vec![CHANNEL, complex, volts]);
+
+        assert_eq!(
+            results[CHANNEL].as_ref().unwrap(),
+            &i32_values(&[(0, 10), (10, 10)])
+        );
+        assert_eq!(
+            results[volts].as_ref().unwrap(),
+            &ramp(DataTypeRaw::DoubleFloat, 0, 8)
+        );
+        match &results[complex] {
+            Err(TdmsError::LoadFailed { path, source }) => {
+                assert_eq!(path, complex);
+                assert!(matches!(
+                    **source,
+                    TdmsError::UnsupportedDataType(DataTypeRaw::ComplexSingleFloat)
+                ));
+            }
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn quantities_of_non_numeric_channels_fail() {
+        let volts = "/'group'/'volts'";
+        let notes = "/'group'/'notes'";
+        let bytes = TdmsBuilder::new()
+            .segment(
+                SegmentBuilder::new()
+                    .channel(volts, i32_ramp(0, 4))
+                    .property("unit_string", DataType::TdmsString("V".to_string()))
+                    .channel(notes, ramp(DataTypeRaw::TdmsString, 0, 4))
+                    .channel(CHANNEL, ramp(DataTypeRaw::TimeStamp, 0, 4)),
+            )
+            .build();
+        let tdms = TdmsFile::new(Cursor::new(bytes)).unwrap();
+
+        let quantity = tdms.load_quantity(volts).unwrap();
+        assert_eq!(quantity.values, vec![0.0, 1.0, 2.0, 3.0]);
+        assert_eq!(quantity.unit, "V");
+        for (path, data_type) in [
+            (notes, DataTypeRaw::TdmsString),
+            (CHANNEL, DataTypeRaw::TimeStamp),
+        ] {
+            match tdms.load_quantity(path) {
+                Err(TdmsError::NotNumeric {
+                    path: failed,
+                    data_type: failed_type,
+                }) => assert_eq!((failed.as_str(), failed_type), (path, data_type)),
+                other => panic!("{:?}", other.map(|quantity| quantity.values)),
+            }
+        }
+        // Nor does converting them directly panic
+        let strings = tdms.load_data(notes).unwrap();
+        assert!(matches!(
+            Vec::<f64>::try_from(strings),
+            Err(TdmsError::UnsupportedDataType(DataTypeRaw::TdmsString))
+        ));
+    }
+
+    #[test]
+    fn progress_rises_to_the_file_length() {
+        let segment = |start| SegmentBuilder::new().channel(CHANNEL, i32_ramp(start, 10));
+        let bytes = TdmsBuilder::new()
+            .segment(segment(0))
+            .segment(segment(10))
+            .segment(segment(20))
+            .build();
+        let length = bytes.len() as u64;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logged.tdms");
+        fs::write(&path, &bytes).unwrap();
+
+        let mut reported = Vec::new();
+        let tdms = TdmsFile::open_with_progress(&path, ReadOptions::new(), |mapped, of| {
+            reported.push((mapped, of))
+        })
+        .unwrap();
+        // Once at the end of each segment
+        let ends: Vec<(u64, u64)> = tdms.segments()[1..]
+            .iter()
+            .map(|segment| (segment.start_index, length))
+            .chain([(length, length)])
+            .collect();
+        assert_eq!(reported, ends);
+        assert_eq!(
+            tdms.load_data(CHANNEL).unwrap(),
+            i32_values(&[(0, 10), (10, 10), (20, 10)])
+        );
+
+        // An index of the first segment maps it in one step before walking the rest
+        let index = TdmsBuilder::new().segment(segment(0).tag(*b"TDSh")).build();
+        let meta_data_len = u64::from_le_bytes(index[20..28].try_into().unwrap());
+        let index = &index[..(HEADER_LEN + meta_data_len) as usize];
+        fs::write(dir.path().join("logged.tdms_index"), index).unwrap();
+        let mut reported = Vec::new();
+        let tdms = TdmsFile::open_with_progress(&path, ReadOptions::new(), |mapped, _| {
+            reported.push(mapped)
+        })
+        .unwrap();
+        assert!(tdms.mapped_from_index());
+        assert_eq!(reported, vec![ends[0].0, ends[1].0, length]);
+
+        let options = ReadOptions::new().ignore_index_file(true);
+        let tdms = TdmsFile::open_with_progress(&path, options, |_, _| {}).unwrap();
+        assert!(!tdms.mapped_from_index());
+
+        // Options apply as they do to open_with_options
+        fs::write(&path, &bytes[..bytes.len() - 4]).unwrap();
+        let options = ReadOptions::new()
+            .ignore_index_file(true)
+            .parse_mode(Some(ParseMode::Strict));
+        assert!(TdmsFile::open_with_progress(&path, options, |_, _| {}).is_err());
+    }
+
+    #[test]
+    fn open_channel_maps_only_the_channel_asked_for() {
+        let volts = "/'group'/'volts'";
+        let segment = |start| {
+            SegmentBuilder::new()
+                .channel(CHANNEL, i32_ramp(start, 10))
+                .channel(volts, ramp(DataTypeRaw::DoubleFloat, start, 10))
+        };
+        let bytes = TdmsBuilder::new()
+            .segment(segment(0))
+            .segment(segment(10))
+            .build();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("two.tdms");
+        fs::write(&path, &bytes).unwrap();
+
+        let (tdms, data) = TdmsFile::open_channel(&path, volts, ReadOptions::new()).unwrap();
+        assert_eq!(data, ramp(DataTypeRaw::DoubleFloat, 0, 20));
+        assert_eq!(tdms.channel_read_pairs(volts).unwrap().len(), 2);
+        // The other channel is listed, but nothing of where its data lies was built
+        assert!(tdms.all_objects().contains(&CHANNEL));
+        assert!(tdms.channel_read_pairs(CHANNEL).unwrap().is_empty());
+        assert_eq!(
+            tdms.load_data(CHANNEL).unwrap(),
+            DataTypeVec::I32(Vec::new())
+        );
+
+        // Mapped from the index file, as open_with_options would
+        let index = TdmsBuilder::new().segment(segment(0).tag(*b"TDSh")).build();
+        let meta_data_len = u64::from_le_bytes(index[20..28].try_into().unwrap());
+        let index = &index[..(HEADER_LEN + meta_data_len) as usize];
+        fs::write(dir.path().join("two.tdms_index"), index).unwrap();
+        let (tdms, data) = TdmsFile::open_channel(&path, CHANNEL, ReadOptions::new()).unwrap();
+        assert!(tdms.mapped_from_index());
+        assert_eq!(data, i32_values(&[(0, 10), (10, 10)]));
+        assert!(tdms.channel_read_pairs(volts).unwrap().is_empty());
+
+        let options = ReadOptions::new().metadata_only(true);
+        let error = TdmsFile::open_channel(&path, CHANNEL, options)
+            .err()
+            .unwrap();
+        assert!(matches!(error, TdmsError::MetadataOnly));
+    }
+
+    #[test]
+    fn value_counts_overflowing_u64_are_malformed() {
+        let notes = "/'group'/'notes'";
+        // Rewrite the dimension and value count following the type in the object's index
+        let sized = |path: &str, data: DataTypeVec, dim: u32, no_values: u64| {
+            let mut bytes = TdmsBuilder::new()
+                .segment(SegmentBuilder::new().channel(path, data))
+                .build();
+            let at = bytes
+                .windows(path.len())
+                .position(|window| window == path.as_bytes())
+                .unwrap();
+            let dim_at = at + path.len() + 8;
+            bytes[dim_at..dim_at + 4].copy_from_slice(&dim.to_le_bytes());
+            bytes[dim_at + 4..dim_at + 12].copy_from_slice(&no_values.to_le_bytes());
+            bytes
+        };
+        let files = [
+            sized(CHANNEL, i32_ramp(0, 10), 1, u64::MAX / 2),
+            sized(CHANNEL, i32_ramp(0, 10), 1 << 31, 1 << 33),
+            // Strings give their size, so only the count of values overflows
+            sized(
+                notes,
+                ramp(DataTypeRaw::TdmsString, 0, 10),
+                1 << 31,
+                1 << 40,
+            ),
+        ];
+
+        for bytes in files {
+            for mode in [None, Some(ParseMode::Strict)] {
+                let (_path, tdms) = open_bytes(&bytes, ReadOptions::new().parse_mode(mode));
+                let error = tdms.err().unwrap();
+                assert!(
+                    matches!(error.root_cause(), TdmsError::MalformedSegment),
+                    "{:?}",
+                    error
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn a_single_interleaved_channel_reads_contiguously() {
+        let segment = |start| {
+            SegmentBuilder::new()
+                .channel(CHANNEL, i32_ramp(start, 5))
+                .interleaved(true)
+                .chunks(3)
+        };
+        let bytes = TdmsBuilder::new()
+            .segment(segment(0))
+            .segment(segment(100))
+            .build();
+        let tdms = TdmsFile::new(Cursor::new(bytes)).unwrap();
+
+        // With nothing between its values there's no stride to step over
+        let pairs = tdms.channel_read_pairs(CHANNEL).unwrap();
+        assert_eq!(pairs.len(), 6);
+        assert!(pairs
+            .iter()
+            .all(|&(_, no_values, interleaved)| { no_values == 5 && !interleaved }));
+        let expected = [(0, 5), (0, 5), (0, 5), (100, 5), (100, 5), (100, 5)];
+        assert_eq!(tdms.load_data(CHANNEL).unwrap(), i32_values(&expected));
+        assert_eq!(
+            tdms.load_segment_data(CHANNEL, 1).unwrap(),
+            i32_values(&expected[3..])
+        );
+    }
+
+    #[test]
+    fn envelopes_give_the_min_and_max_of_each_bucket() {
+        let volts = "/'group'/'volts'";
+        let notes = "/'group'/'notes'";
+        let values = [5.0, -1.0, 3.0, 2.0, 9.0, 0.0, 4.0, f64::NAN, 7.0, 7.5];
+        let bytes = TdmsBuilder::new()
+            .segment(
+                SegmentBuilder::new()
+                    .channel(volts, DataTypeVec::Double(values[..6].to_vec()))
+                    .channel(notes, ramp(DataTypeRaw::TdmsString, 0, 2)),
+            )
+            .segment(
+                SegmentBuilder::new().channel(volts, DataTypeVec::Double(values[6..].to_vec())),
+            )
+            .build();
+        let tdms = TdmsFile::new(Cursor::new(bytes)).unwrap();
+
+        // Ten values don't divide into three buckets, the first takes the extra one
+        let envelope = tdms.load_envelope(volts, 3).unwrap();
+        assert_eq!(envelope, vec![(-1.0, 5.0), (0.0, 9.0), (7.0, 7.5)]);
+        assert_eq!(tdms.load_envelope(volts, 1).unwrap(), vec![(-1.0, 9.0)]);
+        // No more buckets than values, each holding one
+        let envelope = tdms.load_envelope(volts, 20).unwrap();
+        assert_eq!(envelope.len(), 10);
+        assert_eq!(envelope[1], (-1.0, -1.0));
+        assert!(envelope[7].0.is_nan() && envelope[7].1.is_nan());
+        assert!(tdms.load_envelope(volts, 0).unwrap().is_empty());
+
+        assert!(matches!(
+            tdms.load_envelope(notes, 3),
+            Err(TdmsError::NotNumeric { .. })
+        ));
+    }
+
+    #[test]
+    fn segment_data_loads_only_that_segment() {
+        let volts = "/'group'/'volts'";
+        let bytes = TdmsBuilder::new()
+            .segment(
+                SegmentBuilder::new()
+                    .channel(CHANNEL, i32_ramp(0, 10))
+                    .channel(volts, ramp(DataTypeRaw::DoubleFloat, 0, 3)),
+            )
+            .segment(
+                SegmentBuilder::new()
+                    .channel(CHANNEL, i32_ramp(10, 5))
+                    .chunks(2),
+            )
+            .segment(
+                SegmentBuilder::new()
+                    .channel(CHANNEL, i32_ramp(20, 10))
+                    .channel(volts, ramp(DataTypeRaw::DoubleFloat, 3, 3)),
+            )
+            .build();
+        let tdms = TdmsFile::new(Cursor::new(bytes)).unwrap();
+
+        assert_eq!(tdms.load_segment_data(CHANNEL, 0).unwrap(), i32_ramp(0, 10));
+        assert_eq!(
+            tdms.load_segment_data(CHANNEL, 1).unwrap(),
+            i32_values(&[(10, 5), (10, 5)])
+        );
+        assert_eq!(
+            tdms.load_segment_data(CHANNEL, 2).unwrap(),
+            i32_ramp(20, 10)
+        );
+        assert_eq!(
+            tdms.load_segment_data(volts, 2).unwrap(),
+            ramp(DataTypeRaw::DoubleFloat, 3, 3)
+        );
+        // No data in the segment, or no such segment
+        assert_eq!(
+            tdms.load_segment_data(volts, 1).unwrap(),
+            DataTypeVec::Double(Vec::new())
+        );
+        match tdms.load_segment_data(CHANNEL, 3) {
+            Err(TdmsError::SegmentOutOfRange { index, no_segments }) => {
+                assert_eq!((index, no_segments), (3, 3))
+            }
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn segment_boundaries_follow_each_segments_values() {
+        let volts = "/'group'/'volts'";
+        let bytes = TdmsBuilder::new()
+            .segment(SegmentBuilder::new().channel(CHANNEL, i32_ramp(0, 10)))
+            .segment(SegmentBuilder::new().channel(volts, ramp(DataTypeRaw::DoubleFloat, 0, 4)))
+            .segment(
+                SegmentBuilder::new()
+                    .channel(CHANNEL, i32_ramp(10, 5))
+                    .chunks(2),
+            )
+            // Alike segments whose read pairs are merged into one run
+            .segment(SegmentBuilder::new().channel(CHANNEL, i32_ramp(20, 3)))
+            .segment(SegmentBuilder::new().channel(CHANNEL, i32_ramp(30, 3)))
+            .build();
+        let tdms = TdmsFile::new(Cursor::new(bytes)).unwrap();
+
+        assert_eq!(
+            tdms.segment_boundaries(CHANNEL).unwrap(),
+            vec![10, 20, 23, 26]
+        );
+        let segmented = tdms.load_data_segmented(CHANNEL).unwrap();
+        let expected = vec![
+            i32_ramp(0, 10),
+            i32_values(&[(10, 5), (10, 5)]),
+            i32_ramp(20, 3),
+            i32_ramp(30, 3),
+        ];
+        assert_eq!(segmented, expected);
+
+        // Splitting the whole channel at the boundaries gives the same
+        let data = tdms.load_data(CHANNEL).unwrap();
+        let mut start = 0;
+        for (boundary, segment) in tdms
+            .segment_boundaries(CHANNEL)
+            .unwrap()
+            .iter()
+            .zip(&segmented)
+        {
+            let values: Vec<DataType> = (start..*boundary as usize)
+                .map(|i| data.get(i).unwrap())
+                .collect();
+            let segment_values: Vec<DataType> = (0..segment.len())
+                .map(|i| segment.get(i).unwrap())
+                .collect();
+            assert_eq!(values, segment_values);
+            start = *boundary as usize;
+        }
+        assert_eq!(start, data.len());
+
+        assert_eq!(tdms.segment_boundaries(volts).unwrap(), vec![4]);
+    }
+
+    #[test]
+    fn two_dimensional_channels_load_every_value() {
+        let pairs = "/'group'/'pairs'";
+        let segment = |start| {
+            SegmentBuilder::new()
+                .channel(pairs, i32_ramp(start, 10))
+                .dimension(2)
+                .channel(CHANNEL, i32_ramp(start + 100, 3))
+                .chunks(2)
+        };
+        let bytes = TdmsBuilder::new()
+            .segment(segment(0))
+            .segment(segment(10))
+            .build();
+        let tdms = TdmsFile::new(Cursor::new(bytes)).unwrap();
+
+        // Five raw values of two each, in each chunk
+        assert_eq!(tdms.channel_dimension(pairs).unwrap(), 2);
+        assert_eq!(tdms.channel_dimension(CHANNEL).unwrap(), 1);
+        assert_eq!(tdms.channel_len(pairs).unwrap(), 40);
+        let info = tdms.channels()[0].info(&tdms).unwrap();
+        assert_eq!(
+            (info.dimension, info.no_values, info.total_bytes),
+            (2, 40, 160)
+        );
+        assert_eq!(
+            tdms.load_data(pairs).unwrap(),
+            i32_values(&[(0, 10), (0, 10), (10, 10), (10, 10)])
+        );
+        // The chunk math for the channel after it accounts for the whole of its data
+        assert_eq!(
+            tdms.load_data(CHANNEL).unwrap(),
+            i32_values(&[(100, 3), (100, 3), (110, 3), (110, 3)])
+        );
+    }
+
+    #[test]
+    fn segment_layout_reports_a_late_channel_absent_before_it_appears() {
+        let late = "/'group'/'late'";
+        let bytes = TdmsBuilder::new()
+            .segment(
+                SegmentBuilder::new()
+                    .channel(CHANNEL, i32_ramp(0, 5))
+                    .chunks(2),
+            )
+            .segment(SegmentBuilder::new().channel(CHANNEL, i32_ramp(5, 5)))
+            .segment(
+                SegmentBuilder::new()
+                    .channel(CHANNEL, i32_ramp(10, 5))
+                    .channel(late, i32_ramp(100, 4)),
+            )
+            .build();
+        let tdms = TdmsFile::new(Cursor::new(bytes)).unwrap();
+
+        let presence = |present, no_chunks, no_values| SegmentPresence {
+            present,
+            no_chunks,
+            no_values,
+        };
+        assert_eq!(
+            tdms.channel_segment_layout(CHANNEL).unwrap(),
+            vec![
+                presence(true, 2, 10),
+                presence(true, 1, 5),
+                presence(true, 1, 5)
+            ]
+        );
+        assert_eq!(
+            tdms.channel_segment_layout(late).unwrap(),
+            vec![
+                presence(false, 0, 0),
+                presence(false, 0, 0),
+                presence(true, 1, 4)
+            ]
+        );
+    }
+
+    #[test]
+    fn counts_only_channels_with_data_and_the_groups_holding_them() {
+        let mut segment = SegmentBuilder::new()
+            .object("/")
+            .object("/'a'")
+            .object("/'b'");
+        for path in [
+            "/'a'/'one'",
+            "/'a'/'two'",
+            "/'a'/'three'",
+            "/'b'/'four'",
+            "/'b'/'five'",
+        ] {
+            segment = segment.channel(path, i32_ramp(0, 4));
+        }
+        let bytes = TdmsBuilder::new().segment(segment).build();
+        let tdms = TdmsFile::new(Cursor::new(bytes)).unwrap();
+
+        assert_eq!(tdms.channel_count(), 5);
+        assert_eq!(tdms.group_count(), 2);
+        assert!(tdms.has_channel("/'b'/'four'"));
+        // The root, groups and missing paths aren't channels
+        assert!(!tdms.has_channel("/"));
+        assert!(!tdms.has_channel("/'a'"));
+        assert!(!tdms.has_channel("/'a'/'four'"));
+    }
+
+    #[test]
+    fn read_pairs_point_at_each_chunk_in_the_segments() {
+        let other = "/'group'/'other'";
+        let bytes = TdmsBuilder::new()
+            .segment(
+                SegmentBuilder::new()
+                    .channel(other, i32_ramp(0, 3))
+                    .channel(CHANNEL, i32_ramp(0, 5))
+                    .chunks(2),
+            )
+            .segment(
+                SegmentBuilder::new()
+                    .channel(other, i32_ramp(0, 4))
+                    .channel(CHANNEL, i32_ramp(5, 4))
+                    .interleaved(true),
+            )
+            .build();
+        let tdms = TdmsFile::new(Cursor::new(bytes)).unwrap();
+        let raw_start: Vec<u64> = tdms
+            .segments()
+            .iter()
+            .map(|segment| segment.start_index + HEADER_LEN + segment.raw_data_offset)
+            .collect();
+
+        // Contiguous chunks follow the other channel's 12 bytes, 32 bytes apart, and the
+        // interleaved segment starts at the channel's column of each row
+        assert_eq!(
+            tdms.channel_read_pairs(CHANNEL).unwrap(),
+            vec![
+                (raw_start[0] + 12, 5, false),
+                (raw_start[0] + 44, 5, false),
+                (raw_start[1] + 4, 4, true),
+            ]
+        );
+        assert_eq!(
+            tdms.load_data(CHANNEL).unwrap(),
+            i32_values(&[(0, 5), (0, 5), (5, 4)])
+        );
+    }
+
+    #[test]
+    fn segment_flag_accessors_match_the_toc() {
+        let bytes = TdmsBuilder::new()
+            .segment(SegmentBuilder::new().channel(CHANNEL, i32_ramp(0, 4)))
+            .segment(
+                SegmentBuilder::new()
+                    .channel(CHANNEL, i32_ramp(4, 4))
+                    .new_obj_list(false)
+                    .interleaved(true)
+                    .big_endian(true),
+            )
+            .build();
+        let tdms = TdmsFile::new(Cursor::new(bytes)).unwrap();
+        let flags = |segment: &SegmentInfo| {
+            [
+                segment.has_meta_data(),
+                segment.has_raw_data(),
+                segment.has_new_object_list(),
+                segment.has_interleaved(),
+                segment.is_big_endian(),
+                segment.has_daqmx(),
+            ]
+        };
+
+        let segments = tdms.segments();
+        assert_eq!(segments[0].toc_flags, 0b1110);
+        assert_eq!(flags(&segments[0]), [true, true, true, false, false, false]);
+        assert_eq!(segments[1].toc_flags, 0b110_1010);
+        assert_eq!(flags(&segments[1]), [true, true, false, true, true, false]);
+        assert!(segments[1].has_flag(TocProperties::KTocBigEndian));
+    }
+
+    #[test]
+    fn loads_over_the_byte_limit_fail_before_reading() {
+        let bytes = TdmsBuilder::new()
+            .segment(SegmentBuilder::new().channel(CHANNEL, i32_ramp(0, 10)))
+            .build();
+        let options = |limit| ReadOptions::new().max_load_bytes(Some(limit));
+
+        let (_path, tdms) = open_bytes(&bytes, options(39));
+        let tdms = tdms.unwrap();
+        assert_eq!(tdms.estimated_load_size(CHANNEL).unwrap(), 40);
+        assert!(matches!(
+            tdms.load_data(CHANNEL),
+            Err(TdmsError::LoadTooLarge {
+                needed: 40,
+                limit: 39
+            })
+        ));
+        assert!(matches!(
+            tdms.load_data_segmented(CHANNEL),
+            Err(TdmsError::LoadTooLarge { .. })
+        ));
+
+        let (_path, tdms) = open_bytes(&bytes, options(40));
+        assert_eq!(tdms.unwrap().load_data(CHANNEL).unwrap(), i32_ramp(0, 10));
+    }
+
+    #[test]
+    fn forcing_the_other_byte_order_swaps_the_values() {
+        let values = [1, -2, 0x0102_0304];
+        let swapped = DataTypeVec::I32(values.iter().map(|x: &i32| x.swap_bytes()).collect());
+        for big_endian in [false, true] {
+            let bytes = TdmsBuilder::new()
+                .segment(
+                    SegmentBuilder::new()
+                        .channel(CHANNEL, DataTypeVec::I32(values.to_vec()))
+                        .big_endian(big_endian),
+                )
+                .build();
+            let tdms = TdmsFile::new(Cursor::new(bytes)).unwrap();
+            let (same, other) = match big_endian {
+                false => (Endianness::Little, Endianness::Big),
+                true => (Endianness::Big, Endianness::Little),
+            };
+
+            assert_eq!(
+                tdms.load_data_with_endianness(CHANNEL, same).unwrap(),
+                DataTypeVec::I32(values.to_vec())
+            );
+            assert_eq!(
+                tdms.load_data_with_endianness(CHANNEL, other).unwrap(),
+                swapped
+            );
+        }
+    }
+
+    #[test]
+    fn lead_in_dumps_annotate_the_bytes_in_the_file() {
+        let bytes = TdmsBuilder::new()
+            .segment(SegmentBuilder::new().channel(CHANNEL, i32_ramp(0, 4)))
+            .segment(
+                SegmentBuilder::new()
+                    .channel(CHANNEL, i32_ramp(4, 4))
+                    .big_endian(true),
+            )
+            .build();
+        let tdms = TdmsFile::new(Cursor::new(bytes.clone())).unwrap();
+        let segment = &tdms.segments()[1];
+        let start = segment.start_index as usize;
+
+        let dump = tdms.dump_segment_lead_in(1).unwrap();
+        let lines: Vec<&str> = dump.lines().collect();
+        assert_eq!(lines[0], format!("Segment 1 lead in at byte {}", start));
+        assert!(lines[1].ends_with("tag                  \"TDSm\""));
+        assert!(lines[2].ends_with(
+            "ToC mask             0x0000004E KTocMetaData | KTocNewObjList | KTocRawData | KTocBigEndian"
+        ));
+        assert!(lines[3].ends_with("version              4713"));
+        assert!(lines[4].ends_with(&format!(
+            "next segment offset  {}",
+            segment.next_segment_offset
+        )));
+        assert!(lines[5].ends_with(&format!("raw data offset      {}", segment.raw_data_offset)));
+
+        // The hex columns are the lead in exactly as written
+        let hex: Vec<u8> = lines[1..]
+            .iter()
+            .flat_map(|line| line[..23].split_whitespace())
+            .map(|byte| u8::from_str_radix(byte, 16).unwrap())
+            .collect();
+        assert_eq!(hex, bytes[start..start + HEADER_LEN as usize]);
+
+        assert!(matches!(
+            tdms.dump_segment_lead_in(2),
+            Err(TdmsError::SegmentOutOfRange {
+                index: 2,
+                no_segments: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn raw_data_sizes_are_type_size_times_values() {
+        let doubles = "/'group'/'doubles'";
+        let bytes = TdmsBuilder::new()
+            .segment(
+                SegmentBuilder::new()
+                    .channel(CHANNEL, i32_ramp(0, 5))
+                    .channel(doubles, ramp(DataTypeRaw::DoubleFloat, 0, 3))
+                    .chunks(2),
+            )
+            .segment(SegmentBuilder::new().channel(CHANNEL, i32_ramp(5, 7)))
+            .build();
+        let tdms = TdmsFile::new(Cursor::new(bytes)).unwrap();
+
+        assert_eq!(tdms.segment_raw_data_size(0).unwrap(), 2 * (4 * 5 + 8 * 3));
+        assert_eq!(tdms.segment_raw_data_size(1).unwrap(), 4 * 7);
+        assert!(matches!(
+            tdms.segment_raw_data_size(2),
+            Err(TdmsError::SegmentOutOfRange {
+                index: 2,
+                no_segments: 2
+            })
+        ));
+        for (path, type_size) in [(CHANNEL, 4), (doubles, 8)] {
+            assert_eq!(
+                tdms.channel_total_bytes(path).unwrap(),
+                type_size * tdms.channel_len(path).unwrap()
+            );
+        }
+        assert_eq!(tdms.channel_total_bytes(CHANNEL).unwrap(), 4 * 17);
+    }
+
+    /// Counts the reads made of the reader it wraps
+    struct CountingReader {
+        inner: Cursor<Vec<u8>>,
+        reads: usize,
+    }
+
+    impl Read for CountingReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.reads += 1;
+            self.inner.read(buf)
+        }
+    }
+
+    impl Seek for CountingReader {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    #[test]
+    fn larger_buffers_map_with_fewer_reads() {
+        let mut segment = SegmentBuilder::new();
+        for i in 0..500 {
+            segment = segment
+                .channel(&format!("/'group'/'channel {}'", i), i32_ramp(0, 1))
+                .property("description", DataType::TdmsString("x".repeat(20)));
+        }
+        let bytes = TdmsBuilder::new().segment(segment).build();
+        assert!(bytes.len() > 32 * 1024);
+
+        let reads = |options: ReadOptions| {
+            let inner = CountingReader {
+                inner: Cursor::new(bytes.clone()),
+                reads: 0,
+            };
+            let mut reader = buffered(inner, &options);
+            let file_length = reader.seek(SeekFrom::End(0)).unwrap();
+            let mut tdms_map = TdmsMap::new(options);
+            tdms_map.map_segments(&mut reader, file_length).unwrap();
+            assert_eq!(tdms_map.all_objects.len(), 500);
+            reader.get_ref().reads
+        };
+
+        let standard = reads(ReadOptions::new());
+        let large = reads(ReadOptions::new().buffer_capacity(Some(1 << 20)));
+        // The meta data takes a read per 8 KB buffer full, or one read of the larger buffer
+        assert!(
+            standard >= bytes.len() / DEFAULT_BUFFER_CAPACITY,
+            "{} reads",
+            standard
+        );
+        assert!(large <= 2, "{} reads", large);
+    }
+
+    #[test]
+    fn tdms_version_comes_from_the_first_segment() {
+        let file = |first, second| {
+            TdmsBuilder::new()
+                .segment(
+                    SegmentBuilder::new()
+                        .channel(CHANNEL, i32_ramp(0, 2))
+                        .version(first),
+                )
+                .segment(
+                    SegmentBuilder::new()
+                        .channel(CHANNEL, i32_ramp(2, 2))
+                        .version(second),
+                )
+                .build()
+        };
+        for (first, second) in [
+            (TDMS_VERSION_2, TDMS_VERSION_2),
+            (TDMS_VERSION_1, TDMS_VERSION_1),
+            (TDMS_VERSION_1, TDMS_VERSION_2),
+        ] {
+            let tdms = TdmsFile::new(Cursor::new(file(first, second))).unwrap();
+            assert_eq!(tdms.tdms_version(), Some(first));
+            assert_eq!(tdms.info().version, Some(first));
+        }
+        assert_eq!(
+            TdmsFile::new(Cursor::new(Vec::new()))
+                .unwrap()
+                .tdms_version(),
+            None
+        );
+    }
 }
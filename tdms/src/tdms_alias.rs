@@ -0,0 +1,213 @@
+use std::io::{Read, Seek};
+
+use indexmap::IndexMap;
+
+use crate::tdms_datatypes::DataTypeVec;
+use crate::tdms_error::{Result, TdmsError};
+use crate::tdms_path::{path_components, ObjectPathRef};
+use crate::{edit_distance, TdmsFile};
+
+impl<R: Read + Seek> TdmsFile<R> {
+    /// Give a channel a friendly name to show users and load it by, the path is still what
+    /// it's indexed by. Replaces any alias the channel already had. Fails with `AliasInUse` if
+    /// another channel already has the alias.
+    pub fn set_alias<'a, P: Into<ObjectPathRef<'a>>>(
+        &mut self,
+        path: P,
+        alias: &str,
+    ) -> Result<()> {
+        let path = path.into();
+        self.tdms_map.object_map(path.as_str())?;
+        if let Some(other) = self
+            .alias_path(alias)
+            .filter(|other| *other != path.as_str())
+        {
+            return Err(TdmsError::AliasInUse {
+                alias: alias.to_string(),
+                path: other.to_string(),
+            });
+        }
+        self.aliases
+            .insert(path.as_str().to_string(), alias.to_string());
+        Ok(())
+    }
+
+    /// Remove a channel's alias, returning it if it had one
+    pub fn remove_alias<'a, P: Into<ObjectPathRef<'a>>>(&mut self, path: P) -> Option<String> {
+        self.aliases.shift_remove(path.into().as_str())
+    }
+
+    /// A channel's alias, if it's been given one
+    pub fn alias<'a, P: Into<ObjectPathRef<'a>>>(&self, path: P) -> Option<&str> {
+        self.aliases.get(path.into().as_str()).map(String::as_str)
+    }
+
+    /// All aliases keyed by channel path, in the order they were set. With the `serde` feature
+    /// the map can be serialized to save the aliases and restored with `set_aliases`.
+    pub fn aliases(&self) -> &IndexMap<String, String> {
+        &self.aliases
+    }
+
+    /// Replace all aliases with the given map of channel paths to aliases, e.g. one saved from
+    /// `aliases`. Nothing is changed if any path isn't in the file or any alias is repeated.
+    pub fn set_aliases(&mut self, aliases: IndexMap<String, String>) -> Result<()> {
+        let previous = std::mem::take(&mut self.aliases);
+        for (path, alias) in &aliases {
+            if let Err(e) = self.set_alias(path.as_str(), alias) {
+                self.aliases = previous;
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Load a channel's data by its alias
+    pub fn load_by_alias(&self, alias: &str) -> Result<DataTypeVec> {
+        match self.alias_path(alias) {
+            Some(path) => self.load_data(path),
+            None => {
+                let mut scored: Vec<(usize, &String)> = self
+                    .aliases
+                    .values()
+                    .map(|other| (edit_distance(alias, other), other))
+                    .collect();
+                scored.sort();
+                Err(TdmsError::ChannelNotFound {
+                    path: alias.to_string(),
+                    closest: scored
+                        .into_iter()
+                        .take(3)
+                        .map(|(_, other)| other.clone())
+                        .collect(),
+                })
+            }
+        }
+    }
+
+    /// A display name for a channel worked out from its path: the channel name without the
+    /// path's quoting, with control characters such as newlines replaced by spaces and runs of
+    /// whitespace collapsed. Where channels in different groups would get the same
+    /// name, those after the first in the file get a numeric suffix, e.g. `Voltage_2`.
+    pub fn suggested_name<'a, P: Into<ObjectPathRef<'a>>>(&self, path: P) -> Result<String> {
+        let path = path.into();
+        self.tdms_map.object_map(path.as_str())?;
+
+        let mut taken: Vec<String> = Vec::new();
+        for (other, _) in &self.tdms_map.all_objects {
+            let name = match path_components(other).as_slice() {
+                [_, name] => clean_name(name),
+                _ => continue,
+            };
+            let mut suggested = name.clone();
+            let mut suffix = 2;
+            while taken.contains(&suggested) {
+                suggested = format!("{}_{}", name, suffix);
+                suffix += 1;
+            }
+            if other == path.as_str() {
+                return Ok(suggested);
+            }
+            taken.push(suggested);
+        }
+        // Not a channel, e.g. a group, so there's nothing to collide with
+        Ok(path_components(path.as_str())
+            .last()
+            .map(|name| clean_name(name))
+            .unwrap_or_default())
+    }
+
+    fn alias_path(&self, alias: &str) -> Option<&str> {
+        self.aliases
+            .iter()
+            .find(|(_, other)| *other == alias)
+            .map(|(path, _)| path.as_str())
+    }
+}
+
+/// A name with control characters replaced by spaces, runs of whitespace collapsed and
+/// surrounding whitespace trimmed
+fn clean_name(name: &str) -> String {
+    name.replace(|c: char| c.is_control(), " ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tdms_datatypes::DataTypeRaw;
+    use crate::test_util::{ramp, SegmentBuilder, TdmsBuilder};
+    use std::io::Cursor;
+
+    const VOLTS: &str = "/'rig'/'volts'";
+    const AMPS: &str = "/'rig'/'amps'";
+
+    #[test]
+    fn aliases_load_the_channel_they_name() {
+        let bytes = TdmsBuilder::new()
+            .segment(
+                SegmentBuilder::new()
+                    .channel(VOLTS, ramp(DataTypeRaw::DoubleFloat, 0, 5))
+                    .channel(AMPS, ramp(DataTypeRaw::I32, 10, 3)),
+            )
+            .build();
+        let mut tdms = TdmsFile::new(Cursor::new(bytes)).unwrap();
+        tdms.set_alias(VOLTS, "Voltage").unwrap();
+        tdms.set_alias(AMPS, "Current").unwrap();
+
+        assert_eq!(
+            tdms.load_by_alias("Voltage").unwrap(),
+            ramp(DataTypeRaw::DoubleFloat, 0, 5)
+        );
+        assert_eq!(
+            tdms.load_by_alias("Current").unwrap(),
+            ramp(DataTypeRaw::I32, 10, 3)
+        );
+        assert_eq!(tdms.alias(AMPS), Some("Current"));
+
+        // An alias belongs to one channel at a time
+        match tdms.set_alias(AMPS, "Voltage") {
+            Err(TdmsError::AliasInUse { alias, path }) => {
+                assert_eq!((alias.as_str(), path.as_str()), ("Voltage", VOLTS))
+            }
+            other => panic!("{:?}", other),
+        }
+        tdms.set_alias(VOLTS, "Volts").unwrap();
+        assert_eq!(tdms.alias(VOLTS), Some("Volts"));
+        assert_eq!(tdms.remove_alias(AMPS).as_deref(), Some("Current"));
+        assert!(matches!(
+            tdms.load_by_alias("Current"),
+            Err(TdmsError::ChannelNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn unknown_aliases_suggest_the_closest() {
+        let bytes = TdmsBuilder::new()
+            .segment(
+                SegmentBuilder::new()
+                    .channel(VOLTS, ramp(DataTypeRaw::DoubleFloat, 0, 5))
+                    .channel(AMPS, ramp(DataTypeRaw::I32, 10, 3)),
+            )
+            .build();
+        let mut tdms = TdmsFile::new(Cursor::new(bytes)).unwrap();
+        tdms.set_alias(VOLTS, "Voltage").unwrap();
+        tdms.set_alias(AMPS, "Current").unwrap();
+
+        match tdms.load_by_alias("Voltag") {
+            Err(TdmsError::ChannelNotFound { path, closest }) => {
+                assert_eq!(path, "Voltag");
+                assert_eq!(closest, ["Voltage", "Current"]);
+            }
+            other => panic!("{:?}", other),
+        }
+
+        // Aliases for channels not in the file are refused, leaving the others as they were
+        let mut aliases = IndexMap::new();
+        aliases.insert(VOLTS.to_string(), "V".to_string());
+        aliases.insert("/'rig'/'ohms'".to_string(), "R".to_string());
+        assert!(tdms.set_aliases(aliases).is_err());
+        assert_eq!(tdms.alias(VOLTS), Some("Voltage"));
+    }
+}
@@ -0,0 +1,240 @@
+use std::fs;
+use std::io::{BufReader, Read, Seek};
+use std::mem::size_of;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use indexmap::IndexMap;
+
+use crate::tdms_datatypes::DataTypeVec;
+use crate::tdms_error::Result;
+use crate::tdms_path::ObjectPathRef;
+use crate::TdmsFile;
+
+/// A file whose loaded channels are kept in memory for later loads, up to a total size in
+/// bytes. When a channel doesn't fit the least recently loaded ones are evicted to make room.
+/// Channels are shared as `Arc`s, so holding one costs nothing more and it stays valid after
+/// eviction.
+pub struct CachedTdmsFile<R: Read + Seek = BufReader<fs::File>> {
+    file: TdmsFile<R>,
+    max_bytes: u64,
+    cache: Mutex<ChannelCache>,
+}
+
+/// Channels in order of use, least recently used first, with their sizes in bytes
+#[derive(Default)]
+struct ChannelCache {
+    channels: IndexMap<String, (Arc<DataTypeVec>, u64)>,
+    bytes: u64,
+}
+
+impl<R: Read + Seek> CachedTdmsFile<R> {
+    /// Cache channels loaded from file, holding at most max_bytes of their data
+    pub fn new(file: TdmsFile<R>, max_bytes: u64) -> CachedTdmsFile<R> {
+        CachedTdmsFile {
+            file,
+            max_bytes,
+            cache: Mutex::default(),
+        }
+    }
+
+    /// The underlying file, e.g. for metadata or to load without caching
+    pub fn file(&self) -> &TdmsFile<R> {
+        &self.file
+    }
+
+    /// Load a channel's data, from the cache if it's there. Loaded data is cached unless it's
+    /// larger than the cache could ever hold.
+    pub fn get<'a, P: Into<ObjectPathRef<'a>>>(&self, path: P) -> Result<Arc<DataTypeVec>> {
+        let path = path.into();
+        if let Some(data) = self.lock().touch(path.as_str()) {
+            return Ok(data);
+        }
+
+        // Loaded without the lock held, so other channels can be served meanwhile
+        let data = Arc::new(self.file.load_data(path.as_str())?);
+        let bytes = data_bytes(&data);
+        if bytes <= self.max_bytes {
+            let mut cache = self.lock();
+            cache.remove(path.as_str());
+            while cache.bytes + bytes > self.max_bytes {
+                cache.evict_oldest();
+            }
+            cache.bytes += bytes;
+            cache
+                .channels
+                .insert(path.as_str().to_string(), (data.clone(), bytes));
+        }
+        Ok(data)
+    }
+
+    /// Drop a channel from the cache, returns false if it wasn't cached
+    pub fn evict<'a, P: Into<ObjectPathRef<'a>>>(&self, path: P) -> bool {
+        self.lock().remove(path.into().as_str())
+    }
+
+    /// Drop every channel from the cache
+    pub fn clear(&self) {
+        *self.lock() = ChannelCache::default();
+    }
+
+    /// The total bytes of the cached channels' data
+    pub fn cached_bytes(&self) -> u64 {
+        self.lock().bytes
+    }
+
+    /// The paths of the cached channels, least recently used first
+    pub fn cached_channels(&self) -> Vec<String> {
+        self.lock().channels.keys().cloned().collect()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ChannelCache> {
+        // The cache is consistent between statements, so is still usable after a panic
+        self.cache.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl ChannelCache {
+    /// A cached channel's data, marking it the most recently used
+    fn touch(&mut self, path: &str) -> Option<Arc<DataTypeVec>> {
+        let (data, bytes) = self.channels.shift_remove(path)?;
+        self.channels
+            .insert(path.to_string(), (data.clone(), bytes));
+        Some(data)
+    }
+
+    fn remove(&mut self, path: &str) -> bool {
+        match self.channels.shift_remove(path) {
+            Some((_, bytes)) => {
+                self.bytes -= bytes;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn evict_oldest(&mut self) {
+        if let Some((_, (_, bytes))) = self.channels.shift_remove_index(0) {
+            self.bytes -= bytes;
+        }
+    }
+}
+
+/// The bytes the data takes in memory, strings counted with their contents
+fn data_bytes(data: &DataTypeVec) -> u64 {
+    match data {
+        DataTypeVec::TdmsString(datavec) => datavec
+            .iter()
+            .map(|string| (size_of::<String>() + string.len()) as u64)
+            .sum(),
+        other => other.raw_size(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tdms_datatypes::DataTypeRaw;
+    use crate::test_util::{ramp, SegmentBuilder, TdmsBuilder};
+    use std::io::{Cursor, SeekFrom};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const CHANNELS: [&str; 3] = ["/'group'/'a'", "/'group'/'b'", "/'group'/'c'"];
+
+    /// A reader counting the reads made through it
+    struct CountingReader {
+        inner: Cursor<Vec<u8>>,
+        reads: Arc<AtomicUsize>,
+    }
+
+    impl Read for CountingReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.reads.fetch_add(1, Ordering::SeqCst);
+            self.inner.read(buf)
+        }
+    }
+
+    impl Seek for CountingReader {
+        fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    /// Channels of 10 i32s, 40 bytes each, cached up to max_bytes.
+    /// Channel i ramps from 100 * i.
+    fn cached_file(max_bytes: u64) -> (CachedTdmsFile<CountingReader>, Arc<AtomicUsize>) {
+        let mut segment = SegmentBuilder::new();
+        for (i, channel) in CHANNELS.iter().enumerate() {
+            segment = segment.channel(channel, ramp(DataTypeRaw::I32, 100 * i as u64, 10));
+        }
+        let reads = Arc::new(AtomicUsize::new(0));
+        let reader = CountingReader {
+            inner: Cursor::new(TdmsBuilder::new().segment(segment).build()),
+            reads: reads.clone(),
+        };
+        let file = TdmsFile::new(reader).unwrap();
+        (CachedTdmsFile::new(file, max_bytes), reads)
+    }
+
+    #[test]
+    fn cached_channels_are_served_without_reading() {
+        let (cache, reads) = cached_file(100);
+        let first = cache.get(CHANNELS[1]).unwrap();
+        assert_eq!(*first, ramp(DataTypeRaw::I32, 100, 10));
+        assert_eq!(cache.cached_bytes(), 40);
+
+        let read_before = reads.load(Ordering::SeqCst);
+        let second = cache.get(CHANNELS[1]).unwrap();
+        assert_eq!(reads.load(Ordering::SeqCst), read_before);
+        assert!(Arc::ptr_eq(&first, &second));
+    }
+
+    #[test]
+    fn least_recently_used_channels_are_evicted_first() {
+        let (cache, reads) = cached_file(100);
+        cache.get(CHANNELS[0]).unwrap();
+        cache.get(CHANNELS[1]).unwrap();
+        // Using a makes b the least recently used
+        cache.get(CHANNELS[0]).unwrap();
+        assert_eq!(cache.cached_channels(), [CHANNELS[1], CHANNELS[0]]);
+
+        let b = cache.get(CHANNELS[1]).unwrap();
+        cache.get(CHANNELS[2]).unwrap();
+        assert_eq!(cache.cached_channels(), [CHANNELS[1], CHANNELS[2]]);
+        assert_eq!(cache.cached_bytes(), 80);
+
+        // a was evicted so is read again, evicting b, whose data handed out stays valid
+        let read_before = reads.load(Ordering::SeqCst);
+        assert_eq!(
+            *cache.get(CHANNELS[0]).unwrap(),
+            ramp(DataTypeRaw::I32, 0, 10)
+        );
+        assert!(reads.load(Ordering::SeqCst) > read_before);
+        assert_eq!(cache.cached_channels(), [CHANNELS[2], CHANNELS[0]]);
+        assert_eq!(*b, ramp(DataTypeRaw::I32, 100, 10));
+    }
+
+    #[test]
+    fn channels_are_evicted_on_request() {
+        let (cache, _) = cached_file(200);
+        for channel in CHANNELS {
+            cache.get(channel).unwrap();
+        }
+        assert!(cache.evict(CHANNELS[1]));
+        assert!(!cache.evict(CHANNELS[1]));
+        assert_eq!(cache.cached_channels(), [CHANNELS[0], CHANNELS[2]]);
+        assert_eq!(cache.cached_bytes(), 80);
+
+        cache.clear();
+        assert!(cache.cached_channels().is_empty());
+        assert_eq!(cache.cached_bytes(), 0);
+    }
+
+    #[test]
+    fn channels_larger_than_the_cache_are_not_cached() {
+        let (cache, _) = cached_file(30);
+        let data = cache.get(CHANNELS[0]).unwrap();
+        assert_eq!(*data, ramp(DataTypeRaw::I32, 0, 10));
+        assert!(cache.cached_channels().is_empty());
+        assert_eq!(cache.cached_bytes(), 0);
+    }
+}
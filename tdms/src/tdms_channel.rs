@@ -0,0 +1,106 @@
+use std::fmt;
+use std::io::{Read, Seek};
+
+use indexmap::IndexMap;
+
+use crate::tdms_datatypes::{DataTypeRaw, DataTypeVec};
+use crate::tdms_error::Result;
+use crate::tdms_path::path_components;
+use crate::{ObjectMap, ObjectProperty, TdmsFile};
+
+/// A handle to a channel, carrying the raw object path alongside the group and channel names
+/// parsed from it. The raw path is what the file is indexed by, the names are what should be
+/// shown to users.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelHandle {
+    path: String,
+    group: Option<String>,
+    name: Option<String>,
+}
+
+/// Summary information about a channel, available without reading any data
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelInfo {
+    pub data_type: Option<DataTypeRaw>,
+    pub no_values: u64,
+    pub dimension: u32,
+    pub total_bytes: u64,
+    /// The alias set with `TdmsFile::set_alias`, to show in place of the path
+    pub alias: Option<String>,
+}
+
+/// A channel's values converted to f64, along with their unit. The unit is empty if the
+/// channel doesn't have one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quantity {
+    pub values: Vec<f64>,
+    pub unit: String,
+}
+
+impl ChannelHandle {
+    /// Create a handle from a raw object path e.g. `/'Group'/'Channel'`
+    pub fn new(path: &str) -> ChannelHandle {
+        let mut components = path_components(path).into_iter();
+        ChannelHandle {
+            path: path.to_string(),
+            group: components.next(),
+            name: components.next(),
+        }
+    }
+
+    /// The raw object path, as used to look the channel up in the file
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The group name, if the path has one
+    pub fn group(&self) -> Option<&str> {
+        self.group.as_deref()
+    }
+
+    /// The channel name, if the path has one
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Load the channel's data from the file
+    pub fn load<R: Read + Seek>(&self, file: &TdmsFile<R>) -> Result<DataTypeVec> {
+        file.load_data(&self.path)
+    }
+
+    /// The channel's properties, keyed by name
+    pub fn properties<'a, R: Read + Seek>(
+        &self,
+        file: &'a TdmsFile<R>,
+    ) -> Result<&'a IndexMap<String, ObjectProperty>> {
+        file.properties_of(self.object_map(file)?)
+    }
+
+    /// Summary information about the channel
+    pub fn info<R: Read + Seek>(&self, file: &TdmsFile<R>) -> Result<ChannelInfo> {
+        let object_map = self.object_map(file)?;
+        Ok(ChannelInfo {
+            data_type: object_map.last_object.raw_data_type,
+            no_values: object_map.total_values(),
+            dimension: object_map.dimension(),
+            total_bytes: object_map.total_bytes,
+            alias: file.alias(self.path.as_str()).map(str::to_string),
+        })
+    }
+
+    fn object_map<'a, R: Read + Seek>(&self, file: &'a TdmsFile<R>) -> Result<&'a ObjectMap> {
+        file.tdms_map.object_map(&self.path)
+    }
+}
+
+/// Displays as `Group / Channel`, with control characters such as newlines replaced by spaces
+impl fmt::Display for ChannelHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let clean = |name: &str| name.replace(|c: char| c.is_control(), " ");
+        match (&self.group, &self.name) {
+            (Some(group), Some(name)) => write!(f, "{} / {}", clean(group), clean(name)),
+            (Some(group), None) => write!(f, "{}", clean(group)),
+            _ => write!(f, "{}", clean(&self.path)),
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{BufWriter, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+
+use indexmap::IndexMap;
+use log::warn;
+
+use crate::tdms_datatypes::{read_pairs_vector, FormatOptions};
+use crate::tdms_error::{Result, TdmsError};
+use crate::tdms_path::path_components;
+use crate::{ObjectMap, ReadPair, TdmsFile, STREAM_BLOCK_VALUES};
+
+/// A channel's values formatted for a CSV column, read a block at a time as they're needed
+struct Column<'a> {
+    object_map: &'a ObjectMap,
+    pieces: Box<dyn Iterator<Item = ReadPair> + 'a>,
+    values: VecDeque<String>,
+}
+
+impl<'a> Column<'a> {
+    fn new(object_map: &'a ObjectMap) -> Column<'a> {
+        let value_size = object_map
+            .last_object
+            .raw_data_type
+            .and_then(|data_type| data_type.size().ok());
+        let pieces = object_map
+            .read_map
+            .iter()
+            .flat_map(ReadPair::chunks)
+            .flat_map(move |chunk| {
+                chunk
+                    .pieces(value_size, STREAM_BLOCK_VALUES)
+                    .collect::<Vec<ReadPair>>()
+            });
+        Column {
+            object_map,
+            pieces: Box::new(pieces),
+            values: VecDeque::new(),
+        }
+    }
+
+    /// Read blocks until there's a value to take, returns false once the channel is exhausted
+    fn fill<R: Read + Seek>(&mut self, reader: &mut R, lossy_strings: bool) -> Result<bool> {
+        while self.values.is_empty() {
+            let piece = match self.pieces.next() {
+                Some(piece) => piece,
+                None => return Ok(false),
+            };
+            let block = read_pairs_vector(
+                self.object_map,
+                std::slice::from_ref(&piece),
+                reader,
+                lossy_strings,
+            )?;
+            self.values
+                .extend(block.to_string_vec(&FormatOptions::default()));
+        }
+        Ok(true)
+    }
+}
+
+impl<R: Read + Seek> TdmsFile<R> {
+    /// Export each group's channels to a CSV file in dir, created if it doesn't exist, with a
+    /// header row of channel names then one row per value. Channels shorter than the longest
+    /// leave their remaining cells empty. Channels are streamed a block at a time rather than
+    /// loaded, so memory stays bounded however large the file. Files are named after their
+    /// group, with characters that aren't valid in file names replaced. Groups without data
+    /// are skipped, as are channels of types that can't be read. Returns the paths written.
+    pub fn export_all_csv(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+        if self.tdms_map.options.metadata_only {
+            return Err(TdmsError::MetadataOnly);
+        }
+        let mut groups: IndexMap<String, Vec<(String, &ObjectMap)>> = IndexMap::new();
+        for (path, object_map) in &self.tdms_map.all_objects {
+            if let [group, channel] = path_components(path).as_slice() {
+                if !object_map.read_map.is_empty() {
+                    let channels = groups.entry(group.clone()).or_default();
+                    channels.push((channel.clone(), object_map));
+                }
+            }
+        }
+
+        fs::create_dir_all(dir)?;
+        let lossy_strings = self.tdms_map.options.lossy_strings;
+        let mut reader = self.readers.take()?;
+        let mut written: Vec<PathBuf> = Vec::new();
+        for (group, channels) in groups {
+            let mut names = Vec::with_capacity(channels.len());
+            let mut columns = Vec::with_capacity(channels.len());
+            for (name, object_map) in channels {
+                let mut column = Column::new(object_map);
+                match column.fill(&mut *reader, lossy_strings) {
+                    Err(TdmsError::UnsupportedDataType(data_type)) => {
+                        warn!(
+                            "Skipping {}, {:?} data can't be exported",
+                            object_map.last_object.object_path, data_type
+                        );
+                        continue;
+                    }
+                    result => result?,
+                };
+                names.push(name);
+                columns.push(column);
+            }
+            if columns.is_empty() {
+                continue;
+            }
+
+            let out_path = csv_path(dir, &group, &written);
+            let mut writer = BufWriter::new(File::create(&out_path)?);
+            write_row(&mut writer, names.iter().map(String::as_str))?;
+            loop {
+                let mut row = Vec::with_capacity(columns.len());
+                for column in &mut columns {
+                    column.fill(&mut *reader, lossy_strings)?;
+                    row.push(column.values.pop_front());
+                }
+                if row.iter().all(Option::is_none) {
+                    break;
+                }
+                write_row(
+                    &mut writer,
+                    row.iter().map(|cell| cell.as_deref().unwrap_or("")),
+                )?;
+            }
+            writer.flush()?;
+            written.push(out_path);
+        }
+        Ok(written)
+    }
+}
+
+/// A path in dir for the group's CSV file, numbered if a group already written took the name
+fn csv_path(dir: &Path, group: &str, written: &[PathBuf]) -> PathBuf {
+    let stem = file_stem(group);
+    let mut path = dir.join(format!("{}.csv", stem));
+    let mut n = 2;
+    while written.contains(&path) {
+        path = dir.join(format!("{}_{}.csv", stem, n));
+        n += 1;
+    }
+    path
+}
+
+/// A group name with the characters that aren't valid in file names on common platforms
+/// replaced, `_` if nothing is left
+fn file_stem(name: &str) -> String {
+    let stem: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | '\'' | '"' | ':' | '*' | '?' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    match stem.trim_matches(|c: char| c == '.' || c.is_whitespace()) {
+        "" => "_".to_string(),
+        stem => stem.to_string(),
+    }
+}
+
+fn write_row<'a, W: Write>(writer: &mut W, cells: impl Iterator<Item = &'a str>) -> Result<()> {
+    for (i, cell) in cells.enumerate() {
+        if i > 0 {
+            writer.write_all(b",")?;
+        }
+        // Cells with separators, quotes or line breaks are quoted with quotes doubled
+        if cell.contains([',', '"', '\n', '\r']) {
+            write!(writer, "\"{}\"", cell.replace('"', "\"\""))?;
+        } else {
+            writer.write_all(cell.as_bytes())?;
+        }
+    }
+    writer.write_all(b"\r\n")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tdms_datatypes::{DataTypeRaw, DataTypeVec};
+    use crate::test_util::{ramp, SegmentBuilder, TdmsBuilder};
+    use std::io::Cursor;
+
+    #[test]
+    fn ragged_channels_export_with_empty_cells_and_quoted_strings() {
+        let notes = ["plain", "a,b", "say \"hi\"", "two\nlines"];
+        let bytes = TdmsBuilder::new()
+            .segment(
+                SegmentBuilder::new()
+                    .object("/")
+                    .object("/'rig'")
+                    .channel("/'rig'/'count'", ramp(DataTypeRaw::I32, 0, 3))
+                    .channel(
+                        "/'rig'/'notes'",
+                        DataTypeVec::TdmsString(notes.iter().map(|n| n.to_string()).collect()),
+                    )
+                    .channel("/'rig'/'volts'", DataTypeVec::Double(vec![0.5, -1.25]))
+                    .channel("/'a/b'/'on'", ramp(DataTypeRaw::Boolean, 0, 2)),
+            )
+            .build();
+        let tdms = TdmsFile::new(Cursor::new(bytes)).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+
+        let written = tdms.export_all_csv(&dir.path().join("out")).unwrap();
+        let rig = dir.path().join("out").join("rig.csv");
+        let ab = dir.path().join("out").join("a_b.csv");
+        assert_eq!(written, vec![rig.clone(), ab.clone()]);
+        assert_eq!(
+            fs::read_to_string(&rig).unwrap(),
+            "count,notes,volts\r\n\
+             0,plain,0.5\r\n\
+             1,\"a,b\",-1.25\r\n\
+             2,\"say \"\"hi\"\"\",\r\n\
+             ,\"two\nlines\",\r\n"
+        );
+        assert_eq!(fs::read_to_string(&ab).unwrap(), "on\r\nfalse\r\ntrue\r\n");
+    }
+}
@@ -1,15 +1,18 @@
+use std::collections::BTreeMap;
 use std::convert::TryFrom;
 use std::fmt;
-use std::io::{Read, Seek, SeekFrom};
+use std::io::{Cursor, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::str::FromStr;
 
 use crate::tdms_error::{Result, TdmsError};
 use crate::{ObjectMap, ReadPair};
 use byteorder::*;
+use chrono::{DateTime, Local, TimeZone, Utc};
 use log::debug;
 use num_derive::FromPrimitive;
 use num_enum::IntoPrimitive;
 
-#[derive(IntoPrimitive, Debug)]
+#[derive(IntoPrimitive, Clone, Copy, Debug)]
 #[repr(u32)]
 pub enum TocProperties {
     KTocMetaData = 1 << 1,        // segment contains meta data
@@ -20,7 +23,7 @@ pub enum TocProperties {
     KTocNewObjList = 1 << 2, // first segment, or order has changed (is not present when channel is added)
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TocMask {
     pub flags: u32,
 }
@@ -40,7 +43,7 @@ impl TocMask {
 
 /// The DataTypeRaw enum's values match the binary representation of that
 /// type in tdms files.
-#[derive(FromPrimitive, Clone, Copy, Debug)]
+#[derive(FromPrimitive, Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[repr(u32)]
 pub enum DataTypeRaw {
     Void = 0,
@@ -73,6 +76,55 @@ impl DataTypeRaw {
         num::FromPrimitive::from_u32(raw_id).ok_or(TdmsError::RawDataTypeNotFound)
     }
 
+    /// The type's name as Display gives it, its NI name as in the TDMS C API, and a short
+    /// NI style name
+    fn names(&self) -> (&'static str, &'static str, &'static str) {
+        match self {
+            DataTypeRaw::Void => ("Void", "tdsTypeVoid", "DT_VOID"),
+            DataTypeRaw::I8 => ("I8", "tdsTypeI8", "DT_I8"),
+            DataTypeRaw::I16 => ("I16", "tdsTypeI16", "DT_I16"),
+            DataTypeRaw::I32 => ("I32", "tdsTypeI32", "DT_I32"),
+            DataTypeRaw::I64 => ("I64", "tdsTypeI64", "DT_I64"),
+            DataTypeRaw::U8 => ("U8", "tdsTypeU8", "DT_U8"),
+            DataTypeRaw::U16 => ("U16", "tdsTypeU16", "DT_U16"),
+            DataTypeRaw::U32 => ("U32", "tdsTypeU32", "DT_U32"),
+            DataTypeRaw::U64 => ("U64", "tdsTypeU64", "DT_U64"),
+            DataTypeRaw::SingleFloat => ("SingleFloat", "tdsTypeSingleFloat", "DT_FLOAT"),
+            DataTypeRaw::DoubleFloat => ("DoubleFloat", "tdsTypeDoubleFloat", "DT_DOUBLE"),
+            DataTypeRaw::ExtendedFloat => ("ExtendedFloat", "tdsTypeExtendedFloat", "DT_EXTENDED"),
+            DataTypeRaw::SingleFloatWithUnit => (
+                "SingleFloatWithUnit",
+                "tdsTypeSingleFloatWithUnit",
+                "DT_FLOAT_WITH_UNIT",
+            ),
+            DataTypeRaw::DoubleFloatWithUnit => (
+                "DoubleFloatWithUnit",
+                "tdsTypeDoubleFloatWithUnit",
+                "DT_DOUBLE_WITH_UNIT",
+            ),
+            DataTypeRaw::ExtendedFloatWithUnit => (
+                "ExtendedFloatWithUnit",
+                "tdsTypeExtendedFloatWithUnit",
+                "DT_EXTENDED_WITH_UNIT",
+            ),
+            DataTypeRaw::TdmsString => ("TdmsString", "tdsTypeString", "DT_STRING"),
+            DataTypeRaw::Boolean => ("Boolean", "tdsTypeBoolean", "DT_BOOLEAN"),
+            DataTypeRaw::TimeStamp => ("TimeStamp", "tdsTypeTimeStamp", "DT_TIMESTAMP"),
+            DataTypeRaw::FixedPoint => ("FixedPoint", "tdsTypeFixedPoint", "DT_FIXED_POINT"),
+            DataTypeRaw::ComplexSingleFloat => (
+                "ComplexSingleFloat",
+                "tdsTypeComplexSingleFloat",
+                "DT_COMPLEX_FLOAT",
+            ),
+            DataTypeRaw::ComplexDoubleFloat => (
+                "ComplexDoubleFloat",
+                "tdsTypeComplexDoubleFloat",
+                "DT_COMPLEX_DOUBLE",
+            ),
+            DataTypeRaw::DAQmxRawData => ("DAQmxRawData", "tdsTypeDAQmxRawData", "DT_DAQMX"),
+        }
+    }
+
     /// Returns the size of the data type in bytes.    
     pub fn size(&self) -> Result<u64> {
         match self {
@@ -102,12 +154,84 @@ impl DataTypeRaw {
     }
 }
 
-#[derive(Debug, Clone, Default)]
+/// Displays as the variant name, e.g. `DoubleFloat`
+impl fmt::Display for DataTypeRaw {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.names().0)
+    }
+}
+
+/// Parses the variant name, the name of the type in NI's TDMS C API e.g.
+/// `tdsTypeDoubleFloat`, or an NI style short name e.g. `DT_DOUBLE`, ignoring case
+impl FromStr for DataTypeRaw {
+    type Err = TdmsError;
+
+    fn from_str(name: &str) -> Result<DataTypeRaw> {
+        let name = name.trim();
+        DATA_TYPES
+            .iter()
+            .copied()
+            .find(|data_type| {
+                let (variant, ni, short) = data_type.names();
+                [variant, ni, short]
+                    .iter()
+                    .any(|known| known.eq_ignore_ascii_case(name))
+            })
+            .ok_or_else(|| TdmsError::UnknownDataTypeName(name.to_string()))
+    }
+}
+
+/// Every data type, in order of their ids
+const DATA_TYPES: [DataTypeRaw; 22] = [
+    DataTypeRaw::Void,
+    DataTypeRaw::I8,
+    DataTypeRaw::I16,
+    DataTypeRaw::I32,
+    DataTypeRaw::I64,
+    DataTypeRaw::U8,
+    DataTypeRaw::U16,
+    DataTypeRaw::U32,
+    DataTypeRaw::U64,
+    DataTypeRaw::SingleFloat,
+    DataTypeRaw::DoubleFloat,
+    DataTypeRaw::ExtendedFloat,
+    DataTypeRaw::DoubleFloatWithUnit,
+    DataTypeRaw::ExtendedFloatWithUnit,
+    DataTypeRaw::SingleFloatWithUnit,
+    DataTypeRaw::TdmsString,
+    DataTypeRaw::Boolean,
+    DataTypeRaw::TimeStamp,
+    DataTypeRaw::FixedPoint,
+    DataTypeRaw::ComplexSingleFloat,
+    DataTypeRaw::ComplexDoubleFloat,
+    DataTypeRaw::DAQmxRawData,
+];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
 pub struct TimeStamp {
     pub epoch: i64,
     pub radix: u64,
 }
 
+/// Seconds between the LabVIEW epoch (1904-01-01 00:00:00 UTC) and the Unix epoch
+pub const LABVIEW_EPOCH_OFFSET: i64 = 2_082_844_800;
+
+impl TimeStamp {
+    /// Convert to a UTC date time. TDMS timestamps count seconds since 1904-01-01 00:00:00 UTC
+    /// with the radix holding fractions of a second in units of 2^-64 s. Returns None if the
+    /// time can't be represented.
+    pub fn to_utc(&self) -> Option<DateTime<Utc>> {
+        let nanos = ((self.radix as u128 * 1_000_000_000) >> 64) as u32;
+        let secs = self.epoch.checked_sub(LABVIEW_EPOCH_OFFSET)?;
+        Utc.timestamp_opt(secs, nanos).single()
+    }
+
+    /// Convert to a date time in the local timezone of the machine
+    pub fn to_local_time(&self) -> Option<DateTime<Local>> {
+        self.to_utc().map(|time| time.with_timezone(&Local))
+    }
+}
+
 impl fmt::Display for TimeStamp {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         writeln!(f, "{}\t{}", self.epoch, self.radix)?;
@@ -116,9 +240,63 @@ impl fmt::Display for TimeStamp {
     }
 }
 
+/// How `DataTypeVec::to_string_vec` formats values for text export
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FormatOptions {
+    pub(crate) float_precision: Option<usize>,
+    pub(crate) timestamp_format: Option<String>,
+}
+
+impl FormatOptions {
+    /// Floats in their shortest exact form and timestamps as RFC 3339 in UTC
+    pub fn new() -> FormatOptions {
+        FormatOptions::default()
+    }
+
+    /// Format floats with this many decimal places. If None they're written in the shortest
+    /// form that reads back as the same value.
+    pub fn float_precision(mut self, float_precision: Option<usize>) -> Self {
+        self.float_precision = float_precision;
+        self
+    }
+
+    /// Format timestamps in UTC with a chrono strftime format string, e.g. "%Y-%m-%d
+    /// %H:%M:%S%.6f". If None they're written as RFC 3339.
+    pub fn timestamp_format(mut self, timestamp_format: Option<String>) -> Self {
+        self.timestamp_format = timestamp_format;
+        self
+    }
+
+    fn float<T: fmt::Display>(&self, value: T) -> String {
+        match self.float_precision {
+            Some(precision) => format!("{:.*}", precision, value),
+            None => value.to_string(),
+        }
+    }
+
+    /// Timestamps that can't be represented as a date time are written as their raw epoch
+    /// and radix
+    fn timestamp(&self, value: &TimeStamp) -> String {
+        match (value.to_utc(), &self.timestamp_format) {
+            (Some(time), Some(format)) => time.format(format).to_string(),
+            (Some(time), None) => time.to_rfc3339(),
+            (None, _) => format!("{} {}", value.epoch, value.radix),
+        }
+    }
+}
+
+/// How `DataTypeVec::to_f64_cleaned` handles NaN and infinite values
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NonFinite {
+    /// Leave a gap, None
+    Gap,
+    /// Replace them with a sentinel value
+    Replace(f64),
+}
+
 /// A wrapper type for data types found in tdms files
 /// QUESTION: Is there a better way to allow for generic returns in "read_data" functions
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum DataType {
     Void(()),
     Boolean(bool),
@@ -141,8 +319,176 @@ pub enum DataType {
     // ComplexSingle(??)
     // CompledDouble(??)
     TimeStamp(TimeStamp),
+    /// A property of a type this crate doesn't know, kept as the bytes following its type id.
+    /// See `ReadOptions::keep_unknown_properties`.
+    Unknown {
+        type_id: u32,
+        bytes: Vec<u8>,
+    },
+}
+
+impl DataType {
+    /// The data type the value is stored as in a file, Void for unknown types
+    pub fn data_type(&self) -> DataTypeRaw {
+        match self {
+            DataType::Void(_) => DataTypeRaw::Void,
+            DataType::Boolean(_) => DataTypeRaw::Boolean,
+            DataType::I8(_) => DataTypeRaw::I8,
+            DataType::I16(_) => DataTypeRaw::I16,
+            DataType::I32(_) => DataTypeRaw::I32,
+            DataType::I64(_) => DataTypeRaw::I64,
+            DataType::U8(_) => DataTypeRaw::U8,
+            DataType::U16(_) => DataTypeRaw::U16,
+            DataType::U32(_) => DataTypeRaw::U32,
+            DataType::U64(_) => DataTypeRaw::U64,
+            DataType::Float(_) => DataTypeRaw::SingleFloat,
+            DataType::Double(_) => DataTypeRaw::DoubleFloat,
+            DataType::TdmsString(_) => DataTypeRaw::TdmsString,
+            DataType::TimeStamp(_) => DataTypeRaw::TimeStamp,
+            DataType::Unknown { .. } => DataTypeRaw::Void,
+        }
+    }
+
+    fn wrong_type(&self, expected: &'static str) -> TdmsError {
+        TdmsError::WrongPropertyType {
+            expected,
+            found: self.data_type(),
+        }
+    }
+}
+
+/// Displays the value, floats in their shortest exact form, strings quoted and escaped,
+/// timestamps as RFC 3339 in UTC
+impl fmt::Display for DataType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DataType::Void(_) => write!(f, "void"),
+            DataType::Boolean(value) => write!(f, "{}", value),
+            DataType::I8(value) => write!(f, "{}", value),
+            DataType::I16(value) => write!(f, "{}", value),
+            DataType::I32(value) => write!(f, "{}", value),
+            DataType::I64(value) => write!(f, "{}", value),
+            DataType::U8(value) => write!(f, "{}", value),
+            DataType::U16(value) => write!(f, "{}", value),
+            DataType::U32(value) => write!(f, "{}", value),
+            DataType::U64(value) => write!(f, "{}", value),
+            DataType::Float(value) => write!(f, "{}", value),
+            DataType::Double(value) => write!(f, "{}", value),
+            DataType::TdmsString(value) => write!(f, "{:?}", value),
+            DataType::TimeStamp(value) => f.write_str(&FormatOptions::default().timestamp(value)),
+            DataType::Unknown { type_id, bytes } => {
+                write!(f, "<type {:#x}, {} bytes>", type_id, bytes.len())
+            }
+        }
+    }
+}
+
+/// Floats and the integers an f64 holds exactly, 32 bits or narrower
+impl TryFrom<&DataType> for f64 {
+    type Error = TdmsError;
+
+    fn try_from(value: &DataType) -> Result<Self> {
+        match value {
+            DataType::I8(value) => Ok(*value as f64),
+            DataType::I16(value) => Ok(*value as f64),
+            DataType::I32(value) => Ok(*value as f64),
+            DataType::U8(value) => Ok(*value as f64),
+            DataType::U16(value) => Ok(*value as f64),
+            DataType::U32(value) => Ok(*value as f64),
+            DataType::Float(value) => Ok(*value as f64),
+            DataType::Double(value) => Ok(*value),
+            other => Err(other.wrong_type("f64")),
+        }
+    }
 }
 
+/// Signed integers and the unsigned integers narrower than 64 bits, never floats
+impl TryFrom<&DataType> for i64 {
+    type Error = TdmsError;
+
+    fn try_from(value: &DataType) -> Result<Self> {
+        match value {
+            DataType::I8(value) => Ok(*value as i64),
+            DataType::I16(value) => Ok(*value as i64),
+            DataType::I32(value) => Ok(*value as i64),
+            DataType::I64(value) => Ok(*value),
+            DataType::U8(value) => Ok(*value as i64),
+            DataType::U16(value) => Ok(*value as i64),
+            DataType::U32(value) => Ok(*value as i64),
+            other => Err(other.wrong_type("i64")),
+        }
+    }
+}
+
+/// Unsigned integers only
+impl TryFrom<&DataType> for u64 {
+    type Error = TdmsError;
+
+    fn try_from(value: &DataType) -> Result<Self> {
+        match value {
+            DataType::U8(value) => Ok(*value as u64),
+            DataType::U16(value) => Ok(*value as u64),
+            DataType::U32(value) => Ok(*value as u64),
+            DataType::U64(value) => Ok(*value),
+            other => Err(other.wrong_type("u64")),
+        }
+    }
+}
+
+impl TryFrom<&DataType> for bool {
+    type Error = TdmsError;
+
+    fn try_from(value: &DataType) -> Result<Self> {
+        match value {
+            DataType::Boolean(value) => Ok(*value),
+            other => Err(other.wrong_type("bool")),
+        }
+    }
+}
+
+impl TryFrom<&DataType> for String {
+    type Error = TdmsError;
+
+    fn try_from(value: &DataType) -> Result<Self> {
+        match value {
+            DataType::TdmsString(value) => Ok(value.clone()),
+            other => Err(other.wrong_type("String")),
+        }
+    }
+}
+
+impl TryFrom<&DataType> for TimeStamp {
+    type Error = TdmsError;
+
+    fn try_from(value: &DataType) -> Result<Self> {
+        match value {
+            DataType::TimeStamp(value) => Ok(value.clone()),
+            other => Err(other.wrong_type("TimeStamp")),
+        }
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// The types a property value can be extracted as with `TdmsObject::get_property_as`, see
+/// their `TryFrom<&DataType>` implementations for what each accepts
+pub trait PropertyValue: sealed::Sealed + for<'a> TryFrom<&'a DataType, Error = TdmsError> {}
+
+impl sealed::Sealed for f64 {}
+impl sealed::Sealed for i64 {}
+impl sealed::Sealed for u64 {}
+impl sealed::Sealed for bool {}
+impl sealed::Sealed for String {}
+impl sealed::Sealed for TimeStamp {}
+impl PropertyValue for f64 {}
+impl PropertyValue for i64 {}
+impl PropertyValue for u64 {}
+impl PropertyValue for bool {}
+impl PropertyValue for String {}
+impl PropertyValue for TimeStamp {}
+
 /// Helper function for reading a string from file.
 pub fn read_string<R: Read + Seek, O: ByteOrder>(reader: &mut R) -> Result<String> {
     let str_len = reader.read_u32::<O>()?;
@@ -152,6 +498,17 @@ pub fn read_string<R: Read + Seek, O: ByteOrder>(reader: &mut R) -> Result<Strin
     Ok(String::from_utf8(str_raw_buf)?)
 }
 
+/// Replace invalid UTF-8 in a string that failed to decode, if lossy decoding is wanted. The
+/// reader has already moved past the string so reading can carry on from there.
+pub fn lossy_string(result: Result<String>, lossy: bool) -> Result<String> {
+    match result {
+        Err(TdmsError::FromUtf8(e)) if lossy => {
+            Ok(String::from_utf8_lossy(e.as_bytes()).into_owned())
+        }
+        other => other,
+    }
+}
+
 /// Reads data into the DataType enum based on the value of DataTypeRaw.
 pub fn read_datatype<R: Read + Seek, O: ByteOrder>(
     reader: &mut R,
@@ -181,8 +538,42 @@ pub fn read_datatype<R: Read + Seek, O: ByteOrder>(
     Ok(dataout)
 }
 
-/// A wrapper type for vectors of data types found in tdms files
-#[derive(Debug, Clone)]
+/// Helper function for writing a length prefixed string, the inverse of read_string.
+pub fn write_string<W: Write, O: ByteOrder>(writer: &mut W, string: &str) -> Result<()> {
+    writer.write_u32::<O>(string.len() as u32)?;
+    writer.write_all(string.as_bytes())?;
+    Ok(())
+}
+
+/// Writes a DataType in the given byte order, the inverse of read_datatype.
+pub fn write_datatype<W: Write, O: ByteOrder>(writer: &mut W, data: &DataType) -> Result<()> {
+    match data {
+        DataType::Void(_) => {}
+        DataType::Boolean(value) => writer.write_u8(*value as u8)?,
+        DataType::I8(value) => writer.write_i8(*value)?,
+        DataType::I16(value) => writer.write_i16::<O>(*value)?,
+        DataType::I32(value) => writer.write_i32::<O>(*value)?,
+        DataType::I64(value) => writer.write_i64::<O>(*value)?,
+        DataType::U8(value) => writer.write_u8(*value)?,
+        DataType::U16(value) => writer.write_u16::<O>(*value)?,
+        DataType::U32(value) => writer.write_u32::<O>(*value)?,
+        DataType::U64(value) => writer.write_u64::<O>(*value)?,
+        DataType::Float(value) => writer.write_f32::<O>(*value)?,
+        DataType::Double(value) => writer.write_f64::<O>(*value)?,
+        DataType::TdmsString(value) => write_string::<W, O>(writer, value)?,
+        DataType::TimeStamp(value) => {
+            writer.write_i64::<O>(value.epoch)?;
+            writer.write_u64::<O>(value.radix)?;
+        }
+        DataType::Unknown { bytes, .. } => writer.write_all(bytes)?,
+    };
+
+    Ok(())
+}
+
+/// A wrapper type for vectors of data types found in tdms files. Equality compares the variant
+/// and contents exactly, see `approx_eq` for float data.
+#[derive(Debug, Clone, PartialEq)]
 pub enum DataTypeVec {
     Void(Vec<()>),
     Boolean(Vec<bool>),
@@ -207,18 +598,379 @@ pub enum DataTypeVec {
     TimeStamp(Vec<TimeStamp>),
 }
 
+impl DataTypeVec {
+    /// Returns the number of values in the vector
+    pub fn len(&self) -> usize {
+        match self {
+            DataTypeVec::Void(datavec) => datavec.len(),
+            DataTypeVec::Boolean(datavec) => datavec.len(),
+            DataTypeVec::I8(datavec) => datavec.len(),
+            DataTypeVec::I16(datavec) => datavec.len(),
+            DataTypeVec::I32(datavec) => datavec.len(),
+            DataTypeVec::I64(datavec) => datavec.len(),
+            DataTypeVec::U8(datavec) => datavec.len(),
+            DataTypeVec::U16(datavec) => datavec.len(),
+            DataTypeVec::U32(datavec) => datavec.len(),
+            DataTypeVec::U64(datavec) => datavec.len(),
+            DataTypeVec::Float(datavec) => datavec.len(),
+            DataTypeVec::Double(datavec) => datavec.len(),
+            DataTypeVec::TdmsString(datavec) => datavec.len(),
+            DataTypeVec::TimeStamp(datavec) => datavec.len(),
+        }
+    }
+
+    /// Returns true if the vector contains no values
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Extend the vector to len values with the type's default value, e.g. 0 or an empty
+    /// string. Does nothing if it already has len or more values.
+    pub fn pad(&mut self, len: usize) {
+        let len = len.max(self.len());
+        match self {
+            DataTypeVec::Void(datavec) => datavec.resize(len, ()),
+            DataTypeVec::Boolean(datavec) => datavec.resize(len, false),
+            DataTypeVec::I8(datavec) => datavec.resize(len, 0),
+            DataTypeVec::I16(datavec) => datavec.resize(len, 0),
+            DataTypeVec::I32(datavec) => datavec.resize(len, 0),
+            DataTypeVec::I64(datavec) => datavec.resize(len, 0),
+            DataTypeVec::U8(datavec) => datavec.resize(len, 0),
+            DataTypeVec::U16(datavec) => datavec.resize(len, 0),
+            DataTypeVec::U32(datavec) => datavec.resize(len, 0),
+            DataTypeVec::U64(datavec) => datavec.resize(len, 0),
+            DataTypeVec::Float(datavec) => datavec.resize(len, 0.0),
+            DataTypeVec::Double(datavec) => datavec.resize(len, 0.0),
+            DataTypeVec::TdmsString(datavec) => datavec.resize(len, String::new()),
+            DataTypeVec::TimeStamp(datavec) => datavec.resize(len, TimeStamp::default()),
+        }
+    }
+
+    /// Append other's values, e.g. a channel's data from segments mapped by a later refresh.
+    /// Fails with `ChannelDataTypeMismatch`, leaving the vector unchanged, if other holds a
+    /// different type.
+    pub fn extend(&mut self, other: DataTypeVec) -> Result<()> {
+        match (self, other) {
+            (DataTypeVec::Void(datavec), DataTypeVec::Void(other)) => datavec.extend(other),
+            (DataTypeVec::Boolean(datavec), DataTypeVec::Boolean(other)) => datavec.extend(other),
+            (DataTypeVec::I8(datavec), DataTypeVec::I8(other)) => datavec.extend(other),
+            (DataTypeVec::I16(datavec), DataTypeVec::I16(other)) => datavec.extend(other),
+            (DataTypeVec::I32(datavec), DataTypeVec::I32(other)) => datavec.extend(other),
+            (DataTypeVec::I64(datavec), DataTypeVec::I64(other)) => datavec.extend(other),
+            (DataTypeVec::U8(datavec), DataTypeVec::U8(other)) => datavec.extend(other),
+            (DataTypeVec::U16(datavec), DataTypeVec::U16(other)) => datavec.extend(other),
+            (DataTypeVec::U32(datavec), DataTypeVec::U32(other)) => datavec.extend(other),
+            (DataTypeVec::U64(datavec), DataTypeVec::U64(other)) => datavec.extend(other),
+            (DataTypeVec::Float(datavec), DataTypeVec::Float(other)) => datavec.extend(other),
+            (DataTypeVec::Double(datavec), DataTypeVec::Double(other)) => datavec.extend(other),
+            (DataTypeVec::TdmsString(datavec), DataTypeVec::TdmsString(other)) => {
+                datavec.extend(other)
+            }
+            (DataTypeVec::TimeStamp(datavec), DataTypeVec::TimeStamp(other)) => {
+                datavec.extend(other)
+            }
+            (datavec, other) => {
+                return Err(TdmsError::ChannelDataTypeMismatch {
+                    expected: datavec.data_type(),
+                    found: other.data_type(),
+                })
+            }
+        }
+        Ok(())
+    }
+
+    /// The data type the values are stored as in a file
+    pub fn data_type(&self) -> DataTypeRaw {
+        match self {
+            DataTypeVec::Void(_) => DataTypeRaw::Void,
+            DataTypeVec::Boolean(_) => DataTypeRaw::Boolean,
+            DataTypeVec::I8(_) => DataTypeRaw::I8,
+            DataTypeVec::I16(_) => DataTypeRaw::I16,
+            DataTypeVec::I32(_) => DataTypeRaw::I32,
+            DataTypeVec::I64(_) => DataTypeRaw::I64,
+            DataTypeVec::U8(_) => DataTypeRaw::U8,
+            DataTypeVec::U16(_) => DataTypeRaw::U16,
+            DataTypeVec::U32(_) => DataTypeRaw::U32,
+            DataTypeVec::U64(_) => DataTypeRaw::U64,
+            DataTypeVec::Float(_) => DataTypeRaw::SingleFloat,
+            DataTypeVec::Double(_) => DataTypeRaw::DoubleFloat,
+            DataTypeVec::TdmsString(_) => DataTypeRaw::TdmsString,
+            DataTypeVec::TimeStamp(_) => DataTypeRaw::TimeStamp,
+        }
+    }
+
+    /// The number of bytes the values take up as raw data, which a string channel's raw data
+    /// index records. Strings take a 4 byte end offset each followed by their UTF-8 bytes.
+    pub fn raw_size(&self) -> u64 {
+        match self {
+            DataTypeVec::TdmsString(datavec) => {
+                datavec.iter().map(|string| 4 + string.len() as u64).sum()
+            }
+            other => other.data_type().size().unwrap_or(0) * other.len() as u64,
+        }
+    }
+
+    /// Returns true if the values can be viewed as f64, i.e. integer, float and boolean data
+    pub fn is_numeric(&self) -> bool {
+        !matches!(
+            self,
+            DataTypeVec::Void(_) | DataTypeVec::TdmsString(_) | DataTypeVec::TimeStamp(_)
+        )
+    }
+
+    /// The value at index i as a scalar DataType, None if the index is out of range
+    pub fn get(&self, i: usize) -> Option<DataType> {
+        match self {
+            DataTypeVec::Void(datavec) => datavec.get(i).map(|_| DataType::Void(())),
+            DataTypeVec::Boolean(datavec) => datavec.get(i).copied().map(DataType::Boolean),
+            DataTypeVec::I8(datavec) => datavec.get(i).copied().map(DataType::I8),
+            DataTypeVec::I16(datavec) => datavec.get(i).copied().map(DataType::I16),
+            DataTypeVec::I32(datavec) => datavec.get(i).copied().map(DataType::I32),
+            DataTypeVec::I64(datavec) => datavec.get(i).copied().map(DataType::I64),
+            DataTypeVec::U8(datavec) => datavec.get(i).copied().map(DataType::U8),
+            DataTypeVec::U16(datavec) => datavec.get(i).copied().map(DataType::U16),
+            DataTypeVec::U32(datavec) => datavec.get(i).copied().map(DataType::U32),
+            DataTypeVec::U64(datavec) => datavec.get(i).copied().map(DataType::U64),
+            DataTypeVec::Float(datavec) => datavec.get(i).copied().map(DataType::Float),
+            DataTypeVec::Double(datavec) => datavec.get(i).copied().map(DataType::Double),
+            DataTypeVec::TdmsString(datavec) => datavec.get(i).cloned().map(DataType::TdmsString),
+            DataTypeVec::TimeStamp(datavec) => datavec.get(i).cloned().map(DataType::TimeStamp),
+        }
+    }
+
+    /// Lossy f64 view of the value at index i, booleans map to 1.0/0.0. Returns None if the
+    /// index is out of range or the data isn't numeric.
+    pub fn get_f64(&self, i: usize) -> Option<f64> {
+        match self {
+            DataTypeVec::Boolean(datavec) => datavec.get(i).map(|x| if *x { 1.0 } else { 0.0 }),
+            DataTypeVec::I8(datavec) => datavec.get(i).map(|x| *x as f64),
+            DataTypeVec::I16(datavec) => datavec.get(i).map(|x| *x as f64),
+            DataTypeVec::I32(datavec) => datavec.get(i).map(|x| *x as f64),
+            DataTypeVec::I64(datavec) => datavec.get(i).map(|x| *x as f64),
+            DataTypeVec::U8(datavec) => datavec.get(i).map(|x| *x as f64),
+            DataTypeVec::U16(datavec) => datavec.get(i).map(|x| *x as f64),
+            DataTypeVec::U32(datavec) => datavec.get(i).map(|x| *x as f64),
+            DataTypeVec::U64(datavec) => datavec.get(i).map(|x| *x as f64),
+            DataTypeVec::Float(datavec) => datavec.get(i).map(|x| *x as f64),
+            DataTypeVec::Double(datavec) => datavec.get(i).copied(),
+            DataTypeVec::Void(_) | DataTypeVec::TdmsString(_) | DataTypeVec::TimeStamp(_) => None,
+        }
+    }
+
+    /// Iterate over a lossy f64 view of the values, empty if the data isn't numeric
+    pub fn iter_f64(&self) -> impl Iterator<Item = f64> + '_ {
+        let len = if self.is_numeric() { self.len() } else { 0 };
+        (0..len).filter_map(move |i| self.get_f64(i))
+    }
+
+    /// A lossy f64 view of the values as iter_f64 gives, with NaN and infinite values handled
+    /// as non_finite says, e.g. for plotting which can't draw them. Empty if the data isn't
+    /// numeric. The other conversions keep such values as they are.
+    pub fn to_f64_cleaned(&self, non_finite: NonFinite) -> Vec<Option<f64>> {
+        self.iter_f64()
+            .map(|x| match non_finite {
+                _ if x.is_finite() => Some(x),
+                NonFinite::Gap => None,
+                NonFinite::Replace(sentinel) => Some(sentinel),
+            })
+            .collect()
+    }
+
+    /// Iterate over the values of a string vector, empty for any other data type
+    pub fn iter_strings(&self) -> impl Iterator<Item = &str> {
+        let strings: &[String] = match self {
+            DataTypeVec::TdmsString(datavec) => datavec,
+            _ => &[],
+        };
+        strings.iter().map(String::as_str)
+    }
+
+    /// Borrow the values of a Boolean vector, None for any other variant. These accessors
+    /// are the zero copy counterpart to the TryFrom conversions.
+    pub fn as_bool_slice(&self) -> Option<&[bool]> {
+        match self {
+            DataTypeVec::Boolean(datavec) => Some(datavec),
+            _ => None,
+        }
+    }
+
+    /// Borrow the values of an I8 vector, None for any other variant
+    pub fn as_i8_slice(&self) -> Option<&[i8]> {
+        match self {
+            DataTypeVec::I8(datavec) => Some(datavec),
+            _ => None,
+        }
+    }
+
+    /// Borrow the values of an I16 vector, None for any other variant
+    pub fn as_i16_slice(&self) -> Option<&[i16]> {
+        match self {
+            DataTypeVec::I16(datavec) => Some(datavec),
+            _ => None,
+        }
+    }
+
+    /// Borrow the values of an I32 vector, None for any other variant
+    pub fn as_i32_slice(&self) -> Option<&[i32]> {
+        match self {
+            DataTypeVec::I32(datavec) => Some(datavec),
+            _ => None,
+        }
+    }
+
+    /// Borrow the values of an I64 vector, None for any other variant
+    pub fn as_i64_slice(&self) -> Option<&[i64]> {
+        match self {
+            DataTypeVec::I64(datavec) => Some(datavec),
+            _ => None,
+        }
+    }
+
+    /// Borrow the values of a U8 vector, None for any other variant
+    pub fn as_u8_slice(&self) -> Option<&[u8]> {
+        match self {
+            DataTypeVec::U8(datavec) => Some(datavec),
+            _ => None,
+        }
+    }
+
+    /// Borrow the values of a U16 vector, None for any other variant
+    pub fn as_u16_slice(&self) -> Option<&[u16]> {
+        match self {
+            DataTypeVec::U16(datavec) => Some(datavec),
+            _ => None,
+        }
+    }
+
+    /// Borrow the values of a U32 vector, None for any other variant
+    pub fn as_u32_slice(&self) -> Option<&[u32]> {
+        match self {
+            DataTypeVec::U32(datavec) => Some(datavec),
+            _ => None,
+        }
+    }
+
+    /// Borrow the values of a U64 vector, None for any other variant
+    pub fn as_u64_slice(&self) -> Option<&[u64]> {
+        match self {
+            DataTypeVec::U64(datavec) => Some(datavec),
+            _ => None,
+        }
+    }
+
+    /// Borrow the values of a Float vector, None for any other variant
+    pub fn as_f32_slice(&self) -> Option<&[f32]> {
+        match self {
+            DataTypeVec::Float(datavec) => Some(datavec),
+            _ => None,
+        }
+    }
+
+    /// Borrow the values of a Double vector, None for any other variant
+    pub fn as_f64_slice(&self) -> Option<&[f64]> {
+        match self {
+            DataTypeVec::Double(datavec) => Some(datavec),
+            _ => None,
+        }
+    }
+
+    /// Borrow the values of a TdmsString vector, None for any other variant
+    pub fn as_string_slice(&self) -> Option<&[String]> {
+        match self {
+            DataTypeVec::TdmsString(datavec) => Some(datavec),
+            _ => None,
+        }
+    }
+
+    /// Borrow the values of a TimeStamp vector, None for any other variant
+    pub fn as_timestamp_slice(&self) -> Option<&[TimeStamp]> {
+        match self {
+            DataTypeVec::TimeStamp(datavec) => Some(datavec),
+            _ => None,
+        }
+    }
+
+    /// Format each value as a string for text export, e.g. CSV. Floats and timestamps are
+    /// formatted as options say, booleans as true or false and strings as they are. Void
+    /// values are empty strings.
+    pub fn to_string_vec(&self, options: &FormatOptions) -> Vec<String> {
+        fn display<T: ToString>(datavec: &[T]) -> Vec<String> {
+            datavec.iter().map(T::to_string).collect()
+        }
+        match self {
+            DataTypeVec::Void(datavec) => vec![String::new(); datavec.len()],
+            DataTypeVec::Boolean(datavec) => display(datavec),
+            DataTypeVec::I8(datavec) => display(datavec),
+            DataTypeVec::I16(datavec) => display(datavec),
+            DataTypeVec::I32(datavec) => display(datavec),
+            DataTypeVec::I64(datavec) => display(datavec),
+            DataTypeVec::U8(datavec) => display(datavec),
+            DataTypeVec::U16(datavec) => display(datavec),
+            DataTypeVec::U32(datavec) => display(datavec),
+            DataTypeVec::U64(datavec) => display(datavec),
+            DataTypeVec::Float(datavec) => datavec.iter().map(|x| options.float(*x)).collect(),
+            DataTypeVec::Double(datavec) => datavec.iter().map(|x| options.float(*x)).collect(),
+            DataTypeVec::TdmsString(datavec) => datavec.clone(),
+            DataTypeVec::TimeStamp(datavec) => {
+                datavec.iter().map(|x| options.timestamp(x)).collect()
+            }
+        }
+    }
+
+    /// Compare with another vector allowing float values to differ by up to epsilon. NaN is
+    /// equal to NaN in the same position. Variants must match, and non float variants are
+    /// compared exactly.
+    pub fn approx_eq(&self, other: &DataTypeVec, epsilon: f64) -> bool {
+        match (self, other) {
+            (DataTypeVec::Float(a), DataTypeVec::Float(b)) => approx_eq_values(
+                a.iter().map(|&x| x as f64),
+                b.iter().map(|&x| x as f64),
+                epsilon,
+            ),
+            (DataTypeVec::Double(a), DataTypeVec::Double(b)) => {
+                approx_eq_values(a.iter().copied(), b.iter().copied(), epsilon)
+            }
+            _ => self == other,
+        }
+    }
+}
+
+fn approx_eq_values(
+    a: impl ExactSizeIterator<Item = f64>,
+    b: impl ExactSizeIterator<Item = f64>,
+    epsilon: f64,
+) -> bool {
+    a.len() == b.len()
+        && a.zip(b).all(|(a, b)| {
+            // Checking a == b first lets matching infinities through
+            (a.is_nan() && b.is_nan()) || a == b || (a - b).abs() <= epsilon
+        })
+}
+
 /// Defines functionality required to read and construct a vector of Tdms
 /// data types
 trait TdmsVector: Sized + Clone + Default {
     fn read<R: Read + Seek, O: ByteOrder>(buffer: &mut [Self], reader: &mut R) -> Result<()>;
 
+    fn write<W: Write, O: ByteOrder>(buffer: &[Self], writer: &mut W) -> Result<()>;
+
     fn make_vec(v: Vec<Self>) -> DataTypeVec;
 }
 
 impl TdmsVector for bool {
     fn read<R: Read + Seek, O: ByteOrder>(buffer: &mut [Self], reader: &mut R) -> Result<()> {
-        for item in buffer.iter_mut() {
-            *item = !matches!(reader.read_u8()?, 0);
+        // Read all the bytes at once rather than a read call per value
+        let mut bytes = vec![0u8; buffer.len()];
+        reader.read_exact(&mut bytes)?;
+        for (item, byte) in buffer.iter_mut().zip(bytes) {
+            *item = byte != 0;
+        }
+        Ok(())
+    }
+
+    fn write<W: Write, O: ByteOrder>(buffer: &[Self], writer: &mut W) -> Result<()> {
+        for item in buffer {
+            writer.write_u8(*item as u8)?;
         }
         Ok(())
     }
@@ -234,6 +986,13 @@ impl TdmsVector for i8 {
         Ok(())
     }
 
+    fn write<W: Write, O: ByteOrder>(buffer: &[Self], writer: &mut W) -> Result<()> {
+        for item in buffer {
+            writer.write_i8(*item)?;
+        }
+        Ok(())
+    }
+
     fn make_vec(datavec: Vec<Self>) -> DataTypeVec {
         DataTypeVec::I8(datavec)
     }
@@ -245,6 +1004,13 @@ impl TdmsVector for i16 {
         Ok(())
     }
 
+    fn write<W: Write, O: ByteOrder>(buffer: &[Self], writer: &mut W) -> Result<()> {
+        for item in buffer {
+            writer.write_i16::<O>(*item)?;
+        }
+        Ok(())
+    }
+
     fn make_vec(datavec: Vec<Self>) -> DataTypeVec {
         DataTypeVec::I16(datavec)
     }
@@ -256,6 +1022,13 @@ impl TdmsVector for i32 {
         Ok(())
     }
 
+    fn write<W: Write, O: ByteOrder>(buffer: &[Self], writer: &mut W) -> Result<()> {
+        for item in buffer {
+            writer.write_i32::<O>(*item)?;
+        }
+        Ok(())
+    }
+
     fn make_vec(datavec: Vec<Self>) -> DataTypeVec {
         DataTypeVec::I32(datavec)
     }
@@ -267,6 +1040,13 @@ impl TdmsVector for i64 {
         Ok(())
     }
 
+    fn write<W: Write, O: ByteOrder>(buffer: &[Self], writer: &mut W) -> Result<()> {
+        for item in buffer {
+            writer.write_i64::<O>(*item)?;
+        }
+        Ok(())
+    }
+
     fn make_vec(datavec: Vec<Self>) -> DataTypeVec {
         DataTypeVec::I64(datavec)
     }
@@ -278,6 +1058,11 @@ impl TdmsVector for u8 {
         Ok(())
     }
 
+    fn write<W: Write, O: ByteOrder>(buffer: &[Self], writer: &mut W) -> Result<()> {
+        writer.write_all(buffer)?;
+        Ok(())
+    }
+
     fn make_vec(datavec: Vec<Self>) -> DataTypeVec {
         DataTypeVec::U8(datavec)
     }
@@ -289,6 +1074,13 @@ impl TdmsVector for u16 {
         Ok(())
     }
 
+    fn write<W: Write, O: ByteOrder>(buffer: &[Self], writer: &mut W) -> Result<()> {
+        for item in buffer {
+            writer.write_u16::<O>(*item)?;
+        }
+        Ok(())
+    }
+
     fn make_vec(datavec: Vec<Self>) -> DataTypeVec {
         DataTypeVec::U16(datavec)
     }
@@ -300,6 +1092,13 @@ impl TdmsVector for u32 {
         Ok(())
     }
 
+    fn write<W: Write, O: ByteOrder>(buffer: &[Self], writer: &mut W) -> Result<()> {
+        for item in buffer {
+            writer.write_u32::<O>(*item)?;
+        }
+        Ok(())
+    }
+
     fn make_vec(datavec: Vec<Self>) -> DataTypeVec {
         DataTypeVec::U32(datavec)
     }
@@ -311,6 +1110,13 @@ impl TdmsVector for u64 {
         Ok(())
     }
 
+    fn write<W: Write, O: ByteOrder>(buffer: &[Self], writer: &mut W) -> Result<()> {
+        for item in buffer {
+            writer.write_u64::<O>(*item)?;
+        }
+        Ok(())
+    }
+
     fn make_vec(datavec: Vec<Self>) -> DataTypeVec {
         DataTypeVec::U64(datavec)
     }
@@ -322,6 +1128,13 @@ impl TdmsVector for f32 {
         Ok(())
     }
 
+    fn write<W: Write, O: ByteOrder>(buffer: &[Self], writer: &mut W) -> Result<()> {
+        for item in buffer {
+            writer.write_f32::<O>(*item)?;
+        }
+        Ok(())
+    }
+
     fn make_vec(datavec: Vec<Self>) -> DataTypeVec {
         DataTypeVec::Float(datavec)
     }
@@ -333,26 +1146,76 @@ impl TdmsVector for f64 {
         Ok(())
     }
 
+    fn write<W: Write, O: ByteOrder>(buffer: &[Self], writer: &mut W) -> Result<()> {
+        for item in buffer {
+            writer.write_f64::<O>(*item)?;
+        }
+        Ok(())
+    }
+
     fn make_vec(datavec: Vec<Self>) -> DataTypeVec {
         DataTypeVec::Double(datavec)
     }
 }
 
+/// String data for a chunk is a table of u32 offsets, one per string, marking the end of
+/// each string relative to the end of the table, followed by the concatenated strings. The
+/// buffer must therefore cover exactly one chunk.
+fn read_strings<'a, R: Read + Seek, O: ByteOrder>(
+    buffer: impl ExactSizeIterator<Item = &'a mut String>,
+    reader: &mut R,
+    lossy: bool,
+) -> Result<()> {
+    let mut offsets = vec![0u32; buffer.len()];
+    reader.read_u32_into::<O>(&mut offsets)?;
+
+    let mut previous_offset = 0;
+    for (item, offset) in buffer.zip(offsets) {
+        // Offsets never decrease, a zero length string repeats the previous offset
+        let str_len = offset
+            .checked_sub(previous_offset)
+            .ok_or(TdmsError::MalformedSegment)?;
+        let mut str_raw_buf = vec![0u8; str_len as usize];
+        reader.read_exact(&mut str_raw_buf)?;
+        *item = lossy_string(
+            String::from_utf8(str_raw_buf).map_err(TdmsError::from),
+            lossy,
+        )?;
+        previous_offset = offset;
+    }
+    Ok(())
+}
+
+/// The offsets table for a chunk of strings of the given lengths in bytes. Fails with
+/// `StringChunkTooLarge` if they total more than a u32 offset can mark.
+fn string_offsets(lengths: &[usize]) -> Result<Vec<u32>> {
+    let mut offset: u32 = 0;
+    let mut offsets = Vec::with_capacity(lengths.len());
+    for &len in lengths {
+        offset = u32::try_from(len)
+            .ok()
+            .and_then(|len| offset.checked_add(len))
+            .ok_or_else(|| TdmsError::StringChunkTooLarge {
+                bytes: lengths.iter().map(|&len| len as u64).sum(),
+            })?;
+        offsets.push(offset);
+    }
+    Ok(offsets)
+}
+
 impl TdmsVector for String {
     fn read<R: Read + Seek, O: ByteOrder>(buffer: &mut [Self], reader: &mut R) -> Result<()> {
-        let mut string_lengths: Vec<u32> = Vec::new();
-        for _ in 0..buffer.len() {
-            string_lengths.push(reader.read_u32::<O>()?);
-        }
+        read_strings::<R, O>(buffer.iter_mut(), reader, false)
+    }
 
-        for i in 0..buffer.len() {
-            let mut str_raw_buf = if i == 0 {
-                vec![0u8; string_lengths[i] as usize]
-            } else {
-                vec![0u8; (string_lengths[i] - string_lengths[i - 1]) as usize]
-            };
-            reader.read_exact(&mut str_raw_buf)?;
-            buffer[i] = String::from_utf8(str_raw_buf)?;
+    fn write<W: Write, O: ByteOrder>(buffer: &[Self], writer: &mut W) -> Result<()> {
+        // Offsets table marking the end of each string, followed by the concatenated strings
+        let lengths: Vec<usize> = buffer.iter().map(String::len).collect();
+        for offset in string_offsets(&lengths)? {
+            writer.write_u32::<O>(offset)?;
+        }
+        for item in buffer {
+            writer.write_all(item.as_bytes())?;
         }
         Ok(())
     }
@@ -362,6 +1225,25 @@ impl TdmsVector for String {
     }
 }
 
+/// A string read with invalid UTF-8 replaced rather than rejected, only used while reading
+#[derive(Debug, Clone, Default)]
+struct LossyString(String);
+
+impl TdmsVector for LossyString {
+    fn read<R: Read + Seek, O: ByteOrder>(buffer: &mut [Self], reader: &mut R) -> Result<()> {
+        read_strings::<R, O>(buffer.iter_mut().map(|item| &mut item.0), reader, true)
+    }
+
+    fn write<W: Write, O: ByteOrder>(buffer: &[Self], writer: &mut W) -> Result<()> {
+        let strings: Vec<String> = buffer.iter().map(|item| item.0.clone()).collect();
+        String::write::<W, O>(&strings, writer)
+    }
+
+    fn make_vec(datavec: Vec<Self>) -> DataTypeVec {
+        DataTypeVec::TdmsString(datavec.into_iter().map(|item| item.0).collect())
+    }
+}
+
 impl TdmsVector for TimeStamp {
     fn read<R: Read + Seek, O: ByteOrder>(buffer: &mut [Self], reader: &mut R) -> Result<()> {
         for item in buffer.iter_mut() {
@@ -372,82 +1254,415 @@ impl TdmsVector for TimeStamp {
         Ok(())
     }
 
+    fn write<W: Write, O: ByteOrder>(buffer: &[Self], writer: &mut W) -> Result<()> {
+        for item in buffer {
+            writer.write_i64::<O>(item.epoch)?;
+            writer.write_u64::<O>(item.radix)?;
+        }
+        Ok(())
+    }
+
     fn make_vec(datavec: Vec<Self>) -> DataTypeVec {
         DataTypeVec::TimeStamp(datavec)
     }
 }
 
+/// Interleaved values are read ahead in blocks of up to this many bytes
+const READ_AHEAD_BYTES: u64 = 4 * 1024 * 1024;
+
+/// The size of each of a pair's values, for pairs of fixed size values
+fn value_size(pair: &ReadPair) -> Option<u64> {
+    pair.no_bytes
+        .checked_div(pair.no_values)
+        .filter(|&size| size > 0 && size * pair.no_values == pair.no_bytes)
+}
+
+/// Gather the bytes of no_values interleaved values, stride bytes apart, into contiguous form.
+/// Rather than a read and a seek per value, blocks of values and the bytes between them are
+/// read in one go, which matters on readers where each call is slow e.g. network filesystems.
+fn gather_interleaved<R: Read + Seek>(
+    reader: &mut R,
+    value_size: u64,
+    stride: u64,
+    no_values: u64,
+) -> Result<Vec<u8>> {
+    let row = value_size + stride;
+    let rows_per_block = (READ_AHEAD_BYTES / row).max(1);
+    let mut gathered = Vec::with_capacity((value_size * no_values) as usize);
+    let mut block = Vec::new();
+
+    let mut remaining = no_values;
+    while remaining > 0 {
+        let rows = remaining.min(rows_per_block);
+        // The bytes after a block's last value belong to other channels, skip rather than read
+        // them as they may run past the end of the file
+        block.resize((rows * row - stride) as usize, 0);
+        reader.read_exact(&mut block)?;
+        for value in block.chunks(row as usize) {
+            gathered.extend_from_slice(&value[..value_size as usize]);
+        }
+        remaining -= rows;
+        if remaining > 0 {
+            reader.seek(SeekFrom::Current(stride as i64))?;
+        }
+    }
+    Ok(gathered)
+}
+
+/// Gather the values of several objects' pairs in the same interleaved chunk, reading each
+/// block of rows once for all of them rather than once per object. Returns None if the pairs
+/// don't share a row layout, in which case they need reading separately.
+fn gather_shared<R: Read + Seek>(
+    reader: &mut R,
+    pairs: &[&ReadPair],
+) -> Option<Result<Vec<Vec<u8>>>> {
+    let sizes: Vec<u64> = pairs
+        .iter()
+        .map(|pair| value_size(pair))
+        .collect::<Option<_>>()?;
+    let row = sizes[0] + pairs[0].stride?;
+    let no_values = pairs[0].no_values;
+    let base = pairs.iter().map(|pair| pair.start_index).min()?;
+    // The bytes of a row up to the end of the last value being read from it
+    let tail = pairs
+        .iter()
+        .zip(&sizes)
+        .map(|(pair, size)| pair.start_index - base + size)
+        .max()?;
+    let same_layout = pairs.iter().zip(&sizes).all(|(pair, size)| {
+        pair.no_values == no_values && pair.stride.map(|stride| size + stride) == Some(row)
+    });
+    if !same_layout || tail > row {
+        return None;
+    }
+
+    let mut gather = || -> Result<Vec<Vec<u8>>> {
+        let rows_per_block = (READ_AHEAD_BYTES / row).max(1);
+        let mut gathered: Vec<Vec<u8>> = sizes
+            .iter()
+            .map(|size| Vec::with_capacity((size * no_values) as usize))
+            .collect();
+        let mut block = Vec::new();
+
+        reader.seek(SeekFrom::Start(base))?;
+        let mut remaining = no_values;
+        while remaining > 0 {
+            let rows = remaining.min(rows_per_block);
+            block.resize(((rows - 1) * row + tail) as usize, 0);
+            reader.read_exact(&mut block)?;
+            for ((pair, size), values) in pairs.iter().zip(&sizes).zip(&mut gathered) {
+                let offset = (pair.start_index - base) as usize;
+                for value in block[offset..].chunks(row as usize) {
+                    values.extend_from_slice(&value[..*size as usize]);
+                }
+            }
+            remaining -= rows;
+            if remaining > 0 {
+                reader.seek(SeekFrom::Current((row - tail) as i64))?;
+            }
+        }
+        Ok(gathered)
+    };
+    Some(gather())
+}
+
+/// Read the values described by a single read pair into the provided buffer
+fn read_pair<T: TdmsVector, R: Read + Seek, O: ByteOrder>(
+    buffer: &mut [T],
+    pair: &ReadPair,
+    reader: &mut R,
+) -> Result<()> {
+    reader.seek(SeekFrom::Start(pair.start_index))?;
+    if let (true, Some(value_size)) = (pair.interleaved, value_size(pair)) {
+        let stride = pair.stride.unwrap();
+        let gathered = gather_interleaved(reader, value_size, stride, pair.no_values)?;
+        T::read::<_, O>(buffer, &mut Cursor::new(gathered))?;
+    } else if pair.interleaved {
+        for j in 0..buffer.len() {
+            // Other channels' values lie between this channel's, but not after its last
+            if j > 0 {
+                reader.seek(SeekFrom::Current(pair.stride.unwrap() as i64))?;
+            }
+            // exclusive range, to make sure compiler sees slice datatype
+            T::read::<R, O>(&mut buffer[j..j + 1], reader)?;
+        }
+    } else {
+        T::read::<R, O>(buffer, reader)?;
+    }
+    Ok(())
+}
+
+/// The length of a vector holding no_values values of type T, failing with
+/// `ChannelTooLargeForPlatform` if that many can't be allocated on this platform, e.g. a
+/// channel of billions of values on a 32 bit target
+pub(crate) fn vec_len<T>(path: &str, no_values: u64) -> Result<usize> {
+    let bytes = no_values.saturating_mul(std::mem::size_of::<T>() as u64);
+    match usize::try_from(no_values) {
+        Ok(len) if bytes <= isize::MAX as u64 => Ok(len),
+        _ => Err(TdmsError::ChannelTooLargeForPlatform {
+            path: path.to_string(),
+            bytes,
+        }),
+    }
+}
+
+/// Read the bytes described by a single read pair exactly as stored, gathering only this
+/// channel's values from interleaved data
+pub(crate) fn read_raw_pair<R: Read + Seek>(
+    path: &str,
+    pair: &ReadPair,
+    reader: &mut R,
+) -> Result<Vec<u8>> {
+    let no_bytes = vec_len::<u8>(path, pair.no_bytes)?;
+    let result = reader
+        .seek(SeekFrom::Start(pair.start_index))
+        .map_err(TdmsError::from)
+        .and_then(|_| {
+            if pair.interleaved {
+                let value_size = value_size(pair).ok_or(TdmsError::MalformedSegment)?;
+                gather_interleaved(reader, value_size, pair.stride.unwrap(), pair.no_values)
+            } else {
+                let mut buffer = vec![0u8; no_bytes];
+                reader.read_exact(&mut buffer)?;
+                Ok(buffer)
+            }
+        });
+    check_bounds(result, path, pair)
+}
+
+/// Running out of file means the index points past the data, report which pair did it. Any
+/// other failure is reported with the object and segment being read.
+fn check_bounds<T>(result: Result<T>, path: &str, pair: &ReadPair) -> Result<T> {
+    match result {
+        Err(TdmsError::Io(e)) if e.kind() == ErrorKind::UnexpectedEof => {
+            Err(TdmsError::ReadPairOutOfBounds {
+                path: path.to_string(),
+                start_index: pair.start_index,
+                no_values: pair.no_values,
+            })
+        }
+        Err(e) => Err(e.with_context(Some(path), Some(pair.segment_index), pair.start_index)),
+        other => other,
+    }
+}
+
 /// A generic function for reading different data types into a DataTypeVec enum
-/// dispatches to implementations according to type
-fn read_into_vec<T: TdmsVector, R: Read + Seek, O: ByteOrder>(
+/// dispatches to implementations according to type. Byte order is decided per read pair
+/// as endianness is a segment level property.
+fn read_into_vec<T: TdmsVector, R: Read + Seek>(
     reader: &mut R,
+    path: &str,
     read_pairs: &[ReadPair],
-    total_values: usize,
+    total_values: u64,
 ) -> Result<DataTypeVec> {
-    let mut datavec: Vec<T> = vec![T::default(); total_values];
+    let mut datavec: Vec<T> = vec![T::default(); vec_len::<T>(path, total_values)?];
     let mut i: usize = 0; // dummy variable to track values for indexing
 
-    for pair in read_pairs {
-        reader.seek(SeekFrom::Start(pair.start_index))?;
-        let no_values = pair.no_values as usize; // Maybe suspect for the interleaved comp
-        if pair.interleaved {
-            for j in 0..no_values {
-                // exclusive range, to make sure compiler sees slice datatype
-                T::read::<R, O>(&mut datavec[i + j..i + j + 1], reader)?;
-                reader.seek(SeekFrom::Current(pair.stride.unwrap() as i64))?;
-            }
+    for pair in read_pairs.iter().flat_map(ReadPair::chunks) {
+        // Fits as the values of all the pairs do
+        let no_values = pair.no_values as usize;
+        let buffer = &mut datavec[i..i + no_values];
+        let result = if pair.bigendian {
+            read_pair::<T, R, BE>(buffer, &pair, reader)
         } else {
-            T::read::<R, O>(&mut datavec[i..i + no_values], reader)?;
-        }
+            read_pair::<T, R, LE>(buffer, &pair, reader)
+        };
+        check_bounds(result, path, &pair)?;
         i += no_values;
     }
     Ok(T::make_vec(datavec))
 }
 
 /// Read a vector of a given tdms data type associated with an object,
-///  depending on the raw data type recorded for that object
-pub fn read_data_vector<R: Read + Seek, O: ByteOrder>(
+///  depending on the raw data type recorded for that object. If lossy_strings is set invalid
+/// UTF-8 in string data is replaced rather than returning an error.
+pub fn read_data_vector<R: Read + Seek>(
     object_map: &ObjectMap,
     reader: &mut R,
+    lossy_strings: bool,
+) -> Result<DataTypeVec> {
+    read_pairs_vector(object_map, &object_map.read_map, reader, lossy_strings)
+}
+
+/// Read the values of just the given read pairs of an object, which must come from its read
+/// map, e.g. those of a single segment
+pub(crate) fn read_pairs_vector<R: Read + Seek>(
+    object_map: &ObjectMap,
+    read_pairs: &[ReadPair],
+    reader: &mut R,
+    lossy_strings: bool,
 ) -> Result<DataTypeVec> {
-    let read_pairs = &object_map.read_map;
     let rawtype = &object_map
         .last_object
         .raw_data_type
         .ok_or(TdmsError::ObjectHasNoRawData)?;
-    let total_values = object_map.total_values;
+    let path = &object_map.last_object.object_path;
+    let total_values = read_pairs.iter().fold(0u64, |total, pair| {
+        total.saturating_add(pair.no_values.saturating_mul(pair.no_chunks))
+    });
     debug!("Map total values: {}", total_values);
 
     let datavec: DataTypeVec = match rawtype {
         DataTypeRaw::Void => DataTypeVec::Void(Vec::new()),
-        DataTypeRaw::I8 => read_into_vec::<i8, R, O>(reader, read_pairs, total_values)?,
-        DataTypeRaw::I16 => read_into_vec::<i16, R, O>(reader, read_pairs, total_values)?,
-        DataTypeRaw::I32 => read_into_vec::<i32, R, O>(reader, read_pairs, total_values)?,
-        DataTypeRaw::I64 => read_into_vec::<i64, R, O>(reader, read_pairs, total_values)?,
-        DataTypeRaw::U8 => read_into_vec::<u8, R, O>(reader, read_pairs, total_values)?,
-        DataTypeRaw::U16 => read_into_vec::<u16, R, O>(reader, read_pairs, total_values)?,
-        DataTypeRaw::U32 => read_into_vec::<u32, R, O>(reader, read_pairs, total_values)?,
-        DataTypeRaw::U64 => read_into_vec::<u64, R, O>(reader, read_pairs, total_values)?,
-        DataTypeRaw::SingleFloat => read_into_vec::<f32, R, O>(reader, read_pairs, total_values)?,
-        DataTypeRaw::DoubleFloat => read_into_vec::<f64, R, O>(reader, read_pairs, total_values)?,
+        DataTypeRaw::I8 => read_into_vec::<i8, R>(reader, path, read_pairs, total_values)?,
+        DataTypeRaw::I16 => read_into_vec::<i16, R>(reader, path, read_pairs, total_values)?,
+        DataTypeRaw::I32 => read_into_vec::<i32, R>(reader, path, read_pairs, total_values)?,
+        DataTypeRaw::I64 => read_into_vec::<i64, R>(reader, path, read_pairs, total_values)?,
+        DataTypeRaw::U8 => read_into_vec::<u8, R>(reader, path, read_pairs, total_values)?,
+        DataTypeRaw::U16 => read_into_vec::<u16, R>(reader, path, read_pairs, total_values)?,
+        DataTypeRaw::U32 => read_into_vec::<u32, R>(reader, path, read_pairs, total_values)?,
+        DataTypeRaw::U64 => read_into_vec::<u64, R>(reader, path, read_pairs, total_values)?,
+        DataTypeRaw::SingleFloat => {
+            read_into_vec::<f32, R>(reader, path, read_pairs, total_values)?
+        }
+        DataTypeRaw::DoubleFloat => {
+            read_into_vec::<f64, R>(reader, path, read_pairs, total_values)?
+        }
         // DataTypeRaw::ExtendedFloat => {},
         // DataTypeRaw::SingleFloatWithUnit => {},
         // DataTypeRaw::DoubleFloatWithUnit => {},
         // DataTypeRaw::ExtendedFloatWithUnit => {},
-        DataTypeRaw::Boolean => read_into_vec::<bool, R, O>(reader, read_pairs, total_values)?,
-        DataTypeRaw::TdmsString => read_into_vec::<String, R, O>(reader, read_pairs, total_values)?,
+        DataTypeRaw::Boolean => read_into_vec::<bool, R>(reader, path, read_pairs, total_values)?,
+        DataTypeRaw::TdmsString if lossy_strings => {
+            read_into_vec::<LossyString, R>(reader, path, read_pairs, total_values)?
+        }
+        DataTypeRaw::TdmsString => {
+            read_into_vec::<String, R>(reader, path, read_pairs, total_values)?
+        }
         DataTypeRaw::TimeStamp => {
-            read_into_vec::<TimeStamp, R, O>(reader, read_pairs, total_values)?
+            read_into_vec::<TimeStamp, R>(reader, path, read_pairs, total_values)?
         }
         // DataTypeRaw::FixedPoint => {},
         // DataTypeRaw::ComplexSingleFloat => {},
         // DataTypeRaw::ComplexDoubleFloat => {},
         // DataTypeRaw::DAQmxRawData => {},
-        _ => unimplemented!(),
+        // Still readable as raw bytes
+        other => return Err(TdmsError::UnsupportedDataType(*other)),
     };
     Ok(datavec)
 }
 
+/// Read the values of several objects in a single pass through the file. Each object's
+/// chunks are gathered into memory in file order, with the interleaved chunks objects share
+/// read once for all of them, then decoded as if the data had been written contiguously.
+pub(crate) fn read_data_vectors<R: Read + Seek>(
+    object_maps: &[&ObjectMap],
+    reader: &mut R,
+    lossy_strings: bool,
+) -> Result<Vec<DataTypeVec>> {
+    let chunks: Vec<Vec<ReadPair>> = object_maps
+        .iter()
+        .map(|object_map| {
+            object_map
+                .read_map
+                .iter()
+                .flat_map(ReadPair::chunks)
+                .collect()
+        })
+        .collect();
+
+    // Each interleaved chunk holds every object in its segment, so the kth interleaved chunk
+    // of a segment is the same chunk for all objects. Other chunks are read on their own.
+    let mut shared: BTreeMap<(usize, usize), Vec<(usize, usize)>> = BTreeMap::new();
+    let mut reads: Vec<Vec<(usize, usize)>> = Vec::new();
+    for (object, pairs) in chunks.iter().enumerate() {
+        let mut chunk_in_segment = 0;
+        for (chunk, pair) in pairs.iter().enumerate() {
+            if !pair.interleaved {
+                reads.push(vec![(object, chunk)]);
+                continue;
+            }
+            match chunk.checked_sub(1).map(|previous| &pairs[previous]) {
+                Some(previous) if previous.segment_index == pair.segment_index => {
+                    chunk_in_segment += 1
+                }
+                _ => chunk_in_segment = 0,
+            }
+            shared
+                .entry((pair.segment_index, chunk_in_segment))
+                .or_default()
+                .push((object, chunk));
+        }
+    }
+    reads.extend(shared.into_values());
+    reads.sort_by_key(|read| {
+        read.iter()
+            .map(|&(object, chunk)| chunks[object][chunk].start_index)
+            .min()
+    });
+
+    let mut gathered: Vec<Vec<Vec<u8>>> = chunks
+        .iter()
+        .map(|pairs| vec![Vec::new(); pairs.len()])
+        .collect();
+    for read in reads {
+        let pairs: Vec<&ReadPair> = read
+            .iter()
+            .map(|&(object, chunk)| &chunks[object][chunk])
+            .collect();
+        let path = &object_maps[read[0].0].last_object.object_path;
+        let values = match (pairs.len() > 1)
+            .then(|| gather_shared(reader, &pairs))
+            .flatten()
+        {
+            Some(result) => check_bounds(result, path, pairs[0])?,
+            None => read
+                .iter()
+                .zip(&pairs)
+                .map(|(&(object, _), pair)| {
+                    read_raw_pair(&object_maps[object].last_object.object_path, pair, reader)
+                })
+                .collect::<Result<_>>()?,
+        };
+        for (&(object, chunk), values) in read.iter().zip(values) {
+            gathered[object][chunk] = values;
+        }
+    }
+
+    object_maps
+        .iter()
+        .zip(chunks)
+        .zip(gathered)
+        .map(|((object_map, pairs), gathered)| {
+            let mut contiguous_pairs = Vec::with_capacity(pairs.len());
+            let mut start_index = 0;
+            for (pair, values) in pairs.into_iter().zip(&gathered) {
+                contiguous_pairs.push(ReadPair {
+                    start_index,
+                    no_bytes: values.len() as u64,
+                    interleaved: false,
+                    stride: None,
+                    ..pair
+                });
+                start_index += values.len() as u64;
+            }
+            let mut buffer = Cursor::new(gathered.concat());
+            read_pairs_vector(object_map, &contiguous_pairs, &mut buffer, lossy_strings)
+        })
+        .collect()
+}
+
+/// Write a vector of a given tdms data type as a single chunk of raw data in the given byte
+/// order, the inverse of read_data_vector.
+pub fn write_data_vector<W: Write, O: ByteOrder>(writer: &mut W, data: &DataTypeVec) -> Result<()> {
+    match data {
+        DataTypeVec::Void(_) => Ok(()),
+        DataTypeVec::Boolean(datavec) => bool::write::<W, O>(datavec, writer),
+        DataTypeVec::I8(datavec) => i8::write::<W, O>(datavec, writer),
+        DataTypeVec::I16(datavec) => i16::write::<W, O>(datavec, writer),
+        DataTypeVec::I32(datavec) => i32::write::<W, O>(datavec, writer),
+        DataTypeVec::I64(datavec) => i64::write::<W, O>(datavec, writer),
+        DataTypeVec::U8(datavec) => u8::write::<W, O>(datavec, writer),
+        DataTypeVec::U16(datavec) => u16::write::<W, O>(datavec, writer),
+        DataTypeVec::U32(datavec) => u32::write::<W, O>(datavec, writer),
+        DataTypeVec::U64(datavec) => u64::write::<W, O>(datavec, writer),
+        DataTypeVec::Float(datavec) => f32::write::<W, O>(datavec, writer),
+        DataTypeVec::Double(datavec) => f64::write::<W, O>(datavec, writer),
+        DataTypeVec::TdmsString(datavec) => String::write::<W, O>(datavec, writer),
+        DataTypeVec::TimeStamp(datavec) => TimeStamp::write::<W, O>(datavec, writer),
+    }
+}
+
 impl TryFrom<DataTypeVec> for Vec<f64> {
     type Error = TdmsError;
 
@@ -532,7 +1747,510 @@ impl TryFrom<DataTypeVec> for Vec<f64> {
             // ComplexSingle(Vec<??>)
             // CompledDouble(Vec<??>)
             // TimeStamp(Vec<TimeStamp>),
-            _ => unimplemented!(),
+            other => Err(TdmsError::UnsupportedDataType(other.data_type())),
         }
     }
 }
+
+/// Format the values as strings with the default `FormatOptions`, taking string data as it is
+impl From<DataTypeVec> for Vec<String> {
+    fn from(in_vec: DataTypeVec) -> Self {
+        match in_vec {
+            DataTypeVec::TdmsString(datavec) => datavec,
+            other => other.to_string_vec(&FormatOptions::default()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{ramp, SegmentBuilder, TdmsBuilder};
+    use crate::{TdmsFile, HEADER_LEN};
+    use std::collections::HashSet;
+    use std::io::Cursor;
+
+    #[test]
+    fn data_type_names_round_trip() {
+        for data_type in DATA_TYPES {
+            let (variant, ni, short) = data_type.names();
+            assert_eq!(data_type.to_string(), variant);
+            for name in [variant, ni, short] {
+                assert_eq!(name.parse::<DataTypeRaw>().unwrap(), data_type);
+                assert_eq!(
+                    name.to_lowercase().parse::<DataTypeRaw>().unwrap(),
+                    data_type
+                );
+            }
+            assert_eq!(DataTypeRaw::from_u32(data_type as u32).unwrap(), data_type);
+        }
+        assert_eq!(
+            " DT_DOUBLE ".parse::<DataTypeRaw>().unwrap(),
+            DataTypeRaw::DoubleFloat
+        );
+        assert!(matches!(
+            "Quadruple".parse::<DataTypeRaw>(),
+            Err(TdmsError::UnknownDataTypeName(name)) if name == "Quadruple"
+        ));
+    }
+
+    #[test]
+    fn data_types_are_distinct_map_keys() {
+        let distinct: HashSet<DataTypeRaw> = DATA_TYPES.into_iter().collect();
+        assert_eq!(distinct.len(), DATA_TYPES.len());
+        let names: HashSet<String> = DATA_TYPES.iter().map(DataTypeRaw::to_string).collect();
+        assert_eq!(names.len(), DATA_TYPES.len());
+    }
+
+    #[test]
+    fn values_display_readably() {
+        assert_eq!(
+            DataType::Double(1.0 / 3.0).to_string(),
+            "0.3333333333333333"
+        );
+        assert_eq!(DataType::Double(0.1).to_string(), "0.1");
+        assert_eq!(DataType::Float(0.1).to_string(), "0.1");
+        assert_eq!(DataType::I64(-42).to_string(), "-42");
+        assert_eq!(DataType::Boolean(true).to_string(), "true");
+        assert_eq!(
+            DataType::TdmsString("say \"hi\"".to_string()).to_string(),
+            r#""say \"hi\"""#
+        );
+        let half_past_unix_epoch = TimeStamp {
+            epoch: LABVIEW_EPOCH_OFFSET,
+            radix: 1 << 63,
+        };
+        assert_eq!(
+            DataType::TimeStamp(half_past_unix_epoch).to_string(),
+            "1970-01-01T00:00:00.500+00:00"
+        );
+    }
+
+    /// Which of f64, i64, u64, bool, String and TimeStamp each value converts to
+    #[test]
+    fn property_conversion_matrix() {
+        let matrix = [
+            (
+                DataType::Void(()),
+                [false, false, false, false, false, false],
+            ),
+            (
+                DataType::Boolean(true),
+                [false, false, false, true, false, false],
+            ),
+            (DataType::I8(-8), [true, true, false, false, false, false]),
+            (DataType::I16(-16), [true, true, false, false, false, false]),
+            (DataType::I32(-32), [true, true, false, false, false, false]),
+            (
+                DataType::I64(-64),
+                [false, true, false, false, false, false],
+            ),
+            (DataType::U8(8), [true, true, true, false, false, false]),
+            (DataType::U16(16), [true, true, true, false, false, false]),
+            (DataType::U32(32), [true, true, true, false, false, false]),
+            (DataType::U64(64), [false, false, true, false, false, false]),
+            (
+                DataType::Float(0.5),
+                [true, false, false, false, false, false],
+            ),
+            (
+                DataType::Double(0.25),
+                [true, false, false, false, false, false],
+            ),
+            (
+                DataType::TdmsString("12".to_string()),
+                [false, false, false, false, true, false],
+            ),
+            (
+                DataType::TimeStamp(TimeStamp::default()),
+                [false, false, false, false, false, true],
+            ),
+            (
+                DataType::Unknown {
+                    type_id: 0x99,
+                    bytes: vec![0; 4],
+                },
+                [false, false, false, false, false, false],
+            ),
+        ];
+        for (value, converts) in &matrix {
+            let results = [
+                f64::try_from(value).is_ok(),
+                i64::try_from(value).is_ok(),
+                u64::try_from(value).is_ok(),
+                bool::try_from(value).is_ok(),
+                String::try_from(value).is_ok(),
+                TimeStamp::try_from(value).is_ok(),
+            ];
+            assert_eq!(&results, converts, "converting {:?}", value);
+        }
+    }
+
+    #[test]
+    fn property_conversions_widen_exactly() {
+        assert_eq!(
+            f64::try_from(&DataType::I32(i32::MIN)).unwrap(),
+            i32::MIN as f64
+        );
+        assert_eq!(f64::try_from(&DataType::Float(0.1)).unwrap(), 0.1f32 as f64);
+        assert_eq!(
+            i64::try_from(&DataType::U32(u32::MAX)).unwrap(),
+            u32::MAX as i64
+        );
+        assert_eq!(i64::try_from(&DataType::I8(-1)).unwrap(), -1);
+        assert_eq!(u64::try_from(&DataType::U64(u64::MAX)).unwrap(), u64::MAX);
+        assert_eq!(
+            String::try_from(&DataType::TdmsString("V".into())).unwrap(),
+            "V"
+        );
+
+        // Floats aren't truncated to integers, nor strings parsed as numbers
+        assert!(matches!(
+            i64::try_from(&DataType::Double(2.0)),
+            Err(TdmsError::WrongPropertyType {
+                expected: "i64",
+                found: DataTypeRaw::DoubleFloat
+            })
+        ));
+        assert!(matches!(
+            f64::try_from(&DataType::TdmsString("2.5".into())),
+            Err(TdmsError::WrongPropertyType {
+                expected: "f64",
+                found: DataTypeRaw::TdmsString
+            })
+        ));
+    }
+
+    fn strings(values: &[&str]) -> DataTypeVec {
+        DataTypeVec::TdmsString(values.iter().map(|value| value.to_string()).collect())
+    }
+
+    #[test]
+    fn string_chunks_read_from_their_own_offset_tables() {
+        let channel = "/'group'/'notes'";
+        let first = ["start", "", "caf\u{e9}", ""];
+        let second = ["", "", "end"];
+        let bytes = TdmsBuilder::new()
+            .segment(
+                SegmentBuilder::new()
+                    .channel(channel, strings(&first))
+                    .chunks(2),
+            )
+            .segment(SegmentBuilder::new().channel(channel, strings(&second)))
+            .build();
+        let file = TdmsFile::new(Cursor::new(bytes)).unwrap();
+
+        let expected: Vec<&str> = [first, first].concat().into_iter().chain(second).collect();
+        assert_eq!(file.load_data(channel).unwrap(), strings(&expected));
+    }
+
+    #[test]
+    fn big_endian_files_read_back_as_written() {
+        let values = [
+            DataTypeVec::Boolean(vec![true, false, true]),
+            DataTypeVec::I8(vec![-1, 2, i8::MIN]),
+            DataTypeVec::I16(vec![-300, 2, i16::MAX]),
+            DataTypeVec::I32(vec![0x0102_0304, -2, 3]),
+            DataTypeVec::I64(vec![-1, i64::MAX, 3]),
+            DataTypeVec::U8(vec![1, 2, 255]),
+            DataTypeVec::U16(vec![1, 0x0102, 3]),
+            DataTypeVec::U32(vec![1, 2, u32::MAX]),
+            DataTypeVec::U64(vec![1, 2, 1 << 40]),
+            DataTypeVec::Float(vec![1.5, -2.25, f32::MIN_POSITIVE]),
+            DataTypeVec::Double(vec![1.5, -2.25, 1e300]),
+            strings(&["big", "", "endian"]),
+            DataTypeVec::TimeStamp(vec![TimeStamp {
+                epoch: -5,
+                radix: 1 << 63,
+            }]),
+        ];
+        let mut segment = SegmentBuilder::new().big_endian(true);
+        for (i, data) in values.iter().enumerate() {
+            segment = segment
+                .channel(&format!("/'be'/'{}'", i), data.clone())
+                .property("value", data.get(0).unwrap());
+        }
+        let bytes = TdmsBuilder::new().segment(segment).build();
+        // Everything after the ToC is big endian, including the raw data
+        let raw_data_offset = u64::from_be_bytes(bytes[20..28].try_into().unwrap());
+        let file = TdmsFile::new(Cursor::new(bytes.clone())).unwrap();
+        assert!(file.segments()[0].is_big_endian());
+        assert_eq!(file.segments()[0].raw_data_offset, raw_data_offset);
+        let i32_at = (HEADER_LEN + raw_data_offset) as usize + 3 + 3 + 6;
+        assert_eq!(bytes[i32_at..i32_at + 4], [1, 2, 3, 4]);
+
+        for (i, data) in values.iter().enumerate() {
+            let path = format!("/'be'/'{}'", i);
+            assert_eq!(&file.load_data(path.as_str()).unwrap(), data);
+            let property = file.resolve_property(path.as_str(), "value").unwrap();
+            assert_eq!(Some(property.clone()), data.get(0));
+        }
+    }
+
+    #[test]
+    fn strings_round_trip_through_their_offset_table() {
+        let values = strings(&["start", "", "end"]);
+        let mut bytes = Vec::new();
+        write_data_vector::<_, BE>(&mut bytes, &values).unwrap();
+        // The empty string repeats the offset before it
+        let mut offsets = vec![0u32; 3];
+        BE::read_u32_into(&bytes[..12], &mut offsets);
+        assert_eq!(offsets, [5, 5, 8]);
+        assert_eq!(&bytes[12..], b"startend");
+
+        let mut buffer = vec![String::new(); 3];
+        String::read::<_, BE>(&mut buffer, &mut Cursor::new(bytes)).unwrap();
+        assert_eq!(DataTypeVec::TdmsString(buffer), values);
+    }
+
+    #[test]
+    fn string_offsets_past_u32_fail() {
+        let max = u32::MAX as usize;
+        assert_eq!(
+            string_offsets(&[max - 1, 0, 1]).unwrap(),
+            [u32::MAX - 1, u32::MAX - 1, u32::MAX]
+        );
+        assert!(matches!(
+            string_offsets(&[max, 0, 1]),
+            Err(TdmsError::StringChunkTooLarge { bytes }) if bytes == u32::MAX as u64 + 1
+        ));
+        #[cfg(target_pointer_width = "64")]
+        assert!(matches!(
+            string_offsets(&[max + 1]),
+            Err(TdmsError::StringChunkTooLarge { bytes }) if bytes == u32::MAX as u64 + 1
+        ));
+    }
+
+    #[test]
+    fn decreasing_string_offsets_are_malformed() {
+        let mut bytes = Vec::new();
+        for offset in [3u32, 1] {
+            bytes.extend_from_slice(&offset.to_le_bytes());
+        }
+        bytes.extend_from_slice(b"abc");
+        let mut buffer = vec![String::new(); 2];
+        let result = String::read::<_, LE>(&mut buffer, &mut Cursor::new(bytes));
+        assert!(matches!(result, Err(TdmsError::MalformedSegment)));
+    }
+
+    #[test]
+    fn timestamps_count_from_the_labview_epoch() {
+        let utc = |epoch, radix| TimeStamp { epoch, radix }.to_utc().unwrap();
+        let labview_epoch = Utc.with_ymd_and_hms(1904, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(utc(0, 0), labview_epoch);
+        assert_eq!(utc(LABVIEW_EPOCH_OFFSET, 0), DateTime::UNIX_EPOCH);
+        let half_past = Utc.timestamp_opt(1, 500_000_000).unwrap();
+        assert_eq!(utc(LABVIEW_EPOCH_OFFSET + 1, 1 << 63), half_past);
+
+        let local = TimeStamp { epoch: 0, radix: 0 }.to_local_time().unwrap();
+        assert_eq!(local, labview_epoch);
+    }
+
+    #[test]
+    fn timestamp_channels_convert_to_utc() {
+        let channel = "/'group'/'time'";
+        let start = LABVIEW_EPOCH_OFFSET as u64;
+        let bytes = TdmsBuilder::new()
+            .segment(SegmentBuilder::new().channel(channel, ramp(DataTypeRaw::TimeStamp, start, 3)))
+            .build();
+        let file = TdmsFile::new(Cursor::new(bytes)).unwrap();
+        let times = match file.load_data(channel).unwrap() {
+            DataTypeVec::TimeStamp(times) => times,
+            data => panic!("{:?}", data),
+        };
+        let times: Vec<i64> = times
+            .iter()
+            .map(|time| time.to_utc().unwrap().timestamp())
+            .collect();
+        assert_eq!(times, [0, 1, 2]);
+    }
+
+    #[test]
+    fn numeric_views_cover_numeric_data_only() {
+        let booleans = DataTypeVec::Boolean(vec![true, false]);
+        assert!(booleans.is_numeric());
+        assert_eq!(booleans.iter_f64().collect::<Vec<_>>(), [1.0, 0.0]);
+
+        let floats = DataTypeVec::Float(vec![0.5, -2.0, 3.25]);
+        assert_eq!((floats.len(), floats.is_empty()), (3, false));
+        assert_eq!(floats.get(1), Some(DataType::Float(-2.0)));
+        assert_eq!(floats.get(3), None);
+        assert_eq!(floats.get_f64(2), Some(3.25));
+        assert_eq!(floats.get_f64(3), None);
+        assert_eq!(floats.iter_f64().collect::<Vec<_>>(), [0.5, -2.0, 3.25]);
+        assert_eq!(floats.iter_strings().count(), 0);
+
+        let strings = DataTypeVec::TdmsString(vec!["a".to_string(), "bc".to_string()]);
+        assert!(!strings.is_numeric());
+        assert_eq!(strings.get(0), Some(DataType::TdmsString("a".to_string())));
+        assert_eq!(strings.get_f64(0), None);
+        assert_eq!(strings.iter_f64().count(), 0);
+        assert_eq!(strings.iter_strings().collect::<Vec<_>>(), ["a", "bc"]);
+
+        let timestamps = DataTypeVec::TimeStamp(vec![TimeStamp::default()]);
+        assert!(!timestamps.is_numeric());
+        assert_eq!(timestamps.iter_f64().count(), 0);
+
+        let empty = DataTypeVec::U16(Vec::new());
+        assert_eq!((empty.len(), empty.is_empty()), (0, true));
+        assert_eq!(empty.get(0), None);
+    }
+
+    #[test]
+    fn slices_borrow_only_their_own_variant() {
+        let vectors = [
+            DataTypeVec::Void(vec![()]),
+            DataTypeVec::Boolean(vec![true]),
+            DataTypeVec::I8(vec![-1]),
+            DataTypeVec::I16(vec![-2]),
+            DataTypeVec::I32(vec![-3]),
+            DataTypeVec::I64(vec![-4]),
+            DataTypeVec::U8(vec![1]),
+            DataTypeVec::U16(vec![2]),
+            DataTypeVec::U32(vec![3]),
+            DataTypeVec::U64(vec![4]),
+            DataTypeVec::Float(vec![0.5]),
+            DataTypeVec::Double(vec![0.25]),
+            DataTypeVec::TdmsString(vec!["s".to_string()]),
+            DataTypeVec::TimeStamp(vec![TimeStamp { epoch: 1, radix: 2 }]),
+        ];
+        for vector in &vectors {
+            let borrowed = [
+                vector.as_bool_slice().is_some(),
+                vector.as_i8_slice().is_some(),
+                vector.as_i16_slice().is_some(),
+                vector.as_i32_slice().is_some(),
+                vector.as_i64_slice().is_some(),
+                vector.as_u8_slice().is_some(),
+                vector.as_u16_slice().is_some(),
+                vector.as_u32_slice().is_some(),
+                vector.as_u64_slice().is_some(),
+                vector.as_f32_slice().is_some(),
+                vector.as_f64_slice().is_some(),
+                vector.as_string_slice().is_some(),
+                vector.as_timestamp_slice().is_some(),
+            ];
+            let expected = !matches!(vector, DataTypeVec::Void(_)) as usize;
+            assert_eq!(
+                borrowed.iter().filter(|&&some| some).count(),
+                expected,
+                "{:?}",
+                vector
+            );
+        }
+
+        assert_eq!(vectors[1].as_bool_slice(), Some(&[true][..]));
+        assert_eq!(vectors[2].as_i8_slice(), Some(&[-1][..]));
+        assert_eq!(vectors[3].as_i16_slice(), Some(&[-2][..]));
+        assert_eq!(vectors[4].as_i32_slice(), Some(&[-3][..]));
+        assert_eq!(vectors[5].as_i64_slice(), Some(&[-4][..]));
+        assert_eq!(vectors[6].as_u8_slice(), Some(&[1][..]));
+        assert_eq!(vectors[7].as_u16_slice(), Some(&[2][..]));
+        assert_eq!(vectors[8].as_u32_slice(), Some(&[3][..]));
+        assert_eq!(vectors[9].as_u64_slice(), Some(&[4][..]));
+        assert_eq!(vectors[10].as_f32_slice(), Some(&[0.5][..]));
+        assert_eq!(vectors[11].as_f64_slice(), Some(&[0.25][..]));
+        assert_eq!(vectors[12].as_string_slice(), Some(&["s".to_string()][..]));
+        assert_eq!(
+            vectors[13].as_timestamp_slice(),
+            Some(&[TimeStamp { epoch: 1, radix: 2 }][..])
+        );
+    }
+
+    #[test]
+    fn approx_eq_allows_floats_within_epsilon() {
+        let doubles = |values: &[f64]| DataTypeVec::Double(values.to_vec());
+        let base = doubles(&[1.0, f64::NAN, f64::INFINITY, f64::NEG_INFINITY]);
+
+        assert!(base.approx_eq(&base.clone(), 0.0));
+        // Up to epsilon either way is equal, beyond it isn't
+        assert!(doubles(&[1.0]).approx_eq(&doubles(&[1.5]), 0.5));
+        assert!(doubles(&[1.0]).approx_eq(&doubles(&[0.5]), 0.5));
+        assert!(!doubles(&[1.0]).approx_eq(&doubles(&[1.5000001]), 0.5));
+        // NaN and infinities only match themselves
+        assert!(!doubles(&[f64::NAN]).approx_eq(&doubles(&[1.0]), f64::MAX));
+        assert!(!doubles(&[f64::INFINITY]).approx_eq(&doubles(&[f64::NEG_INFINITY]), 1.0));
+        assert!(!doubles(&[f64::INFINITY]).approx_eq(&doubles(&[f64::MAX]), 1.0));
+        assert!(!doubles(&[1.0]).approx_eq(&doubles(&[1.0, 1.0]), 1.0));
+
+        assert!(DataTypeVec::Float(vec![1.0, f32::NAN])
+            .approx_eq(&DataTypeVec::Float(vec![1.25, f32::NAN]), 0.25));
+        // Variants must match, and other variants compare exactly
+        assert!(!DataTypeVec::Float(vec![1.0]).approx_eq(&doubles(&[1.0]), 1.0));
+        assert!(DataTypeVec::I32(vec![1, 2]).approx_eq(&DataTypeVec::I32(vec![1, 2]), 0.0));
+        assert!(!DataTypeVec::I32(vec![1, 2]).approx_eq(&DataTypeVec::I32(vec![1, 3]), 10.0));
+    }
+
+    #[test]
+    fn string_vecs_follow_the_format_options() {
+        let floats = DataTypeVec::Double(vec![0.1, 2.0, -1.25]);
+        assert_eq!(
+            floats.to_string_vec(&FormatOptions::new()),
+            ["0.1", "2", "-1.25"]
+        );
+        assert_eq!(
+            floats.to_string_vec(&FormatOptions::new().float_precision(Some(2))),
+            ["0.10", "2.00", "-1.25"]
+        );
+
+        // Half a second after the Unix epoch, and a time before any date chrono can hold
+        let timestamps = DataTypeVec::TimeStamp(vec![
+            TimeStamp {
+                epoch: LABVIEW_EPOCH_OFFSET,
+                radix: 1 << 63,
+            },
+            TimeStamp {
+                epoch: i64::MIN,
+                radix: 5,
+            },
+        ]);
+        assert_eq!(
+            timestamps.to_string_vec(&FormatOptions::new()),
+            ["1970-01-01T00:00:00.500+00:00", "-9223372036854775808 5"]
+        );
+        let options = FormatOptions::new().timestamp_format(Some("%Y-%m-%d %H:%M:%S%.3f".into()));
+        assert_eq!(
+            timestamps.to_string_vec(&options),
+            ["1970-01-01 00:00:00.500", "-9223372036854775808 5"]
+        );
+
+        // Other types ignore the options
+        assert_eq!(
+            DataTypeVec::Boolean(vec![true, false]).to_string_vec(&options),
+            ["true", "false"]
+        );
+        assert_eq!(DataTypeVec::I8(vec![-3]).to_string_vec(&options), ["-3"]);
+        assert_eq!(
+            DataTypeVec::Void(vec![(); 2]).to_string_vec(&options),
+            ["", ""]
+        );
+    }
+
+    #[test]
+    fn cleaning_handles_only_non_finite_values() {
+        let floats = DataTypeVec::Float(vec![1.5, f32::NAN, f32::INFINITY, f32::NEG_INFINITY]);
+        assert_eq!(
+            floats.to_f64_cleaned(NonFinite::Gap),
+            [Some(1.5), None, None, None]
+        );
+        assert_eq!(
+            floats.to_f64_cleaned(NonFinite::Replace(0.0)),
+            [Some(1.5), Some(0.0), Some(0.0), Some(0.0)]
+        );
+        assert_eq!(
+            DataTypeVec::I16(vec![-1, 2]).to_f64_cleaned(NonFinite::Gap),
+            [Some(-1.0), Some(2.0)]
+        );
+        assert!(DataTypeVec::TdmsString(vec!["1".to_string()])
+            .to_f64_cleaned(NonFinite::Gap)
+            .is_empty());
+
+        // The raw conversions keep them
+        let raw: Vec<f64> = floats.iter_f64().collect();
+        assert!(raw[1].is_nan());
+        assert_eq!(raw[2..], [f64::INFINITY, f64::NEG_INFINITY]);
+        let converted = Vec::<f64>::try_from(floats).unwrap();
+        assert!(converted[1].is_nan());
+        assert_eq!(converted[2..], [f64::INFINITY, f64::NEG_INFINITY]);
+    }
+}
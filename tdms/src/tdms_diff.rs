@@ -0,0 +1,490 @@
+use std::fmt;
+use std::io::{Read, Seek};
+
+use indexmap::IndexMap;
+
+use crate::tdms_datatypes::{DataType, DataTypeRaw, TimeStamp};
+use crate::tdms_error::Result;
+use crate::{ObjectProperty, TdmsFile};
+
+/// Options controlling how `diff` compares two files. By default only objects, properties,
+/// data types and lengths are compared, see data_compare.
+#[derive(Debug, Clone)]
+pub struct DiffOptions {
+    data_compare: bool,
+    absolute_tolerance: f64,
+    relative_tolerance: f64,
+    max_mismatches: usize,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        DiffOptions {
+            data_compare: false,
+            absolute_tolerance: 0.0,
+            relative_tolerance: 0.0,
+            max_mismatches: 10,
+        }
+    }
+}
+
+impl DiffOptions {
+    pub fn new() -> DiffOptions {
+        DiffOptions::default()
+    }
+
+    /// Load and compare the values of channels in both files, not just their lengths
+    pub fn data_compare(mut self, data_compare: bool) -> Self {
+        self.data_compare = data_compare;
+        self
+    }
+
+    /// Floating point values differing by up to this much are taken as equal
+    pub fn absolute_tolerance(mut self, absolute_tolerance: f64) -> Self {
+        self.absolute_tolerance = absolute_tolerance;
+        self
+    }
+
+    /// Floating point values differing by up to this fraction of the larger magnitude are
+    /// taken as equal, as well as those within the absolute tolerance
+    pub fn relative_tolerance(mut self, relative_tolerance: f64) -> Self {
+        self.relative_tolerance = relative_tolerance;
+        self
+    }
+
+    /// List the indices of at most this many mismatching values per channel, the rest are
+    /// only counted. 10 by default.
+    pub fn max_mismatches(mut self, max_mismatches: usize) -> Self {
+        self.max_mismatches = max_mismatches;
+        self
+    }
+
+    /// Whether two values are taken as equal. Floats are within the tolerances, with NaN
+    /// matching NaN, timestamps agree to the nearest microsecond, and anything else,
+    /// including 64 bit integers, must be equal.
+    fn values_match(&self, a: &DataType, b: &DataType) -> bool {
+        if a == b {
+            return true;
+        }
+        match (a, b) {
+            (DataType::TimeStamp(a), DataType::TimeStamp(b)) => micros(a) == micros(b),
+            _ => match (f64::try_from(a), f64::try_from(b)) {
+                (Ok(a), Ok(b)) if a.is_nan() || b.is_nan() => a.is_nan() && b.is_nan(),
+                (Ok(a), Ok(b)) => {
+                    let tolerance = self
+                        .absolute_tolerance
+                        .max(self.relative_tolerance * a.abs().max(b.abs()));
+                    (a - b).abs() <= tolerance
+                }
+                _ => false,
+            },
+        }
+    }
+}
+
+/// A timestamp in microseconds since the LabVIEW epoch, rounded to the nearest
+fn micros(time: &TimeStamp) -> i128 {
+    let fraction = (time.radix as u128 * 1_000_000 + (1 << 63)) >> 64;
+    time.epoch as i128 * 1_000_000 + fraction as i128
+}
+
+/// A property that differs between the files, None where a file's object doesn't have it
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertyDiff {
+    pub path: String,
+    pub name: String,
+    pub a: Option<DataType>,
+    pub b: Option<DataType>,
+}
+
+/// A channel with a different number of values in each file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LengthMismatch {
+    pub path: String,
+    pub a: u64,
+    pub b: u64,
+}
+
+/// A channel stored as a different data type in each file, its values aren't compared
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeMismatch {
+    pub path: String,
+    pub a: DataTypeRaw,
+    pub b: DataTypeRaw,
+}
+
+/// A channel with values that differ between the files, of those both files have
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValueDiff {
+    pub path: String,
+    /// The number of values that differ
+    pub mismatches: u64,
+    /// The indices of the first values that differ, up to max_mismatches of them
+    pub first_indices: Vec<u64>,
+}
+
+/// The differences between two files found by `diff`, each list in the order the objects
+/// appear in the first file, then the second
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DiffReport {
+    pub only_in_a: Vec<String>,
+    pub only_in_b: Vec<String>,
+    pub properties: Vec<PropertyDiff>,
+    pub lengths: Vec<LengthMismatch>,
+    pub data_types: Vec<TypeMismatch>,
+    pub values: Vec<ValueDiff>,
+}
+
+impl DiffReport {
+    /// Returns true if no differences were found
+    pub fn is_empty(&self) -> bool {
+        self.only_in_a.is_empty()
+            && self.only_in_b.is_empty()
+            && self.properties.is_empty()
+            && self.lengths.is_empty()
+            && self.data_types.is_empty()
+            && self.values.is_empty()
+    }
+}
+
+impl fmt::Display for DiffReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No differences");
+        }
+        let value = |value: &Option<DataType>| match value {
+            Some(value) => value.to_string(),
+            None => "<missing>".to_string(),
+        };
+
+        for path in &self.only_in_a {
+            writeln!(f, "Only in a:\t{}", path)?;
+        }
+        for path in &self.only_in_b {
+            writeln!(f, "Only in b:\t{}", path)?;
+        }
+        for diff in &self.properties {
+            writeln!(
+                f,
+                "Property:\t{} {}: {} != {}",
+                diff.path,
+                diff.name,
+                value(&diff.a),
+                value(&diff.b)
+            )?;
+        }
+        for mismatch in &self.lengths {
+            writeln!(
+                f,
+                "Length:\t{}: {} != {}",
+                mismatch.path, mismatch.a, mismatch.b
+            )?;
+        }
+        for mismatch in &self.data_types {
+            writeln!(
+                f,
+                "Data type:\t{}: {} != {}",
+                mismatch.path, mismatch.a, mismatch.b
+            )?;
+        }
+        for diff in &self.values {
+            write!(f, "Values:\t{}: {} differ", diff.path, diff.mismatches)?;
+            if !diff.first_indices.is_empty() {
+                let indices: Vec<String> = diff.first_indices.iter().map(u64::to_string).collect();
+                write!(f, ", first at {}", indices.join(", "))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compare two files, e.g. a newly acquired file against a golden one, reporting objects
+/// only one has, properties that differ, and channels whose data type or length differ. With
+/// data_compare set the values of channels in both are loaded and compared too, see
+/// `DiffOptions`. Properties are compared as values are. Fails if a channel can't be loaded.
+pub fn diff<R1: Read + Seek, R2: Read + Seek>(
+    a: &TdmsFile<R1>,
+    b: &TdmsFile<R2>,
+    options: &DiffOptions,
+) -> Result<DiffReport> {
+    let (objects_a, objects_b) = (&a.tdms_map.all_objects, &b.tdms_map.all_objects);
+    let mut report = DiffReport {
+        only_in_a: missing_from(objects_a.keys(), |path| objects_b.contains_key(path)),
+        only_in_b: missing_from(objects_b.keys(), |path| objects_a.contains_key(path)),
+        ..DiffReport::default()
+    };
+    let maps_lengths = !a.tdms_map.options.metadata_only && !b.tdms_map.options.metadata_only;
+
+    for (path, map_a) in objects_a {
+        let map_b = match objects_b.get(path) {
+            Some(map_b) => map_b,
+            None => continue,
+        };
+        let properties = (a.properties_of(map_a)?, b.properties_of(map_b)?);
+        compare_properties(path, properties, options, &mut report);
+
+        let types = (
+            map_a.last_object.raw_data_type,
+            map_b.last_object.raw_data_type,
+        );
+        let (type_a, type_b) = match types {
+            (Some(type_a), Some(type_b)) => (type_a, type_b),
+            _ => continue,
+        };
+        if type_a != type_b {
+            report.data_types.push(TypeMismatch {
+                path: path.clone(),
+                a: type_a,
+                b: type_b,
+            });
+            continue;
+        }
+        if maps_lengths && map_a.total_values() != map_b.total_values() {
+            report.lengths.push(LengthMismatch {
+                path: path.clone(),
+                a: map_a.total_values(),
+                b: map_b.total_values(),
+            });
+        }
+        if options.data_compare {
+            let (data_a, data_b) = (a.load_data(path.as_str())?, b.load_data(path.as_str())?);
+            let mut diff = ValueDiff {
+                path: path.clone(),
+                mismatches: 0,
+                first_indices: Vec::new(),
+            };
+            for i in 0..data_a.len().min(data_b.len()) {
+                if let (Some(value_a), Some(value_b)) = (data_a.get(i), data_b.get(i)) {
+                    if !options.values_match(&value_a, &value_b) {
+                        diff.mismatches += 1;
+                        if diff.first_indices.len() < options.max_mismatches {
+                            diff.first_indices.push(i as u64);
+                        }
+                    }
+                }
+            }
+            if diff.mismatches > 0 {
+                report.values.push(diff);
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// The paths not found in the other file
+fn missing_from<'a>(
+    paths: impl Iterator<Item = &'a String>,
+    found: impl Fn(&str) -> bool,
+) -> Vec<String> {
+    paths.filter(|path| !found(path)).cloned().collect()
+}
+
+/// Add the properties an object has that differ between the files to the report, those of
+/// the first file's object in order then any only the second's has
+fn compare_properties(
+    path: &str,
+    (properties_a, properties_b): (
+        &IndexMap<String, ObjectProperty>,
+        &IndexMap<String, ObjectProperty>,
+    ),
+    options: &DiffOptions,
+    report: &mut DiffReport,
+) {
+    let names = properties_a.keys().chain(
+        properties_b
+            .keys()
+            .filter(|name| !properties_a.contains_key(*name)),
+    );
+    for name in names {
+        let a = properties_a.get(name).map(|property| property.value());
+        let b = properties_b.get(name).map(|property| property.value());
+        let matches = match (a, b) {
+            (Some(a), Some(b)) => options.values_match(a, b),
+            _ => false,
+        };
+        if !matches {
+            report.properties.push(PropertyDiff {
+                path: path.to_string(),
+                name: name.clone(),
+                a: a.cloned(),
+                b: b.cloned(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tdms_datatypes::DataTypeVec;
+    use crate::test_util::{SegmentBuilder, TdmsBuilder};
+    use std::io::Cursor;
+
+    const VOLTS: &str = "/'group'/'volts'";
+    const COUNT: &str = "/'group'/'count'";
+    const TIME: &str = "/'group'/'time'";
+
+    /// A time radix of this many microseconds
+    fn radix_micros(micros: f64) -> u64 {
+        (micros * 1e-6 * 2f64.powi(64)) as u64
+    }
+
+    #[test]
+    fn floats_match_within_either_tolerance() {
+        let options = DiffOptions::new()
+            .absolute_tolerance(0.01)
+            .relative_tolerance(0.001);
+        let matches = |a, b| options.values_match(&DataType::Double(a), &DataType::Double(b));
+        assert!(matches(1.0, 1.005));
+        assert!(!matches(1.0, 1.02));
+        assert!(matches(1000.0, 1001.0));
+        assert!(!matches(1000.0, 1001.5));
+        assert!(matches(f64::NAN, f64::NAN));
+        assert!(!matches(f64::NAN, 1.0));
+        assert!(matches(f64::INFINITY, f64::INFINITY));
+        assert!(!DiffOptions::new().values_match(&DataType::Double(1.0), &DataType::Double(1.0001)));
+
+        // Integers compare as floats, apart from 64 bit integers which must be equal
+        assert!(options.values_match(&DataType::I32(1000), &DataType::Float(1000.5)));
+        let big = 1 << 53;
+        assert!(!options.values_match(&DataType::I64(big), &DataType::I64(big + 1)));
+        assert!(!options.values_match(&DataType::U64(1), &DataType::U64(2)));
+    }
+
+    #[test]
+    fn timestamps_match_to_the_microsecond() {
+        let options = DiffOptions::new().absolute_tolerance(1.0);
+        let matches = |(epoch_a, micros_a), (epoch_b, micros_b)| {
+            let time = |epoch, micros| {
+                DataType::TimeStamp(TimeStamp {
+                    epoch,
+                    radix: radix_micros(micros),
+                })
+            };
+            options.values_match(&time(epoch_a, micros_a), &time(epoch_b, micros_b))
+        };
+        assert!(matches((10, 5.0), (10, 5.2)));
+        assert!(!matches((10, 5.0), (10, 6.0)));
+        assert!(!matches((10, 5.0), (11, 5.0)));
+        // Rounded to the nearest microsecond
+        assert!(matches((10, 5.6), (10, 6.4)));
+        assert!(!matches((10, 5.4), (10, 5.6)));
+    }
+
+    fn times(micros: &[f64]) -> DataTypeVec {
+        let time = |&micros| TimeStamp {
+            epoch: 100,
+            radix: radix_micros(micros),
+        };
+        DataTypeVec::TimeStamp(micros.iter().map(time).collect())
+    }
+
+    #[test]
+    fn files_differing_in_every_way_are_reported() {
+        let a = TdmsBuilder::new()
+            .segment(
+                SegmentBuilder::new()
+                    .object("/'group'")
+                    .property("gain", DataType::Double(1.0))
+                    .property("operator", DataType::TdmsString("ann".to_string()))
+                    .channel(VOLTS, DataTypeVec::Double(vec![1.0, 2.0, 3.0, 4.0]))
+                    .channel(COUNT, DataTypeVec::I32(vec![1, 2, 3]))
+                    .channel(TIME, times(&[1.0, 2.0, 3.0]))
+                    .object("/'group'/'only a'"),
+            )
+            .build();
+        let b = TdmsBuilder::new()
+            .segment(
+                SegmentBuilder::new()
+                    .object("/'group'")
+                    .property("gain", DataType::Double(1.001))
+                    .property("site", DataType::TdmsString("lab".to_string()))
+                    .channel(VOLTS, DataTypeVec::Double(vec![1.0, 2.5, 3.001, 5.0]))
+                    .channel(COUNT, DataTypeVec::I32(vec![1, 2, 3, 4]))
+                    .channel(TIME, times(&[1.2, 2.0, 4.0]))
+                    .object("/'group'/'only b'"),
+            )
+            .build();
+        let a = TdmsFile::new(Cursor::new(a)).unwrap();
+        let b = TdmsFile::new(Cursor::new(b)).unwrap();
+
+        let options = DiffOptions::new()
+            .data_compare(true)
+            .absolute_tolerance(0.01)
+            .max_mismatches(1);
+        let report = diff(&a, &b, &options).unwrap();
+        let property = |name: &str, a: Option<&str>, b: Option<&str>| PropertyDiff {
+            path: "/'group'".to_string(),
+            name: name.to_string(),
+            a: a.map(|value| DataType::TdmsString(value.to_string())),
+            b: b.map(|value| DataType::TdmsString(value.to_string())),
+        };
+        let expected = DiffReport {
+            only_in_a: vec!["/'group'/'only a'".to_string()],
+            only_in_b: vec!["/'group'/'only b'".to_string()],
+            properties: vec![
+                property("operator", Some("ann"), None),
+                property("site", None, Some("lab")),
+            ],
+            lengths: vec![LengthMismatch {
+                path: COUNT.to_string(),
+                a: 3,
+                b: 4,
+            }],
+            data_types: Vec::new(),
+            values: vec![
+                ValueDiff {
+                    path: VOLTS.to_string(),
+                    mismatches: 2,
+                    first_indices: vec![1],
+                },
+                ValueDiff {
+                    path: TIME.to_string(),
+                    mismatches: 1,
+                    first_indices: vec![2],
+                },
+            ],
+        };
+        assert_eq!(report, expected);
+
+        let lines = [
+            "Only in a:\t/'group'/'only a'",
+            "Only in b:\t/'group'/'only b'",
+            "Property:\t/'group' operator: \"ann\" != <missing>",
+            "Property:\t/'group' site: <missing> != \"lab\"",
+            "Length:\t/'group'/'count': 3 != 4",
+            "Values:\t/'group'/'volts': 2 differ, first at 1",
+            "Values:\t/'group'/'time': 1 differ, first at 2",
+        ];
+        assert_eq!(report.to_string(), lines.join("\n") + "\n");
+
+        let report = diff(&a, &a, &options).unwrap();
+        assert!(report.is_empty());
+        assert_eq!(report.to_string(), "No differences\n");
+    }
+
+    #[test]
+    fn channels_of_differing_types_are_reported() {
+        let file = |data| {
+            let bytes = TdmsBuilder::new()
+                .segment(SegmentBuilder::new().channel(VOLTS, data))
+                .build();
+            TdmsFile::new(Cursor::new(bytes)).unwrap()
+        };
+        let a = file(DataTypeVec::Double(vec![1.0]));
+        let b = file(DataTypeVec::Float(vec![1.0]));
+        let report = diff(&a, &b, &DiffOptions::new().data_compare(true)).unwrap();
+        let expected = TypeMismatch {
+            path: VOLTS.to_string(),
+            a: DataTypeRaw::DoubleFloat,
+            b: DataTypeRaw::SingleFloat,
+        };
+        assert_eq!(report.data_types, [expected]);
+        assert!(report.values.is_empty());
+        assert_eq!(
+            report.to_string(),
+            "Data type:\t/'group'/'volts': DoubleFloat != SingleFloat\n"
+        );
+    }
+}
@@ -0,0 +1,364 @@
+use std::io::{Read, Seek, SeekFrom};
+
+use crate::tdms_datatypes::read_raw_pair;
+use crate::tdms_error::{Result, TdmsError};
+use crate::tdms_path::ObjectPathRef;
+use crate::{ReadPair, TdmsFile, HEADER_LEN, STREAM_BLOCK_VALUES};
+
+/// Raw data of whole files is hashed in blocks of this many bytes
+const DIGEST_BLOCK_BYTES: u64 = 1024 * 1024;
+
+/// The algorithm a digest of raw data is computed with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DigestAlgo {
+    /// SHA-256, as in FIPS 180-4, computed without any extra dependency
+    Sha256,
+}
+
+/// A digest being computed with one of the algorithms
+pub(crate) enum Hasher {
+    Sha256(Sha256),
+}
+
+impl Hasher {
+    pub(crate) fn new(algo: DigestAlgo) -> Hasher {
+        match algo {
+            DigestAlgo::Sha256 => Hasher::Sha256(Sha256::new()),
+        }
+    }
+
+    pub(crate) fn update(&mut self, bytes: &[u8]) {
+        match self {
+            Hasher::Sha256(sha256) => sha256.update(bytes),
+        }
+    }
+
+    pub(crate) fn finish(self) -> [u8; 32] {
+        match self {
+            Hasher::Sha256(sha256) => sha256.finish(),
+        }
+    }
+}
+
+/// CRC-32 of each byte value, for the reflected polynomial 0xEDB88320
+const CRC32_TABLE: [u32; 256] = crc32_table();
+
+const fn crc32_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+/// Add bytes to a CRC-32, which starts from !0 and is inverted once all its bytes are added
+fn crc32_update(mut crc: u32, bytes: &[u8]) -> u32 {
+    for byte in bytes {
+        crc = CRC32_TABLE[((crc ^ *byte as u32) & 0xff) as usize] ^ (crc >> 8);
+    }
+    crc
+}
+
+const SHA256_K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// A SHA-256 digest computed a piece at a time
+pub(crate) struct Sha256 {
+    state: [u32; 8],
+    block: [u8; 64],
+    block_len: usize,
+    total_len: u64, // bytes hashed so far
+}
+
+impl Sha256 {
+    fn new() -> Sha256 {
+        Sha256 {
+            state: [
+                0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+                0x5be0cd19,
+            ],
+            block: [0; 64],
+            block_len: 0,
+            total_len: 0,
+        }
+    }
+
+    fn update(&mut self, mut bytes: &[u8]) {
+        self.total_len += bytes.len() as u64;
+        while !bytes.is_empty() {
+            let taken = (64 - self.block_len).min(bytes.len());
+            self.block[self.block_len..self.block_len + taken].copy_from_slice(&bytes[..taken]);
+            self.block_len += taken;
+            bytes = &bytes[taken..];
+            if self.block_len == 64 {
+                self.compress();
+                self.block_len = 0;
+            }
+        }
+    }
+
+    fn finish(mut self) -> [u8; 32] {
+        let bit_len = self.total_len.wrapping_mul(8);
+        self.update(&[0x80]);
+        while self.block_len != 56 {
+            self.update(&[0]);
+        }
+        self.update(&bit_len.to_be_bytes());
+
+        let mut digest = [0u8; 32];
+        for (bytes, word) in digest.chunks_exact_mut(4).zip(self.state) {
+            bytes.copy_from_slice(&word.to_be_bytes());
+        }
+        digest
+    }
+
+    /// Mix the full block into the state
+    fn compress(&mut self) {
+        let mut w = [0u32; 64];
+        for (word, bytes) in w.iter_mut().zip(self.block.chunks_exact(4)) {
+            *word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = self.state;
+        for (k, w) in SHA256_K.iter().zip(w) {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(*k)
+                .wrapping_add(w);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (state, word) in self.state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *state = state.wrapping_add(word);
+        }
+    }
+}
+
+impl<R: Read + Seek> TdmsFile<R> {
+    /// A digest of a channel's raw bytes, exactly as `load_raw_bytes` gives them but streamed
+    /// a block at a time, so channels of any size can be hashed e.g. to validate an archive.
+    /// Interleaved values are gathered as they're hashed, so the digest doesn't depend on how
+    /// the channel was laid out. The bytes are hashed as stored, not decoded, so the digest is
+    /// the same on any platform but differs between files storing the same values in big and
+    /// little endian.
+    pub fn channel_digest<'a, P: Into<ObjectPathRef<'a>>>(
+        &self,
+        path: P,
+        algo: DigestAlgo,
+    ) -> Result<[u8; 32]> {
+        let mut hasher = Hasher::new(algo);
+        self.stream_raw_bytes(path.into().as_str(), |bytes| hasher.update(bytes))?;
+        Ok(hasher.finish())
+    }
+
+    /// The CRC-32 of a channel's raw bytes, as used by zip and PNG, e.g. to detect bit rot or
+    /// check two channels hold the same data. Quicker than `channel_digest`, though not
+    /// suited to spotting deliberate changes. The bytes are streamed as channel_digest does.
+    pub fn channel_crc32<'a, P: Into<ObjectPathRef<'a>>>(&self, path: P) -> Result<u32> {
+        let mut crc = !0u32;
+        self.stream_raw_bytes(path.into().as_str(), |bytes| crc = crc32_update(crc, bytes))?;
+        Ok(!crc)
+    }
+
+    /// Pass a channel's raw bytes to f a block at a time, as `raw_chunks` gives them but with
+    /// large chunks of fixed size values split into blocks
+    fn stream_raw_bytes(&self, path: &str, mut f: impl FnMut(&[u8])) -> Result<()> {
+        let object_map = self.tdms_map.object_map(path)?;
+        if self.tdms_map.options.metadata_only {
+            return Err(TdmsError::MetadataOnly);
+        }
+
+        let path = &object_map.last_object.object_path;
+        let value_size = object_map
+            .last_object
+            .raw_data_type
+            .and_then(|data_type| data_type.size().ok());
+        let mut reader = self.readers.take()?;
+        for chunk in object_map.read_map.iter().flat_map(ReadPair::chunks) {
+            for piece in chunk.pieces(value_size, STREAM_BLOCK_VALUES) {
+                f(&read_raw_pair(path, &piece, &mut *reader)?);
+            }
+        }
+        Ok(())
+    }
+
+    /// A digest of all the file's raw data, each segment's from its raw data offset to the
+    /// next segment, in file order, e.g. to find files holding the same data whatever their
+    /// meta data. As with `channel_digest` the bytes are hashed as stored. A final segment
+    /// cut short is hashed as far as the file goes.
+    pub fn file_raw_digest(&self, algo: DigestAlgo) -> Result<[u8; 32]> {
+        let mut hasher = Hasher::new(algo);
+        let mut reader = self.readers.take()?;
+        let mut block = Vec::new();
+        for segment_index in 0..self.tdms_map.segments.len() {
+            let segment = &self.tdms_map.segments[segment_index];
+            let start = segment.start_index + HEADER_LEN + segment.raw_data_offset;
+            let mut remaining = self.segment_raw_data_size(segment_index)?;
+            reader.seek(SeekFrom::Start(start))?;
+            while remaining > 0 {
+                block.clear();
+                let len = remaining.min(DIGEST_BLOCK_BYTES);
+                (&mut *reader).take(len).read_to_end(&mut block)?;
+                if block.is_empty() {
+                    break;
+                }
+                hasher.update(&block);
+                remaining -= block.len() as u64;
+            }
+        }
+        Ok(hasher.finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tdms_datatypes::{DataTypeRaw, DataTypeVec};
+    use crate::test_util::{ramp, SegmentBuilder, TdmsBuilder};
+    use std::io::Cursor;
+
+    fn sha256(pieces: &[&[u8]]) -> [u8; 32] {
+        let mut sha256 = Sha256::new();
+        for piece in pieces {
+            sha256.update(piece);
+        }
+        sha256.finish()
+    }
+
+    fn sha256_hex(pieces: &[&[u8]]) -> String {
+        sha256(pieces)
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    #[test]
+    fn sha256_known_answers() {
+        assert_eq!(
+            sha256_hex(&[b""]),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            sha256_hex(&[b"abc"]),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        // 448 bits, so the length no longer fits in the message's last block
+        assert_eq!(
+            sha256_hex(&[b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"]),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+    }
+
+    #[test]
+    fn sha256_of_pieces_matches_the_whole() {
+        let message: Vec<u8> = (0..1000u32).map(|i| (i * 7) as u8).collect();
+        let whole = sha256(&[&message]);
+        let (a, rest) = message.split_at(1);
+        let (b, rest) = rest.split_at(63);
+        let (c, d) = rest.split_at(130);
+        assert_eq!(sha256(&[a, b, c, d]), whole);
+
+        // A million "a"s fed in across block boundaries
+        let thousand = [b'a'; 1000];
+        assert_eq!(
+            sha256_hex(&[&thousand[..]; 1000]),
+            "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"
+        );
+    }
+
+    #[test]
+    fn channel_digest_is_independent_of_layout() {
+        let file = |interleaved| {
+            let bytes = TdmsBuilder::new()
+                .segment(
+                    SegmentBuilder::new()
+                        .channel("/'group'/'a'", ramp(DataTypeRaw::I32, 0, 100))
+                        .channel("/'group'/'b'", ramp(DataTypeRaw::DoubleFloat, 0, 100))
+                        .interleaved(interleaved),
+                )
+                .build();
+            TdmsFile::new(Cursor::new(bytes)).unwrap()
+        };
+        let (contiguous, interleaved) = (file(false), file(true));
+        for path in ["/'group'/'a'", "/'group'/'b'"] {
+            let digest = contiguous.channel_digest(path, DigestAlgo::Sha256).unwrap();
+            let raw_bytes = contiguous.load_raw_bytes(path).unwrap();
+            assert_eq!(digest, sha256(&[&raw_bytes]));
+            assert_eq!(
+                interleaved
+                    .channel_digest(path, DigestAlgo::Sha256)
+                    .unwrap(),
+                digest
+            );
+        }
+    }
+
+    #[test]
+    fn crc32_check_value() {
+        assert_eq!(!crc32_update(!0, b"123456789"), 0xCBF4_3926);
+        assert_eq!(
+            !crc32_update(crc32_update(!0, b"1234"), b"56789"),
+            0xCBF4_3926
+        );
+        assert_eq!(!crc32_update(!0, b""), 0);
+    }
+
+    #[test]
+    fn channel_crc32_spots_changed_data() {
+        let check = DataTypeVec::U8(b"123456789".to_vec());
+        let bytes = TdmsBuilder::new()
+            .segment(
+                SegmentBuilder::new()
+                    .channel("/'group'/'check'", check.clone())
+                    .channel("/'group'/'copy'", check)
+                    .channel("/'group'/'changed'", DataTypeVec::U8(b"123456780".to_vec())),
+            )
+            .build();
+        let tdms = TdmsFile::new(Cursor::new(bytes)).unwrap();
+
+        let crc = tdms.channel_crc32("/'group'/'check'").unwrap();
+        assert_eq!(crc, 0xCBF4_3926);
+        assert_eq!(tdms.channel_crc32("/'group'/'copy'").unwrap(), crc);
+        assert_ne!(tdms.channel_crc32("/'group'/'changed'").unwrap(), crc);
+    }
+}
@@ -2,6 +2,8 @@ use std::fmt;
 use std::io;
 use std::string;
 
+use crate::tdms_datatypes::DataTypeRaw;
+
 /// Errors propagated either from low level read operations, or from malformed
 /// data in the file
 #[derive(Debug)]
@@ -11,17 +13,180 @@ pub enum TdmsError {
     NoPreviousObject,
     StringSizeNotDefined,
     RawDataTypeNotFound,
-    ChannelNotFound,
+    ChannelNotFound {
+        path: String,
+        closest: Vec<String>,
+    },
     ObjectHasNoRawData,
+    MalformedSegment,
+    ReadPairOutOfBounds {
+        path: String,
+        start_index: u64,
+        no_values: u64,
+    },
+    InvalidObjectPath(String),
+    MetadataOnly,
+    UnsupportedDataType(DataTypeRaw),
+    MissingProperty {
+        path: String,
+        property: String,
+    },
+    ChannelLengthMismatch {
+        path: String,
+        expected: usize,
+        found: usize,
+    },
+    UnsupportedExportType {
+        path: String,
+        data_type: DataTypeRaw,
+    },
+    InvalidSegmentTag {
+        found: u32,
+    },
+    MissingValueCount {
+        path: String,
+    },
+    NotNumeric {
+        path: String,
+        data_type: DataTypeRaw,
+    },
+    AliasInUse {
+        alias: String,
+        path: String,
+    },
+    SegmentOutOfRange {
+        index: usize,
+        no_segments: usize,
+    },
+    ChannelDataTypeMismatch {
+        expected: DataTypeRaw,
+        found: DataTypeRaw,
+    },
+    /// Loading a channel would take needed bytes, more than the max_load_bytes limit
+    LoadTooLarge {
+        needed: u64,
+        limit: u64,
+    },
+    /// The lead in of the segment at first gives the next segment at second, inside it
+    OverlappingSegments {
+        first: u64,
+        second: u64,
+    },
+    /// A segment's version is neither TDMS 2.0 nor 1.0, or is 1.0 when parsing strictly
+    UnsupportedVersion {
+        version: u32,
+    },
+    /// The segment at start_index runs past the end of the file
+    TruncatedSegment {
+        start_index: u64,
+    },
+    DuplicateProperty {
+        path: String,
+        property: String,
+    },
+    UnexpectedIndexLength {
+        path: String,
+        index_info_len: u32,
+    },
+    /// A segment's objects ended at actual rather than expected, where its raw data starts
+    MetadataLengthMismatch {
+        expected: u64,
+        actual: u64,
+    },
+    /// Raw data laid out in a way that can't be read, described by the string
+    UnsupportedLayout(String),
+    /// A property was asked for as the type expected but holds a value of type found
+    WrongPropertyType {
+        expected: &'static str,
+        found: DataTypeRaw,
+    },
+    /// The string isn't the name of a data type
+    UnknownDataTypeName(String),
+    /// Loading the channel at path would take bytes of memory, more than can be allocated on
+    /// this platform
+    ChannelTooLargeForPlatform {
+        path: String,
+        bytes: u64,
+    },
+    /// The value at index of the timestamp channel at path can't be represented as a date time
+    TimestampOutOfRange {
+        path: String,
+        index: u64,
+    },
+    /// A chunk of strings being written holds bytes of string data, more than the u32 offsets
+    /// in its offsets table can mark
+    StringChunkTooLarge {
+        bytes: u64,
+    },
+    /// Loading the channel at path failed, as reported by `TdmsFile::load_all_channels`
+    LoadFailed {
+        path: String,
+        source: Box<TdmsError>,
+    },
+    /// A read failed part way through the file, with where it was reading. The offset is the
+    /// byte position in the file being read, a .tdms_index file while mapping from one.
+    ReadFailed {
+        object: Option<String>,
+        segment: Option<usize>,
+        offset: u64,
+        source: Box<TdmsError>,
+    },
+    #[cfg(feature = "hdf5")]
+    Hdf5(hdf5::Error),
 }
 
 pub type Result<T> = std::result::Result<T, TdmsError>;
 
+impl TdmsError {
+    /// Attach where a read failed. Context already present is kept and any missing parts
+    /// filled in, so wrapping at several levels gives a single ReadFailed.
+    pub(crate) fn with_context(
+        self,
+        object: Option<&str>,
+        segment: Option<usize>,
+        offset: u64,
+    ) -> TdmsError {
+        match self {
+            TdmsError::ReadFailed {
+                object: inner_object,
+                segment: inner_segment,
+                offset,
+                source,
+            } => TdmsError::ReadFailed {
+                object: inner_object.or_else(|| object.map(String::from)),
+                segment: inner_segment.or(segment),
+                offset,
+                source,
+            },
+            source => TdmsError::ReadFailed {
+                object: object.map(String::from),
+                segment,
+                offset,
+                source: Box::new(source),
+            },
+        }
+    }
+
+    /// The underlying error, looking through any read or load context
+    pub fn root_cause(&self) -> &TdmsError {
+        match self {
+            TdmsError::ReadFailed { source, .. } | TdmsError::LoadFailed { source, .. } => {
+                source.root_cause()
+            }
+            other => other,
+        }
+    }
+}
+
 impl std::error::Error for TdmsError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match *self {
             TdmsError::Io(ref e) => Some(e),
             TdmsError::FromUtf8(ref e) => Some(e),
+            TdmsError::ReadFailed { ref source, .. } => Some(source.as_ref()),
+            TdmsError::LoadFailed { ref source, .. } => Some(source.as_ref()),
+            #[cfg(feature = "hdf5")]
+            TdmsError::Hdf5(ref e) => Some(e),
             _ => None,
         }
     }
@@ -46,12 +211,115 @@ impl fmt::Display for TdmsError {
             TdmsError::RawDataTypeNotFound => {
                 write!(f, "The parsed u32 did not match a known data type")?
             },
-            TdmsError::ChannelNotFound => {
-                write!(f, "The requested channel is not in the channel list, ensure special characters are correctly escaped")?
+            TdmsError::ChannelNotFound { path, closest } => {
+                write!(f, "The requested channel {:?} is not in the channel list, ensure special characters are correctly escaped", path)?;
+                if !closest.is_empty() {
+                    write!(f, ". Closest matches: {:?}", closest)?
+                }
             },
             TdmsError::ObjectHasNoRawData => {
                 write!(f, "The requested object does not contain any raw data")?
             },
+            TdmsError::MalformedSegment => {
+                write!(f, "Segment sizes are inconsistent with the objects it contains, data may be malformed")?
+            },
+            TdmsError::ReadPairOutOfBounds { path, start_index, no_values } => {
+                write!(f, "Reading {} values at index {} for {:?} ran past the end of the file, the raw data offset may be malformed", no_values, start_index, path)?
+            },
+            TdmsError::InvalidObjectPath(path) => {
+                write!(f, "{:?} is not a valid object path, expected the form /'Group'/'Channel'", path)?
+            },
+            TdmsError::MetadataOnly => {
+                write!(f, "The file was opened metadata only, no raw data can be loaded")?
+            },
+            TdmsError::UnsupportedDataType(data_type) => {
+                write!(f, "Decoding {:?} data is not supported, it can still be loaded as raw bytes", data_type)?
+            },
+            TdmsError::MissingProperty { path, property } => {
+                write!(f, "{:?} has no usable {:?} property", path, property)?
+            },
+            TdmsError::ChannelLengthMismatch { path, expected, found } => {
+                write!(f, "{:?} has {} values where {} were expected to match the other channels", path, found, expected)?
+            },
+            TdmsError::UnsupportedExportType { path, data_type } => {
+                write!(f, "{:?} holds {:?} data which can't be exported in this format", path, data_type)?
+            },
+            TdmsError::InvalidSegmentTag { found } => {
+                write!(f, "Expected a segment to start with \"TDSm\" or \"TDSh\" but found {:?}, this may not be a TDMS file", String::from_utf8_lossy(&found.to_le_bytes()))?
+            },
+            TdmsError::MissingValueCount { path } => {
+                write!(f, "{:?} has raw data but its number of values was never given, data may be malformed", path)?
+            },
+            TdmsError::NotNumeric { path, data_type } => {
+                write!(f, "{:?} holds {:?} data which has no numeric value", path, data_type)?
+            },
+            TdmsError::AliasInUse { alias, path } => {
+                write!(f, "The alias {:?} is already given to {:?}", alias, path)?
+            },
+            TdmsError::SegmentOutOfRange { index, no_segments } => {
+                write!(f, "There is no segment {}, the file has {} segments", index, no_segments)?
+            },
+            TdmsError::ChannelDataTypeMismatch { expected, found } => {
+                write!(f, "Expected {:?} data but found {:?}", expected, found)?
+            },
+            TdmsError::LoadTooLarge { needed, limit } => {
+                write!(f, "Loading would take {} bytes, more than the limit of {}", needed, limit)?
+            },
+            TdmsError::OverlappingSegments { first, second } => {
+                write!(f, "The segment at {} gives the next segment at {}, overlapping it", first, second)?
+            },
+            TdmsError::UnsupportedVersion { version } => {
+                write!(f, "TDMS version {} isn't supported", version)?
+            },
+            TdmsError::TruncatedSegment { start_index } => {
+                write!(f, "The segment at {} runs past the end of the file", start_index)?
+            },
+            TdmsError::DuplicateProperty { path, property } => {
+                write!(f, "{} lists the property {:?} more than once", path, property)?
+            },
+            TdmsError::UnexpectedIndexLength { path, index_info_len } => {
+                write!(f, "{} has a raw data index of unexpected length {}", path, index_info_len)?
+            },
+            TdmsError::MetadataLengthMismatch { expected, actual } => {
+                write!(f, "Meta data ends at {} but raw data starts at {}", actual, expected)?
+            },
+            TdmsError::UnsupportedLayout(layout) => {
+                write!(f, "Reading raw data with an {} is not supported", layout)?
+            },
+            TdmsError::WrongPropertyType { expected, found } => {
+                write!(f, "The property holds {:?} which can't be read as {}", found, expected)?
+            },
+            TdmsError::UnknownDataTypeName(name) => {
+                write!(f, "{:?} isn't the name of a data type", name)?
+            },
+            TdmsError::ChannelTooLargeForPlatform { path, bytes } => {
+                write!(f, "Loading {:?} would take {} bytes, more than this platform can allocate, read it in parts instead e.g. with raw_chunks or load_segment_data", path, bytes)?
+            },
+            TdmsError::TimestampOutOfRange { path, index } => {
+                write!(f, "Value {} of {:?} is outside the range of dates that can be represented", index, path)?
+            },
+            TdmsError::StringChunkTooLarge { bytes } => {
+                write!(f, "A chunk of strings holding {} bytes is more than its u32 offsets can mark, write the strings as several chunks", bytes)?
+            },
+            TdmsError::LoadFailed { path, source } => {
+                write!(f, "Loading {:?} failed: {}", path, source)?
+            },
+            TdmsError::ReadFailed { object, segment, offset, source } => {
+                write!(f, "Reading")?;
+                if let Some(object) = object {
+                    write!(f, " {:?}", object)?
+                }
+                match (object, segment) {
+                    (Some(_), Some(segment)) => write!(f, " in segment {}", segment)?,
+                    (None, Some(segment)) => write!(f, " segment {}", segment)?,
+                    _ => {}
+                }
+                write!(f, " failed at byte {}: {}", offset, source)?
+            },
+            #[cfg(feature = "hdf5")]
+            TdmsError::Hdf5(e) => {
+                write!(f, "HDF5 error: {}", e)?
+            },
         }
         Ok(())
     }
@@ -63,8 +331,147 @@ impl From<std::io::Error> for TdmsError {
     }
 }
 
+#[cfg(feature = "hdf5")]
+impl From<hdf5::Error> for TdmsError {
+    fn from(err: hdf5::Error) -> TdmsError {
+        TdmsError::Hdf5(err)
+    }
+}
+
 impl From<std::string::FromUtf8Error> for TdmsError {
     fn from(err: std::string::FromUtf8Error) -> TdmsError {
         TdmsError::FromUtf8(err)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tdms_datatypes::{DataType, DataTypeRaw};
+    use crate::test_util::{ramp, SegmentBuilder, TdmsBuilder};
+    use crate::{TdmsFile, HEADER_LEN};
+    use std::io::{Cursor, Read, Seek, SeekFrom};
+
+    const VOLTS: &str = "/'group'/'volts'";
+
+    fn segment(start: u64) -> SegmentBuilder {
+        SegmentBuilder::new().channel(VOLTS, ramp(DataTypeRaw::DoubleFloat, start, 10))
+    }
+
+    /// A two segment file and the address of its second segment
+    fn two_segments(second: SegmentBuilder) -> (Vec<u8>, u64) {
+        let address = TdmsBuilder::new().segment(segment(0)).build().len() as u64;
+        let bytes = TdmsBuilder::new()
+            .segment(segment(0))
+            .segment(second)
+            .build();
+        (bytes, address)
+    }
+
+    #[test]
+    fn mapping_errors_give_the_segment_and_offset() {
+        let (bytes, address) = two_segments(segment(10).tag(*b"TDSx"));
+        let error = TdmsFile::new(Cursor::new(bytes)).err().unwrap();
+        match &error {
+            TdmsError::ReadFailed {
+                object: None,
+                segment: Some(1),
+                offset,
+                source,
+            } => {
+                assert_eq!(*offset, address + HEADER_LEN);
+                assert!(matches!(**source, TdmsError::InvalidSegmentTag { .. }));
+            }
+            e => panic!("{:?}", e),
+        }
+        let message = error.to_string();
+        let expected = format!(
+            "Reading segment 1 failed at byte {}: ",
+            address + HEADER_LEN
+        );
+        assert!(message.starts_with(&expected), "{}", message);
+    }
+
+    #[test]
+    fn meta_data_errors_give_the_object() {
+        let property = DataType::TdmsString("caf\u{e9}".to_string());
+        let (mut bytes, address) = two_segments(segment(10).property("note", property));
+        let at = bytes
+            .windows(2)
+            .position(|w| w == "\u{e9}".as_bytes())
+            .unwrap();
+        bytes[at + 1] = 0x20;
+
+        let error = TdmsFile::new(Cursor::new(bytes.clone())).err().unwrap();
+        match &error {
+            TdmsError::ReadFailed {
+                object: Some(object),
+                segment: Some(1),
+                offset,
+                ..
+            } => {
+                assert_eq!(object, VOLTS);
+                assert!((address..bytes.len() as u64).contains(offset));
+            }
+            e => panic!("{:?}", e),
+        }
+        assert!(matches!(error.root_cause(), TdmsError::FromUtf8(_)));
+        let expected = format!("Reading {:?} in segment 1 failed at byte ", VOLTS);
+        assert!(error.to_string().starts_with(&expected), "{}", error);
+    }
+
+    /// A reader whose reads from fail_at on fail, as a disk might
+    struct FailingReader {
+        inner: Cursor<Vec<u8>>,
+        fail_at: u64,
+    }
+
+    impl Read for FailingReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let available = self.fail_at.saturating_sub(self.inner.position());
+            if available == 0 {
+                return Err(std::io::Error::other("disk gone"));
+            }
+            let len = buf.len().min(available as usize);
+            self.inner.read(&mut buf[..len])
+        }
+    }
+
+    impl Seek for FailingReader {
+        fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    #[test]
+    fn load_errors_give_the_object_and_offset() {
+        let (bytes, address) = two_segments(segment(10));
+        let raw_data_start = bytes.len() as u64 - 80;
+        assert!(raw_data_start > address);
+        let reader = FailingReader {
+            inner: Cursor::new(bytes),
+            fail_at: raw_data_start,
+        };
+        let file = TdmsFile::new(reader).unwrap();
+
+        let error = file.load_data(VOLTS).err().unwrap();
+        match &error {
+            TdmsError::ReadFailed {
+                object: Some(object),
+                segment: Some(1),
+                offset,
+                ..
+            } => {
+                assert_eq!(object, VOLTS);
+                assert_eq!(*offset, raw_data_start);
+            }
+            e => panic!("{:?}", e),
+        }
+        assert!(matches!(error.root_cause(), TdmsError::Io(_)));
+        let expected = format!(
+            "Reading {:?} in segment 1 failed at byte {}: IO error: disk gone",
+            VOLTS, raw_data_start
+        );
+        assert_eq!(error.to_string(), expected);
+    }
+}
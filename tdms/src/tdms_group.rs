@@ -0,0 +1,211 @@
+use std::io::{Read, Seek};
+
+use crate::tdms_datatypes::{read_data_vectors, DataType, DataTypeVec};
+use crate::tdms_error::{Result, TdmsError};
+use crate::tdms_path::{object_path, path_components};
+use crate::{ObjectMap, TdmsFile};
+
+/// A group's channels loaded as the columns of a table, in the order they appear in the file
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupTable {
+    names: Vec<String>,
+    columns: Vec<DataTypeVec>,
+    len: usize,
+}
+
+impl GroupTable {
+    /// The channel names, one per column
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// The channels' data, in the same order as the names
+    pub fn columns(&self) -> &[DataTypeVec] {
+        &self.columns
+    }
+
+    /// The number of rows, the length all the columns share
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the table has no rows
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The data of the channel with the given name
+    pub fn column(&self, name: &str) -> Option<&DataTypeVec> {
+        let index = self.names.iter().position(|column| column == name)?;
+        self.columns.get(index)
+    }
+
+    /// The values of row i, one per column, None if the index is out of range
+    pub fn row(&self, i: usize) -> Option<Vec<DataType>> {
+        self.columns.iter().map(|column| column.get(i)).collect()
+    }
+
+    /// Iterate over the rows in order, building each as it's reached
+    pub fn iter_rows(&self) -> impl Iterator<Item = Vec<DataType>> + '_ {
+        (0..self.len).filter_map(move |i| self.row(i))
+    }
+}
+
+impl<R: Read + Seek> TdmsFile<R> {
+    /// Load all of a group's channels with data as the columns of a table, reading the file
+    /// once for all of them. Channels of differing lengths fail with `ChannelLengthMismatch`
+    /// unless pad is set, which pads the shorter ones to the longest with zeros, empty strings
+    /// etc.
+    pub fn load_group(&self, group: &str, pad: bool) -> Result<GroupTable> {
+        let channels: Vec<(String, &ObjectMap)> = self
+            .tdms_map
+            .all_objects
+            .iter()
+            .filter(|(_, object_map)| !object_map.read_map.is_empty())
+            .filter_map(
+                |(path, object_map)| match path_components(path).as_slice() {
+                    [channel_group, name] if channel_group == group => {
+                        Some((name.clone(), object_map))
+                    }
+                    _ => None,
+                },
+            )
+            .collect();
+        if channels.is_empty() {
+            // Fail as for any missing object, otherwise the group just has no data
+            self.tdms_map.object_map(&object_path(Some(group), None))?;
+        }
+        if self.tdms_map.options.metadata_only {
+            return Err(TdmsError::MetadataOnly);
+        }
+
+        let object_maps: Vec<&ObjectMap> =
+            channels.iter().map(|(_, object_map)| *object_map).collect();
+        let mut columns = read_data_vectors(
+            &object_maps,
+            &mut *self.readers.take()?,
+            self.tdms_map.options.lossy_strings,
+        )?;
+
+        let len = if pad {
+            let len = columns.iter().map(DataTypeVec::len).max().unwrap_or(0);
+            columns.iter_mut().for_each(|column| column.pad(len));
+            len
+        } else {
+            let len = columns.first().map_or(0, DataTypeVec::len);
+            if let Some((object_map, column)) = object_maps
+                .iter()
+                .zip(&columns)
+                .find(|(_, column)| column.len() != len)
+            {
+                return Err(TdmsError::ChannelLengthMismatch {
+                    path: object_map.last_object.object_path.clone(),
+                    expected: len,
+                    found: column.len(),
+                });
+            }
+            len
+        };
+
+        Ok(GroupTable {
+            names: channels.into_iter().map(|(name, _)| name).collect(),
+            columns,
+            len,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tdms_datatypes::DataTypeRaw;
+    use crate::test_util::{ramp, SegmentBuilder, TdmsBuilder};
+    use std::io::Cursor;
+
+    const VOLTS: &str = "/'group'/'volts'";
+    const AMPS: &str = "/'group'/'amps'";
+
+    fn open(builder: TdmsBuilder) -> TdmsFile<Cursor<Vec<u8>>> {
+        TdmsFile::new(Cursor::new(builder.build())).unwrap()
+    }
+
+    #[test]
+    fn interleaved_group_loads_as_columns() {
+        let segment = |start| {
+            SegmentBuilder::new()
+                .channel(VOLTS, ramp(DataTypeRaw::DoubleFloat, start, 5))
+                .channel(AMPS, ramp(DataTypeRaw::I16, start + 100, 5))
+                .interleaved(true)
+        };
+        let tdms = open(
+            TdmsBuilder::new()
+                .segment(segment(0))
+                .segment(segment(5))
+                // Another group's channel isn't part of the table
+                .segment(
+                    SegmentBuilder::new().channel("/'other'/'x'", ramp(DataTypeRaw::U8, 0, 3)),
+                ),
+        );
+
+        let table = tdms.load_group("group", false).unwrap();
+        assert_eq!(table.names(), ["volts", "amps"]);
+        assert_eq!(table.len(), 10);
+        assert_eq!(
+            table.column("volts"),
+            Some(&ramp(DataTypeRaw::DoubleFloat, 0, 10))
+        );
+        assert_eq!(table.column("amps"), Some(&ramp(DataTypeRaw::I16, 100, 10)));
+        assert_eq!(table.column("x"), None);
+
+        assert_eq!(
+            table.row(7),
+            Some(vec![DataType::Double(7.0), DataType::I16(107)])
+        );
+        assert_eq!(table.row(10), None);
+        let rows: Vec<Vec<DataType>> = table.iter_rows().collect();
+        assert_eq!(rows.len(), 10);
+        assert_eq!(rows[0], vec![DataType::Double(0.0), DataType::I16(100)]);
+    }
+
+    #[test]
+    fn columns_of_differing_lengths_fail_unless_padded() {
+        let tdms = open(
+            TdmsBuilder::new().segment(
+                SegmentBuilder::new()
+                    .channel(VOLTS, ramp(DataTypeRaw::I32, 1, 4))
+                    .channel(AMPS, ramp(DataTypeRaw::I32, 1, 2)),
+            ),
+        );
+
+        match tdms.load_group("group", false) {
+            Err(TdmsError::ChannelLengthMismatch {
+                path,
+                expected,
+                found,
+            }) => {
+                assert_eq!(path, AMPS);
+                assert_eq!((expected, found), (4, 2));
+            }
+            other => panic!("expected a length mismatch, got {:?}", other),
+        }
+
+        let table = tdms.load_group("group", true).unwrap();
+        assert_eq!(table.len(), 4);
+        assert_eq!(
+            table.column("amps"),
+            Some(&DataTypeVec::I32(vec![1, 2, 0, 0]))
+        );
+    }
+
+    #[test]
+    fn missing_group_fails() {
+        let tdms = open(
+            TdmsBuilder::new()
+                .segment(SegmentBuilder::new().channel(VOLTS, ramp(DataTypeRaw::I32, 0, 4))),
+        );
+        match tdms.load_group("nothing", false) {
+            Err(TdmsError::ChannelNotFound { path, .. }) => assert_eq!(path, "/'nothing'"),
+            other => panic!("expected the group not to be found, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,259 @@
+use std::io::{Read, Seek};
+use std::path::Path;
+
+use hdf5::types::VarLenUnicode;
+use hdf5::{Group, H5Type, Location};
+use indexmap::IndexMap;
+
+use crate::tdms_datatypes::{DataType, DataTypeVec, TimeStamp, LABVIEW_EPOCH_OFFSET};
+use crate::tdms_error::{Result, TdmsError};
+use crate::tdms_path::path_components;
+use crate::{ObjectProperty, TdmsFile};
+
+/// Chunk length used when compressing without an explicit chunk size, compression needs a
+/// chunked layout
+const DEFAULT_CHUNK: usize = 65536;
+
+/// Options for `TdmsFile::export_hdf5`. By default datasets are contiguous and uncompressed.
+#[derive(Debug, Clone, Default)]
+pub struct Hdf5Options {
+    compression: Option<u8>,
+    chunk_size: Option<usize>,
+}
+
+impl Hdf5Options {
+    pub fn new() -> Hdf5Options {
+        Hdf5Options::default()
+    }
+
+    /// Compress datasets with gzip at the given level, 0 to 9
+    pub fn compression(mut self, level: Option<u8>) -> Self {
+        self.compression = level;
+        self
+    }
+
+    /// Store datasets in chunks of this many values
+    pub fn chunk_size(mut self, chunk_size: Option<usize>) -> Self {
+        self.chunk_size = chunk_size;
+        self
+    }
+}
+
+impl<R: Read + Seek> TdmsFile<R> {
+    /// Export to an HDF5 file, groups become HDF5 groups and channels datasets within them.
+    /// Properties become attributes, with the root object's on the file and timestamps as
+    /// ISO 8601 strings. Timestamp channels are stored as i64 nanoseconds since the Unix epoch.
+    /// Channels whose data can't be exported are skipped and their paths returned.
+    pub fn export_hdf5(&self, out_path: &Path, options: Hdf5Options) -> Result<Vec<String>> {
+        let file = hdf5::File::create(out_path)?;
+        let mut skipped = Vec::new();
+
+        let paths: Vec<String> = self.all_objects().into_iter().map(String::from).collect();
+        for path in paths {
+            let properties = self.object_properties_lazy(path.as_str())?.clone();
+            match path_components(&path).as_slice() {
+                [] => write_attributes(&file, &properties)?,
+                [group] => {
+                    let group = hdf5_group(&file, group)?;
+                    write_attributes(&group, &properties)?
+                }
+                [group, channel] => {
+                    let data = match self.load_data(path.as_str()) {
+                        Ok(data) => data,
+                        Err(TdmsError::UnsupportedDataType(_))
+                        | Err(TdmsError::ObjectHasNoRawData) => {
+                            skipped.push(path);
+                            continue;
+                        }
+                        Err(e) => return Err(e),
+                    };
+
+                    let group = hdf5_group(&file, group)?;
+                    match write_channel(&group, &hdf5_name(channel), &data, &options)? {
+                        Some(dataset) => write_attributes(&dataset, &properties)?,
+                        None => skipped.push(path),
+                    }
+                }
+                _ => skipped.push(path),
+            }
+        }
+        Ok(skipped)
+    }
+}
+
+/// HDF5 names can't be empty or contain slashes
+fn hdf5_name(name: &str) -> String {
+    match name.replace('/', "_") {
+        name if name.is_empty() => "_".to_string(),
+        name => name,
+    }
+}
+
+fn hdf5_group(file: &hdf5::File, name: &str) -> Result<Group> {
+    let name = hdf5_name(name);
+    if file.link_exists(&name) {
+        Ok(file.group(&name)?)
+    } else {
+        Ok(file.create_group(&name)?)
+    }
+}
+
+/// Write a channel as a dataset, returns None if the data type has no HDF5 equivalent
+fn write_channel(
+    group: &Group,
+    name: &str,
+    data: &DataTypeVec,
+    options: &Hdf5Options,
+) -> Result<Option<hdf5::Dataset>> {
+    let dataset = match data {
+        DataTypeVec::Void(_) => return Ok(None),
+        DataTypeVec::Boolean(datavec) => write_dataset(group, name, datavec, options)?,
+        DataTypeVec::I8(datavec) => write_dataset(group, name, datavec, options)?,
+        DataTypeVec::I16(datavec) => write_dataset(group, name, datavec, options)?,
+        DataTypeVec::I32(datavec) => write_dataset(group, name, datavec, options)?,
+        DataTypeVec::I64(datavec) => write_dataset(group, name, datavec, options)?,
+        DataTypeVec::U8(datavec) => write_dataset(group, name, datavec, options)?,
+        DataTypeVec::U16(datavec) => write_dataset(group, name, datavec, options)?,
+        DataTypeVec::U32(datavec) => write_dataset(group, name, datavec, options)?,
+        DataTypeVec::U64(datavec) => write_dataset(group, name, datavec, options)?,
+        DataTypeVec::Float(datavec) => write_dataset(group, name, datavec, options)?,
+        DataTypeVec::Double(datavec) => write_dataset(group, name, datavec, options)?,
+        DataTypeVec::TdmsString(datavec) => {
+            let strings: Vec<VarLenUnicode> = datavec.iter().map(|s| unicode(s)).collect();
+            write_dataset(group, name, &strings, options)?
+        }
+        DataTypeVec::TimeStamp(datavec) => {
+            let nanos: Vec<i64> = datavec.iter().map(unix_nanos).collect();
+            write_dataset(group, name, &nanos, options)?
+        }
+    };
+    Ok(Some(dataset))
+}
+
+fn write_dataset<T: H5Type>(
+    group: &Group,
+    name: &str,
+    data: &[T],
+    options: &Hdf5Options,
+) -> Result<hdf5::Dataset> {
+    let mut builder = group.new_dataset::<T>();
+    let chunk_size = match (options.chunk_size, options.compression) {
+        (Some(chunk_size), _) => Some(chunk_size),
+        (None, Some(_)) => Some(DEFAULT_CHUNK),
+        (None, None) => None,
+    };
+    if let Some(chunk_size) = chunk_size {
+        builder = builder.chunk(chunk_size.min(data.len()).max(1));
+    }
+    if let Some(level) = options.compression {
+        builder = builder.deflate(level);
+    }
+
+    let dataset = builder.shape(data.len()).create(name)?;
+    dataset.write(data)?;
+    Ok(dataset)
+}
+
+fn write_attributes(
+    location: &Location,
+    properties: &IndexMap<String, ObjectProperty>,
+) -> Result<()> {
+    for (name, property) in properties {
+        match property.value() {
+            DataType::Void(_) | DataType::Unknown { .. } => {}
+            DataType::Boolean(value) => write_attribute(location, name, value)?,
+            DataType::I8(value) => write_attribute(location, name, value)?,
+            DataType::I16(value) => write_attribute(location, name, value)?,
+            DataType::I32(value) => write_attribute(location, name, value)?,
+            DataType::I64(value) => write_attribute(location, name, value)?,
+            DataType::U8(value) => write_attribute(location, name, value)?,
+            DataType::U16(value) => write_attribute(location, name, value)?,
+            DataType::U32(value) => write_attribute(location, name, value)?,
+            DataType::U64(value) => write_attribute(location, name, value)?,
+            DataType::Float(value) => write_attribute(location, name, value)?,
+            DataType::Double(value) => write_attribute(location, name, value)?,
+            DataType::TdmsString(value) => write_attribute(location, name, &unicode(value))?,
+            DataType::TimeStamp(value) => {
+                let iso = match value.to_utc() {
+                    Some(time) => time.to_rfc3339(),
+                    None => format!("{}\t{}", value.epoch, value.radix),
+                };
+                write_attribute(location, name, &unicode(&iso))?
+            }
+        }
+    }
+    Ok(())
+}
+
+fn write_attribute<T: H5Type>(location: &Location, name: &str, value: &T) -> Result<()> {
+    location.new_attr::<T>().create(name)?.write_scalar(value)?;
+    Ok(())
+}
+
+/// HDF5 strings can't hold nul characters, they're dropped
+fn unicode(value: &str) -> VarLenUnicode {
+    value.replace('\0', "").parse().unwrap_or_default()
+}
+
+/// Nanoseconds since the Unix epoch, saturating at the limits of i64
+fn unix_nanos(timestamp: &TimeStamp) -> i64 {
+    let secs = timestamp.epoch as i128 - LABVIEW_EPOCH_OFFSET as i128;
+    let nanos = (timestamp.radix as u128 * 1_000_000_000) >> 64;
+    let total = secs * 1_000_000_000 + nanos as i128;
+    total.clamp(i64::MIN as i128, i64::MAX as i128) as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tdms_datatypes::DataTypeRaw;
+    use crate::test_util::{ramp, SegmentBuilder, TdmsBuilder};
+    use std::io::Cursor;
+
+    #[test]
+    fn exported_file_reads_back() {
+        let bytes = TdmsBuilder::new()
+            .segment(
+                SegmentBuilder::new()
+                    .object("/")
+                    .property("title", DataType::TdmsString("run 1".to_string()))
+                    .object("/'group'")
+                    .property("gain", DataType::Double(2.5))
+                    .channel("/'group'/'volts'", ramp(DataTypeRaw::DoubleFloat, 0, 100))
+                    .property("count", DataType::I32(7))
+                    .channel("/'group'/'ticks'", ramp(DataTypeRaw::U16, 3, 100))
+                    .channel("/'group'/'times'", ramp(DataTypeRaw::TimeStamp, 0, 100)),
+            )
+            .build();
+        let tdms = TdmsFile::new(Cursor::new(bytes)).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let out_path = dir.path().join("export.h5");
+        let options = Hdf5Options::new().compression(Some(4)).chunk_size(Some(16));
+        let skipped = tdms.export_hdf5(&out_path, options).unwrap();
+        assert!(skipped.is_empty());
+
+        let file = hdf5::File::open(&out_path).unwrap();
+        let title: VarLenUnicode = file.attr("title").unwrap().read_scalar().unwrap();
+        assert_eq!(title.as_str(), "run 1");
+
+        let group = file.group("group").unwrap();
+        let gain: f64 = group.attr("gain").unwrap().read_scalar().unwrap();
+        assert_eq!(gain, 2.5);
+
+        let volts = group.dataset("volts").unwrap();
+        let expected: Vec<f64> = (0..100).map(|v| v as f64).collect();
+        assert_eq!(volts.read_raw::<f64>().unwrap(), expected);
+        let count: i32 = volts.attr("count").unwrap().read_scalar().unwrap();
+        assert_eq!(count, 7);
+
+        let ticks = group.dataset("ticks").unwrap();
+        let expected: Vec<u16> = (3..103).collect();
+        assert_eq!(ticks.read_raw::<u16>().unwrap(), expected);
+
+        // Timestamps count whole seconds from the LabVIEW epoch
+        let times = group.dataset("times").unwrap().read_raw::<i64>().unwrap();
+        assert_eq!(times[0], -LABVIEW_EPOCH_OFFSET * 1_000_000_000);
+        assert_eq!(times[1] - times[0], 1_000_000_000);
+    }
+}
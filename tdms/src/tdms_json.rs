@@ -0,0 +1,278 @@
+use std::io::{Read, Seek};
+
+use indexmap::IndexMap;
+use serde::ser::{Serialize, SerializeMap, SerializeStruct, Serializer};
+use serde_json::{json, Map, Value};
+
+use crate::tdms_datatypes::DataType;
+use crate::tdms_diff::{DiffReport, LengthMismatch, PropertyDiff, TypeMismatch, ValueDiff};
+use crate::tdms_path::path_components;
+use crate::{ObjectProperty, TdmsFile};
+
+/// Values serialize as the matching JSON type. Timestamps become RFC 3339 strings, or the raw
+/// epoch and radix where they can't be represented, and unknown types their id and bytes.
+impl Serialize for DataType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            DataType::Void(_) => serializer.serialize_unit(),
+            DataType::Boolean(value) => serializer.serialize_bool(*value),
+            DataType::I8(value) => serializer.serialize_i8(*value),
+            DataType::I16(value) => serializer.serialize_i16(*value),
+            DataType::I32(value) => serializer.serialize_i32(*value),
+            DataType::I64(value) => serializer.serialize_i64(*value),
+            DataType::U8(value) => serializer.serialize_u8(*value),
+            DataType::U16(value) => serializer.serialize_u16(*value),
+            DataType::U32(value) => serializer.serialize_u32(*value),
+            DataType::U64(value) => serializer.serialize_u64(*value),
+            DataType::Float(value) => serializer.serialize_f32(*value),
+            DataType::Double(value) => serializer.serialize_f64(*value),
+            DataType::TdmsString(value) => serializer.serialize_str(value),
+            DataType::TimeStamp(value) => match value.to_utc() {
+                Some(time) => serializer.serialize_str(&time.to_rfc3339()),
+                None => {
+                    let mut map = serializer.serialize_map(Some(2))?;
+                    map.serialize_entry("epoch", &value.epoch)?;
+                    map.serialize_entry("radix", &value.radix)?;
+                    map.end()
+                }
+            },
+            DataType::Unknown { type_id, bytes } => {
+                let mut map = serializer.serialize_map(Some(2))?;
+                map.serialize_entry("type_id", type_id)?;
+                map.serialize_entry("bytes", bytes)?;
+                map.end()
+            }
+        }
+    }
+}
+
+/// A property serializes as its value, its name is the key it's stored under
+impl Serialize for ObjectProperty {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value().serialize(serializer)
+    }
+}
+
+/// A report serializes as its lists of differences, properties missing from a file as null
+impl Serialize for DiffReport {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut report = serializer.serialize_struct("DiffReport", 6)?;
+        report.serialize_field("only_in_a", &self.only_in_a)?;
+        report.serialize_field("only_in_b", &self.only_in_b)?;
+        report.serialize_field("properties", &self.properties)?;
+        report.serialize_field("lengths", &self.lengths)?;
+        report.serialize_field("data_types", &self.data_types)?;
+        report.serialize_field("values", &self.values)?;
+        report.end()
+    }
+}
+
+impl Serialize for PropertyDiff {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut diff = serializer.serialize_struct("PropertyDiff", 4)?;
+        diff.serialize_field("path", &self.path)?;
+        diff.serialize_field("name", &self.name)?;
+        diff.serialize_field("a", &self.a)?;
+        diff.serialize_field("b", &self.b)?;
+        diff.end()
+    }
+}
+
+impl Serialize for LengthMismatch {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut mismatch = serializer.serialize_struct("LengthMismatch", 3)?;
+        mismatch.serialize_field("path", &self.path)?;
+        mismatch.serialize_field("a", &self.a)?;
+        mismatch.serialize_field("b", &self.b)?;
+        mismatch.end()
+    }
+}
+
+/// Data types serialize as their names, e.g. "DoubleFloat"
+impl Serialize for TypeMismatch {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut mismatch = serializer.serialize_struct("TypeMismatch", 3)?;
+        mismatch.serialize_field("path", &self.path)?;
+        mismatch.serialize_field("a", &self.a.to_string())?;
+        mismatch.serialize_field("b", &self.b.to_string())?;
+        mismatch.end()
+    }
+}
+
+impl Serialize for ValueDiff {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut diff = serializer.serialize_struct("ValueDiff", 3)?;
+        diff.serialize_field("path", &self.path)?;
+        diff.serialize_field("mismatches", &self.mismatches)?;
+        diff.serialize_field("first_indices", &self.first_indices)?;
+        diff.end()
+    }
+}
+
+impl<R: Read + Seek> TdmsFile<R> {
+    /// The file's objects and their properties as JSON. The root object's properties are under
+    /// "properties" with groups under "groups", each group has its "properties" and
+    /// "channels", and each channel its "properties". Groups and channels are keyed by name.
+    pub fn metadata_json(&self) -> Value {
+        let mut root = json!({ "properties": {}, "groups": {} });
+        for (path, object_map) in &self.tdms_map.all_objects {
+            let properties = match self.properties_of(object_map) {
+                Ok(properties) => properties_json(properties),
+                Err(_) => Value::Null,
+            };
+            match path_components(path).as_slice() {
+                [] => root["properties"] = properties,
+                [group] => group_json(&mut root, group)["properties"] = properties,
+                [group, channel] => {
+                    group_json(&mut root, group)["channels"][channel.as_str()] =
+                        json!({ "properties": properties })
+                }
+                _ => {}
+            }
+        }
+        root
+    }
+}
+
+/// The entry for a group, created if this is the first time it's seen, e.g. a channel listed
+/// before its group
+fn group_json<'a>(root: &'a mut Value, group: &str) -> &'a mut Value {
+    root["groups"]
+        .as_object_mut()
+        .unwrap()
+        .entry(group)
+        .or_insert_with(|| json!({ "properties": {}, "channels": {} }))
+}
+
+fn properties_json(properties: &IndexMap<String, ObjectProperty>) -> Value {
+    let properties: Map<String, Value> = properties
+        .iter()
+        .map(|(name, property)| {
+            let value = serde_json::to_value(property).unwrap_or(Value::Null);
+            (name.clone(), value)
+        })
+        .collect();
+    Value::Object(properties)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tdms_datatypes::{DataTypeRaw, DataTypeVec, TimeStamp, LABVIEW_EPOCH_OFFSET};
+    use crate::test_util::{SegmentBuilder, TdmsBuilder};
+    use std::io::Cursor;
+
+    fn metadata_file() -> TdmsFile<Cursor<Vec<u8>>> {
+        let half_past_unix_epoch = TimeStamp {
+            epoch: LABVIEW_EPOCH_OFFSET,
+            radix: 1 << 63,
+        };
+        let bytes = TdmsBuilder::new()
+            .segment(
+                SegmentBuilder::new()
+                    .object("/")
+                    .property("name", DataType::TdmsString("run".to_string()))
+                    // A channel listed before its group
+                    .channel("/'group'/'volts'", DataTypeVec::I32(vec![1, 2]))
+                    .property("gain", DataType::Double(1.5))
+                    .property("count", DataType::U64(3))
+                    .property("start", DataType::TimeStamp(half_past_unix_epoch))
+                    .property("enabled", DataType::Boolean(true))
+                    .object("/'group'")
+                    .property("offset", DataType::I8(-1))
+                    .channel("/'group'/'amps'", DataTypeVec::I32(vec![3, 4])),
+            )
+            .build();
+        TdmsFile::new(Cursor::new(bytes)).unwrap()
+    }
+
+    #[test]
+    fn metadata_json_matches_snapshot() {
+        let expected = json!({
+            "properties": { "name": "run" },
+            "groups": {
+                "group": {
+                    "properties": { "offset": -1 },
+                    "channels": {
+                        "volts": {
+                            "properties": {
+                                "gain": 1.5,
+                                "count": 3,
+                                "start": "1970-01-01T00:00:00.500+00:00",
+                                "enabled": true
+                            }
+                        },
+                        "amps": { "properties": {} }
+                    }
+                }
+            }
+        });
+        assert_eq!(metadata_file().metadata_json(), expected);
+    }
+
+    #[test]
+    fn property_map_serializes_to_natural_types() {
+        let tdms = metadata_file();
+        let properties = tdms.channels()[0].properties(&tdms).unwrap();
+        let json = serde_json::to_value(properties).unwrap();
+        let keys: Vec<&str> = json
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        assert_eq!(keys, ["count", "enabled", "gain", "start"]);
+        assert!(json["gain"].is_f64());
+        assert!(json["count"].is_u64());
+        assert!(json["start"].is_string());
+        assert!(json["enabled"].is_boolean());
+    }
+
+    #[test]
+    fn unknown_values_serialize_as_their_type_and_bytes() {
+        let unknown = DataType::Unknown {
+            type_id: 0x99,
+            bytes: vec![1, 2],
+        };
+        let expected = json!({ "type_id": 0x99, "bytes": [1, 2] });
+        assert_eq!(serde_json::to_value(&unknown).unwrap(), expected);
+    }
+
+    #[test]
+    fn diff_reports_serialize_for_machines() {
+        let report = DiffReport {
+            only_in_a: vec!["/'a'".to_string()],
+            only_in_b: Vec::new(),
+            properties: vec![PropertyDiff {
+                path: "/".to_string(),
+                name: "gain".to_string(),
+                a: Some(DataType::Double(1.5)),
+                b: None,
+            }],
+            lengths: vec![LengthMismatch {
+                path: "/'a'/'x'".to_string(),
+                a: 3,
+                b: 4,
+            }],
+            data_types: vec![TypeMismatch {
+                path: "/'a'/'y'".to_string(),
+                a: DataTypeRaw::I32,
+                b: DataTypeRaw::DoubleFloat,
+            }],
+            values: vec![ValueDiff {
+                path: "/'a'/'z'".to_string(),
+                mismatches: 12,
+                first_indices: vec![0, 4],
+            }],
+        };
+        let expected = json!({
+            "only_in_a": ["/'a'"],
+            "only_in_b": [],
+            "properties": [{"path": "/", "name": "gain", "a": 1.5, "b": null}],
+            "lengths": [{"path": "/'a'/'x'", "a": 3, "b": 4}],
+            "data_types": [{"path": "/'a'/'y'", "a": "I32", "b": "DoubleFloat"}],
+            "values": [{"path": "/'a'/'z'", "mismatches": 12, "first_indices": [0, 4]}],
+        });
+        assert_eq!(serde_json::to_value(&report).unwrap(), expected);
+    }
+}
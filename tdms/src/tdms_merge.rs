@@ -0,0 +1,194 @@
+use std::io::{Read, Seek};
+
+use indexmap::IndexMap;
+
+use crate::tdms_datatypes::{DataTypeRaw, DataTypeVec};
+use crate::tdms_error::{Result, TdmsError};
+use crate::{ObjectProperty, TdmsFile};
+
+/// Channels combined from several files, keyed by path in the order they were first found
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergedChannels {
+    channels: IndexMap<String, DataTypeVec>,
+    properties: IndexMap<String, IndexMap<String, ObjectProperty>>,
+}
+
+impl MergedChannels {
+    /// The merged data of the channel at path
+    pub fn get(&self, path: &str) -> Option<&DataTypeVec> {
+        self.channels.get(path)
+    }
+
+    /// The merged properties of the channel at path. Each file's properties are added in
+    /// turn, so where files disagree the last file's value is kept, as a later segment's would
+    /// be within a file.
+    pub fn properties(&self, path: &str) -> Option<&IndexMap<String, ObjectProperty>> {
+        self.properties.get(path)
+    }
+
+    /// The paths of the merged channels
+    pub fn paths(&self) -> Vec<&str> {
+        self.channels.keys().map(String::as_str).collect()
+    }
+
+    /// Iterate over the channels' paths and merged data
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &DataTypeVec)> {
+        self.channels
+            .iter()
+            .map(|(path, data)| (path.as_str(), data))
+    }
+
+    /// The number of merged channels
+    pub fn len(&self) -> usize {
+        self.channels.len()
+    }
+
+    /// Returns true if none of the files had channels with data
+    pub fn is_empty(&self) -> bool {
+        self.channels.is_empty()
+    }
+
+    /// Take the merged data, keyed by path
+    pub fn into_map(self) -> IndexMap<String, DataTypeVec> {
+        self.channels
+    }
+}
+
+impl<R: Read + Seek> TdmsFile<R> {
+    /// Combine the channels of several files, such as one acquisition logged across many,
+    /// appending the data of channels with the same path in the order of the files and
+    /// gathering their properties. Channels missing from some files are merged from the files
+    /// that have them. Fails with
+    /// `ChannelDataTypeMismatch` before loading anything if a channel's type differs between
+    /// files.
+    pub fn merge(files: &[TdmsFile<R>]) -> Result<MergedChannels> {
+        let mut data_types: IndexMap<&str, DataTypeRaw> = IndexMap::new();
+        for file in files {
+            for (path, object_map) in &file.tdms_map.all_objects {
+                let data_type = match object_map.last_object.raw_data_type {
+                    Some(data_type) if !object_map.read_map.is_empty() => data_type,
+                    _ => continue,
+                };
+                let expected = *data_types.entry(path).or_insert(data_type);
+                if expected != data_type {
+                    return Err(TdmsError::ChannelDataTypeMismatch {
+                        expected,
+                        found: data_type,
+                    });
+                }
+            }
+        }
+
+        let mut channels: IndexMap<String, DataTypeVec> = IndexMap::new();
+        let mut properties: IndexMap<String, IndexMap<String, ObjectProperty>> = IndexMap::new();
+        for path in data_types.keys() {
+            let mut merged: Option<DataTypeVec> = None;
+            let mut merged_properties = IndexMap::new();
+            for file in files {
+                let has_data = match file.tdms_map.all_objects.get(*path) {
+                    Some(object_map) => !object_map.read_map.is_empty(),
+                    None => false,
+                };
+                if !has_data {
+                    continue;
+                }
+                let data = file.load_data(*path)?;
+                let object_map = file.tdms_map.object_map(path)?;
+                for (name, property) in file.properties_of(object_map)? {
+                    merged_properties.insert(name.clone(), property.clone());
+                }
+                match merged.as_mut() {
+                    Some(merged) => merged.extend(data)?,
+                    None => merged = Some(data),
+                }
+            }
+            if let Some(merged) = merged {
+                channels.insert(path.to_string(), merged);
+                properties.insert(path.to_string(), merged_properties);
+            }
+        }
+        Ok(MergedChannels {
+            channels,
+            properties,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tdms_datatypes::DataType;
+    use crate::test_util::{ramp, SegmentBuilder, TdmsBuilder};
+    use std::io::Cursor;
+
+    const VOLTS: &str = "/'rig'/'volts'";
+    const NOTES: &str = "/'rig'/'notes'";
+
+    fn text(value: &str) -> DataType {
+        DataType::TdmsString(value.to_string())
+    }
+
+    fn open(segment: SegmentBuilder) -> TdmsFile<Cursor<Vec<u8>>> {
+        let bytes = TdmsBuilder::new().segment(segment).build();
+        TdmsFile::new(Cursor::new(bytes)).unwrap()
+    }
+
+    #[test]
+    fn halves_of_a_channel_merge_in_file_order() {
+        let first = open(
+            SegmentBuilder::new()
+                .channel(VOLTS, ramp(DataTypeRaw::DoubleFloat, 0, 10))
+                .property("unit_string", text("V"))
+                .property("run", text("first")),
+        );
+        let second = open(
+            SegmentBuilder::new()
+                .channel(NOTES, ramp(DataTypeRaw::TdmsString, 0, 2))
+                .channel(VOLTS, ramp(DataTypeRaw::DoubleFloat, 10, 10))
+                .property("run", text("second"))
+                .property("gain", DataType::I32(2)),
+        );
+
+        let merged = TdmsFile::merge(&[first, second]).unwrap();
+        assert_eq!(merged.paths(), vec![VOLTS, NOTES]);
+        assert_eq!(
+            merged.get(VOLTS),
+            Some(&ramp(DataTypeRaw::DoubleFloat, 0, 20))
+        );
+        assert_eq!(
+            merged.get(NOTES),
+            Some(&ramp(DataTypeRaw::TdmsString, 0, 2))
+        );
+
+        // The second file's run replaces the first's
+        let properties = merged.properties(VOLTS).unwrap();
+        let values: Vec<(&str, &DataType)> = properties
+            .iter()
+            .map(|(name, property)| (name.as_str(), property.value()))
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                ("unit_string", &text("V")),
+                ("run", &text("second")),
+                ("gain", &DataType::I32(2)),
+            ]
+        );
+        assert!(merged.properties(NOTES).unwrap().is_empty());
+    }
+
+    #[test]
+    fn channels_of_differing_types_fail_to_merge() {
+        let doubles =
+            open(SegmentBuilder::new().channel(VOLTS, ramp(DataTypeRaw::DoubleFloat, 0, 10)));
+        let integers = open(SegmentBuilder::new().channel(VOLTS, ramp(DataTypeRaw::I32, 10, 10)));
+
+        match TdmsFile::merge(&[doubles, integers]) {
+            Err(TdmsError::ChannelDataTypeMismatch { expected, found }) => {
+                assert_eq!(expected, DataTypeRaw::DoubleFloat);
+                assert_eq!(found, DataTypeRaw::I32);
+            }
+            other => panic!("{:?}", other),
+        }
+    }
+}
@@ -0,0 +1,302 @@
+use std::path::PathBuf;
+
+/// Options controlling how a file is mapped and read, passed to `TdmsFile::open_with_options`.
+/// Built by chaining setters on the default, which matches `TdmsFile::open`, e.g.
+/// `ReadOptions::new().lossy_strings(true).repair_truncated(true)`.
+#[derive(Debug, Clone, Default)]
+pub struct ReadOptions {
+    pub(crate) lossy_strings: bool,
+    pub(crate) metadata_only: bool,
+    pub(crate) repair_truncated: bool,
+    pub(crate) index_file: Option<PathBuf>,
+    pub(crate) ignore_index_file: bool,
+    pub(crate) buffer_capacity: Option<usize>,
+    pub(crate) keep_unknown_properties: bool,
+    pub(crate) skip_unknown_types: bool,
+    pub(crate) defer_properties: bool,
+    pub(crate) tolerate_metadata_mismatch: bool,
+    pub(crate) skip_overlapping_segments: bool,
+    pub(crate) max_load_bytes: Option<u64>,
+    pub(crate) parse_mode: Option<ParseMode>,
+}
+
+/// How strictly files are held to the spec, overriding the options for handling each way a
+/// file can break it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Fail on anything breaking the spec, with where it was found
+    Strict,
+    /// Carry on wherever it's safe to, as with repair_truncated, tolerate_metadata_mismatch
+    /// and skip_overlapping_segments all set
+    Lenient,
+}
+
+impl ReadOptions {
+    pub fn new() -> ReadOptions {
+        ReadOptions::default()
+    }
+
+    /// Replace invalid UTF-8 in names, string properties and string channels with U+FFFD
+    /// rather than failing with a `FromUtf8` error
+    pub fn lossy_strings(mut self, lossy_strings: bool) -> Self {
+        self.lossy_strings = lossy_strings;
+        self
+    }
+
+    /// Only read objects and properties, no read maps are built and loading data fails with
+    /// `MetadataOnly`. Saves time and memory when only properties are of interest.
+    pub fn metadata_only(mut self, metadata_only: bool) -> Self {
+        self.metadata_only = metadata_only;
+        self
+    }
+
+    /// Recover the complete chunks of a final segment whose raw data was cut short, e.g. by a
+    /// writer that crashed before finishing it. Otherwise reading that segment's data fails.
+    pub fn repair_truncated(mut self, repair_truncated: bool) -> Self {
+        self.repair_truncated = repair_truncated;
+        self
+    }
+
+    /// Map the file from its `.tdms_index` companion, which holds the same meta data without
+    /// the raw data in between so is much quicker to walk. Data is still read from the file.
+    /// If None, a `.tdms_index` file beside the file is used if there is one.
+    pub fn index_file(mut self, index_file: Option<PathBuf>) -> Self {
+        self.index_file = index_file;
+        self
+    }
+
+    /// Walk the file itself even if there's a `.tdms_index` file beside it, e.g. if the index
+    /// may not match the file. An index_file given explicitly is still used.
+    pub fn ignore_index_file(mut self, ignore_index_file: bool) -> Self {
+        self.ignore_index_file = ignore_index_file;
+        self
+    }
+
+    /// Buffer reads from the file with this many bytes rather than the standard 8 KB. Larger
+    /// buffers mean fewer reads while mapping segments with large meta data, which helps most
+    /// where each read is slow e.g. on network filesystems. Raw data is read in large blocks
+    /// regardless.
+    pub fn buffer_capacity(mut self, buffer_capacity: Option<usize>) -> Self {
+        self.buffer_capacity = buffer_capacity;
+        self
+    }
+
+    /// Keep properties of types this crate doesn't know as `DataType::Unknown` rather than
+    /// failing to open the file. Their size isn't known, so the bytes up to where the next
+    /// property or object appears to start are taken as the value.
+    pub fn keep_unknown_properties(mut self, keep_unknown_properties: bool) -> Self {
+        self.keep_unknown_properties = keep_unknown_properties;
+        self
+    }
+
+    /// Keep objects whose raw data is of a type this crate doesn't know rather than failing to
+    /// open the file. They're listed in the file's objects without a data type or any data to
+    /// load, and logged as an `UnknownDataType` warning. Where the size of their data isn't
+    /// given the rest of the segment's raw data can't be placed, so isn't read either.
+    pub fn skip_unknown_types(mut self, skip_unknown_types: bool) -> Self {
+        self.skip_unknown_types = skip_unknown_types;
+        self
+    }
+
+    /// Skip over properties while mapping, only noting where each object's are listed, and
+    /// parse them when first asked for by any property accessor, see
+    /// `TdmsFile::object_properties_lazy`. Opens files with many properties much quicker.
+    /// wf_start_time and wf_increment are still read while mapping, waveform timing is
+    /// recorded from each segment's.
+    pub fn defer_properties(mut self, defer_properties: bool) -> Self {
+        self.defer_properties = defer_properties;
+        self
+    }
+
+    /// Carry on from where the raw data offset says a segment's raw data starts when its
+    /// objects end before that, logging a warning, rather than failing with
+    /// `MetadataLengthMismatch`. The objects may still have been misparsed.
+    pub fn tolerate_metadata_mismatch(mut self, tolerate_metadata_mismatch: bool) -> Self {
+        self.tolerate_metadata_mismatch = tolerate_metadata_mismatch;
+        self
+    }
+
+    /// Skip a segment whose lead in gives the next segment inside it, as left by a writer that
+    /// crashed and restarted, carrying on from the next segment tag found after it. The skipped
+    /// segments are listed by `TdmsFile::recovery_info`. Otherwise mapping fails with
+    /// `OverlappingSegments`. Only applies when walking the file itself, not an index file.
+    pub fn skip_overlapping_segments(mut self, skip_overlapping_segments: bool) -> Self {
+        self.skip_overlapping_segments = skip_overlapping_segments;
+        self
+    }
+
+    /// Fail with `LoadTooLarge` before allocating anything when loading a channel whole, with
+    /// `TdmsFile::load_data` and the like, would take more than this many bytes of memory, as
+    /// estimated by `TdmsFile::estimated_load_size`. Channels can still be streamed or
+    /// decimated, e.g. with `TdmsFile::load_envelope`.
+    pub fn max_load_bytes(mut self, max_load_bytes: Option<u64>) -> Self {
+        self.max_load_bytes = max_load_bytes;
+        self
+    }
+
+    /// Hold the file strictly to the spec or carry on past what breaks it wherever safe, see
+    /// `ParseMode`. What's carried past is listed by `TdmsFile::warnings`. If None each is
+    /// handled as the other options say.
+    pub fn parse_mode(mut self, parse_mode: Option<ParseMode>) -> Self {
+        self.parse_mode = parse_mode;
+        self
+    }
+
+    /// The options with those the parse mode overrides set accordingly
+    pub(crate) fn resolved(self) -> ReadOptions {
+        let tolerate = match self.parse_mode {
+            Some(ParseMode::Strict) => false,
+            Some(ParseMode::Lenient) => true,
+            None => return self,
+        };
+        ReadOptions {
+            repair_truncated: tolerate,
+            tolerate_metadata_mismatch: tolerate,
+            skip_overlapping_segments: tolerate,
+            ..self
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tdms_datatypes::{DataType, DataTypeRaw, DataTypeVec};
+    use crate::test_util::{ramp, SegmentBuilder, TdmsBuilder};
+    use crate::tests::open_bytes;
+    use crate::{TdmsError, HEADER_LEN, UNFINISHED_SEGMENT};
+    use std::io::Write;
+
+    const VOLTS: &str = "/'group'/'volts'";
+
+    fn volts(start: u64, len: usize) -> DataTypeVec {
+        ramp(DataTypeRaw::DoubleFloat, start, len)
+    }
+
+    /// A segment listing the volts channel with its values from start, and a source property
+    fn segment(start: u64, source: &str) -> SegmentBuilder {
+        SegmentBuilder::new()
+            .object("/'group'")
+            .channel(VOLTS, volts(start, 10))
+            .property("source", DataType::TdmsString(source.to_string()))
+    }
+
+    #[test]
+    fn lossy_strings_replaces_invalid_utf8() {
+        let mut bytes = TdmsBuilder::new().segment(segment(0, "caf\u{e9}")).build();
+        let at = bytes
+            .windows(2)
+            .position(|w| w == "\u{e9}".as_bytes())
+            .unwrap();
+        bytes[at..at + 2].copy_from_slice(&[0xFF, 0xFE]);
+
+        let (_path, file) = open_bytes(&bytes, ReadOptions::new());
+        let error = file.err().unwrap();
+        assert!(matches!(error.root_cause(), TdmsError::FromUtf8(_)));
+
+        let (_path, file) = open_bytes(&bytes, ReadOptions::new().lossy_strings(true));
+        let file = file.unwrap();
+        let source = file.get_property_as::<String>(VOLTS, "source").unwrap();
+        assert_eq!(source, "caf\u{FFFD}\u{FFFD}");
+        assert_eq!(file.load_data(VOLTS).unwrap(), volts(0, 10));
+    }
+
+    #[test]
+    fn metadata_only_keeps_properties_but_not_data() {
+        let bytes = TdmsBuilder::new().segment(segment(0, "rig")).build();
+        let (_path, file) = open_bytes(&bytes, ReadOptions::new().metadata_only(true));
+        let file = file.unwrap();
+        let source = file.get_property_as::<String>(VOLTS, "source").unwrap();
+        assert_eq!(source, "rig");
+        let error = file.load_data(VOLTS).err().unwrap();
+        assert!(matches!(error.root_cause(), TdmsError::MetadataOnly));
+        assert!(file.estimated_load_size(VOLTS).is_err());
+
+        let (_path, file) = open_bytes(&bytes, ReadOptions::new());
+        assert_eq!(file.unwrap().load_data(VOLTS).unwrap(), volts(0, 10));
+    }
+
+    #[test]
+    fn repair_truncated_recovers_complete_chunks() {
+        let mut bytes = TdmsBuilder::new()
+            .segment(segment(0, "rig"))
+            .segment(segment(10, "rig").chunks(3))
+            .build();
+        // The writer stopped part way through the last chunk
+        bytes.truncate(bytes.len() - 12);
+
+        let (_path, file) = open_bytes(&bytes, ReadOptions::new());
+        let file = file.unwrap();
+        assert!(file.info().truncated);
+        let error = file.load_data(VOLTS).err().unwrap();
+        assert!(matches!(
+            error.root_cause(),
+            TdmsError::ReadPairOutOfBounds { .. }
+        ));
+
+        let (_path, file) = open_bytes(&bytes, ReadOptions::new().repair_truncated(true));
+        let file = file.unwrap();
+        assert_eq!(file.segments()[1].no_chunks, 2);
+        let expected = (0..20).chain(10..20).map(f64::from).collect();
+        assert_eq!(
+            file.load_data(VOLTS).unwrap(),
+            DataTypeVec::Double(expected)
+        );
+        assert_eq!(file.estimated_load_size(VOLTS).unwrap(), 30 * 8);
+    }
+
+    #[test]
+    fn index_file_maps_from_the_index() {
+        let bytes = TdmsBuilder::new().segment(segment(0, "file!")).build();
+        // An index whose meta data differs from the file's, to tell which was mapped
+        let index = TdmsBuilder::new()
+            .segment(segment(0, "index").tag(*b"TDSh"))
+            .build();
+        let meta_data_len = u64::from_le_bytes(index[20..28].try_into().unwrap());
+        let mut index_file = tempfile::NamedTempFile::new().unwrap();
+        index_file
+            .write_all(&index[..(HEADER_LEN + meta_data_len) as usize])
+            .unwrap();
+
+        let (_path, file) = open_bytes(&bytes, ReadOptions::new());
+        let file = file.unwrap();
+        assert!(!file.mapped_from_index());
+        let source = file.get_property_as::<String>(VOLTS, "source").unwrap();
+        assert_eq!(source, "file!");
+
+        let options = ReadOptions::new().index_file(Some(index_file.path().to_path_buf()));
+        let (_path, file) = open_bytes(&bytes, options);
+        let file = file.unwrap();
+        assert!(file.mapped_from_index());
+        let source = file.get_property_as::<String>(VOLTS, "source").unwrap();
+        assert_eq!(source, "index");
+        assert_eq!(file.load_data(VOLTS).unwrap(), volts(0, 10));
+    }
+
+    #[test]
+    fn lenient_parsing_maps_an_unfinished_last_segment() {
+        // The writer has written two chunks of the last segment and part of a third
+        let unfinished = segment(10, "rig")
+            .chunks(3)
+            .next_segment_offset(Some(UNFINISHED_SEGMENT));
+        let mut bytes = TdmsBuilder::new()
+            .segment(segment(0, "rig"))
+            .segment(unfinished)
+            .build();
+        bytes.truncate(bytes.len() - 12);
+
+        for mode in [None, Some(ParseMode::Lenient), Some(ParseMode::Strict)] {
+            let (_path, file) = open_bytes(&bytes, ReadOptions::new().parse_mode(mode));
+            let file = file.unwrap();
+            assert!(file.warnings().is_empty(), "{:?}", mode);
+            assert_eq!(file.segments()[1].no_chunks, 2);
+            assert_eq!(file.estimated_load_size(VOLTS).unwrap(), 30 * 8);
+            assert_eq!(file.channel_total_bytes(VOLTS).unwrap(), 30 * 8);
+            let expected = (0..20).chain(10..20).map(f64::from).collect();
+            assert_eq!(
+                file.load_data(VOLTS).unwrap(),
+                DataTypeVec::Double(expected)
+            );
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use byteorder::{BE, LE};
+use indexmap::IndexMap;
+
+use crate::tdms_datatypes::TocProperties;
+use crate::tdms_error::{Result, TdmsError};
+use crate::tdms_options::ReadOptions;
+use crate::{
+    parse_objects, update_live_objects, SegmentLayout, TdmsObject, TdmsSegment, HEADER_LEN,
+};
+
+/// What parsing a segment needs to know of the segments before it: each object as the last
+/// segment listing it left it, and the order objects appear in raw data. Starts empty for a
+/// file's first segment, then is updated with each segment as it's parsed.
+#[derive(Debug, Clone, Default)]
+pub struct SegmentContext {
+    objects: IndexMap<String, TdmsObject>,
+    live_objects: Vec<String>,
+    options: ReadOptions,
+}
+
+impl SegmentContext {
+    /// A context for a file's first segment. Of the options only those for parsing meta data
+    /// apply, i.e. lossy_strings, keep_unknown_properties, skip_unknown_types and
+    /// tolerate_metadata_mismatch, the last set by parse_mode if given. Properties are always
+    /// parsed, as there's no file to parse them from later.
+    pub fn new(options: ReadOptions) -> SegmentContext {
+        SegmentContext {
+            options: ReadOptions {
+                defer_properties: false,
+                ..options.resolved()
+            },
+            ..SegmentContext::default()
+        }
+    }
+
+    /// Apply a segment and the objects parse_segment returned for it, ready for the next
+    pub fn update(&mut self, segment: &TdmsSegment, objects: Vec<TdmsObject>) {
+        let listed: Vec<String> = objects
+            .iter()
+            .map(|object| object.path().to_string())
+            .collect();
+        update_live_objects(
+            &mut self.live_objects,
+            &listed,
+            segment.has_new_object_list(),
+        );
+        for object in objects {
+            self.objects.insert(object.path().to_string(), object);
+        }
+    }
+
+    /// An object as the segments so far have left it
+    pub fn object(&self, path: &str) -> Option<&TdmsObject> {
+        self.objects.get(path)
+    }
+
+    /// The paths of the objects in the last segment's object list, in the order their raw data
+    /// appears, including those without data in it
+    pub fn live_objects(&self) -> &[String] {
+        &self.live_objects
+    }
+}
+
+/// Parse a segment from bytes starting with its lead in, without any other IO, e.g. for
+/// segments arriving over a network. The bytes must hold at least the lead in and meta data,
+/// raw data after them is ignored. Returns the segment, with its start index 0 and its chunk
+/// count worked out, and the objects its meta data lists as it leaves them. The segment's
+/// total length is its `next_segment_offset` plus the 28 byte lead in. Pass both to
+/// `SegmentContext::update` before parsing the next segment.
+pub fn parse_segment(
+    bytes: &[u8],
+    prior_state: &SegmentContext,
+) -> Result<(TdmsSegment, Vec<TdmsObject>)> {
+    let lead_in_len = (HEADER_LEN as usize).min(bytes.len());
+    let mut segment = TdmsSegment::parse_lead_in(&bytes[..lead_in_len], 0)?;
+
+    let objects = if segment.toc_mask.has_flag(TocProperties::KTocMetaData) {
+        let meta_data = &bytes[lead_in_len..];
+        let meta_data = &meta_data[..meta_data.len().min(segment.raw_data_offset as usize)];
+        let prior = |path: &str| prior_state.objects.get(path);
+        let (_, objects, end) = if segment.toc_mask.has_flag(TocProperties::KTocBigEndian) {
+            parse_objects::<BE>(meta_data, HEADER_LEN, prior, &prior_state.options)?
+        } else {
+            parse_objects::<LE>(meta_data, HEADER_LEN, prior, &prior_state.options)?
+        };
+        if end != meta_data.len() as u64 && !prior_state.options.tolerate_metadata_mismatch {
+            return Err(TdmsError::MetadataLengthMismatch {
+                expected: HEADER_LEN + meta_data.len() as u64,
+                actual: HEADER_LEN + end,
+            });
+        }
+        objects
+    } else {
+        Vec::new()
+    };
+
+    if segment.toc_mask.has_flag(TocProperties::KTocRawData) {
+        let listed: Vec<String> = objects
+            .iter()
+            .map(|object| object.path().to_string())
+            .collect();
+        let mut live_objects = prior_state.live_objects.clone();
+        update_live_objects(&mut live_objects, &listed, segment.has_new_object_list());
+
+        let layout = SegmentLayout::new(live_objects.iter().filter_map(|path| {
+            objects
+                .iter()
+                .find(|object| object.path() == path)
+                .or_else(|| prior_state.objects.get(path))
+        }))?;
+        segment.no_chunks = segment.chunk_count(layout.chunk_size)?;
+    }
+
+    Ok((segment, objects))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tdms_datatypes::{DataType, DataTypeRaw};
+    use crate::test_util::{ramp, SegmentBuilder, TdmsBuilder};
+
+    const VOLTS: &str = "/'rig'/'volts'";
+
+    #[test]
+    fn segments_parse_from_bytes_in_turn() {
+        let bytes = TdmsBuilder::new()
+            .segment(
+                SegmentBuilder::new()
+                    .object("/'rig'")
+                    .channel(VOLTS, ramp(DataTypeRaw::I32, 0, 10))
+                    .property("gain", DataType::Double(2.5))
+                    .chunks(2),
+            )
+            .segment(
+                SegmentBuilder::new()
+                    .meta_data(false)
+                    .channel(VOLTS, ramp(DataTypeRaw::I32, 20, 10))
+                    .chunks(3),
+            )
+            .build();
+        let first_len = u64::from_le_bytes(bytes[12..20].try_into().unwrap()) + HEADER_LEN;
+        let (first, second) = bytes.split_at(first_len as usize);
+        let mut context = SegmentContext::new(ReadOptions::new());
+
+        let (segment, objects) = parse_segment(first, &context).unwrap();
+        let info = segment.info();
+        assert_eq!(info.start_index, 0);
+        assert_eq!(info.next_segment_offset + HEADER_LEN, first_len);
+        assert_eq!(info.raw_data_offset + HEADER_LEN + 2 * 10 * 4, first_len);
+        assert_eq!(info.no_chunks, 2);
+        let paths: Vec<&str> = objects.iter().map(TdmsObject::path).collect();
+        assert_eq!(paths, ["/'rig'", VOLTS]);
+        assert_eq!(objects[1].get_property_as::<f64>("gain").unwrap(), 2.5);
+
+        // Raw data laid out as before, only known from the first segment's objects
+        context.update(&segment, objects);
+        assert_eq!(context.live_objects(), ["/'rig'", VOLTS]);
+        let (segment, objects) = parse_segment(second, &context).unwrap();
+        assert!(objects.is_empty());
+        assert!(!segment.info().has_meta_data());
+        assert_eq!(segment.info().no_chunks, 3);
+        context.update(&segment, objects);
+        let volts = context.object(VOLTS).unwrap();
+        assert_eq!(volts.get_property_as::<f64>("gain").unwrap(), 2.5);
+
+        assert!(parse_segment(&first[..HEADER_LEN as usize - 1], &context).is_err());
+    }
+}
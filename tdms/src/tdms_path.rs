@@ -0,0 +1,192 @@
+use std::borrow::Cow;
+
+use crate::tdms_channel::ChannelHandle;
+use crate::tdms_error::{Result, TdmsError};
+
+/// An object path accepted by the `TdmsFile` lookup functions. Built from either a ready made
+/// path e.g. `"/'Group'/'Channel'"`, or a `(group, channel)` pair which is escaped for you.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectPathRef<'a>(Cow<'a, str>);
+
+impl<'a> ObjectPathRef<'a> {
+    /// The escaped object path
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<'a> From<&'a str> for ObjectPathRef<'a> {
+    fn from(path: &'a str) -> Self {
+        ObjectPathRef(Cow::Borrowed(path))
+    }
+}
+
+impl<'a> From<&'a String> for ObjectPathRef<'a> {
+    fn from(path: &'a String) -> Self {
+        ObjectPathRef(Cow::Borrowed(path))
+    }
+}
+
+impl From<String> for ObjectPathRef<'static> {
+    fn from(path: String) -> Self {
+        ObjectPathRef(Cow::Owned(path))
+    }
+}
+
+impl<'a> From<(&str, &str)> for ObjectPathRef<'a> {
+    fn from((group, channel): (&str, &str)) -> Self {
+        ObjectPathRef(Cow::Owned(object_path(Some(group), Some(channel))))
+    }
+}
+
+impl<'a> From<&'a ChannelHandle> for ObjectPathRef<'a> {
+    fn from(handle: &'a ChannelHandle) -> Self {
+        ObjectPathRef(Cow::Borrowed(handle.path()))
+    }
+}
+
+/// Build an object path from a group and channel name, quoting each and doubling any
+/// apostrophes. No group gives the root path `/`, the channel is only used with a group.
+pub fn object_path(group: Option<&str>, channel: Option<&str>) -> String {
+    match (group, channel) {
+        (Some(group), Some(channel)) => quote_component(group) + &quote_component(channel),
+        (Some(group), None) => quote_component(group),
+        (None, _) => "/".to_string(),
+    }
+}
+
+/// A name as a path component, quoted with any apostrophes doubled and preceded by a slash
+pub(crate) fn quote_component(name: &str) -> String {
+    format!("/'{}'", name.replace('\'', "''"))
+}
+
+/// Parse an object path into its group and channel names, the inverse of `object_path`.
+/// The root path `/` gives `(None, None)`.
+pub fn parse_object_path(path: &str) -> Result<(Option<String>, Option<String>)> {
+    let invalid = || TdmsError::InvalidObjectPath(path.to_string());
+    let components = split_components(path).ok_or_else(invalid)?;
+    if components.len() > 2 {
+        return Err(invalid());
+    }
+
+    let mut components = components.into_iter();
+    Ok((components.next(), components.next()))
+}
+
+/// Split an object path into its unquoted components, e.g. `/'Group'/'It''s'` gives
+/// `["Group", "It's"]`. A path that doesn't follow this form yields no components.
+pub(crate) fn path_components(path: &str) -> Vec<String> {
+    split_components(path).unwrap_or_default()
+}
+
+/// Components are single quoted with quotes escaped by doubling, anything else including a
+/// slash may appear inside the quotes. Returns None if the path is malformed.
+fn split_components(path: &str) -> Option<Vec<String>> {
+    match path {
+        "" => return None,
+        "/" => return Some(Vec::new()),
+        _ => {}
+    }
+
+    let mut components = Vec::new();
+    let mut chars = path.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '/' || chars.next() != Some('\'') {
+            return None;
+        }
+        let mut component = String::new();
+        loop {
+            match chars.next() {
+                Some('\'') if chars.peek() == Some(&'\'') => {
+                    chars.next();
+                    component.push('\'');
+                }
+                Some('\'') => break,
+                Some(c) => component.push(c),
+                None => return None,
+            }
+        }
+        components.push(component);
+    }
+    Some(components)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tdms_datatypes::DataTypeRaw;
+    use crate::test_util::{ramp, SegmentBuilder, TdmsBuilder};
+    use crate::TdmsFile;
+    use std::io::Cursor;
+
+    const NASTY_NAMES: [&str; 6] = [
+        "Dave's 'special' channel",
+        "''",
+        "a/b",
+        "/'x'/'y'",
+        " ",
+        "voltage \u{2013} \u{3bc}V",
+    ];
+
+    #[test]
+    fn object_paths_escape_names() {
+        assert_eq!(object_path(None, None), "/");
+        assert_eq!(object_path(None, Some("ignored")), "/");
+        assert_eq!(object_path(Some("Group"), None), "/'Group'");
+        assert_eq!(
+            object_path(Some("Dave's"), Some("'special'")),
+            "/'Dave''s'/'''special'''"
+        );
+    }
+
+    #[test]
+    fn object_paths_round_trip() {
+        assert_eq!(parse_object_path("/").unwrap(), (None, None));
+        for group in NASTY_NAMES {
+            let path = object_path(Some(group), None);
+            let parsed = parse_object_path(&path).unwrap();
+            assert_eq!(parsed, (Some(group.to_string()), None));
+
+            for channel in NASTY_NAMES {
+                let path = object_path(Some(group), Some(channel));
+                let parsed = parse_object_path(&path).unwrap();
+                assert_eq!(parsed, (Some(group.to_string()), Some(channel.to_string())));
+            }
+        }
+    }
+
+    #[test]
+    fn malformed_paths_fail_to_parse() {
+        let malformed = [
+            "",
+            "Group",
+            "/Group",
+            "/'Group",
+            "/'Group'x",
+            "/'It's'",
+            "/'a'/'b'/'c'",
+        ];
+        for path in malformed {
+            match parse_object_path(path) {
+                Err(TdmsError::InvalidObjectPath(invalid)) => assert_eq!(invalid, path),
+                result => panic!("{:?} parsed as {:?}", path, result),
+            }
+        }
+    }
+
+    #[test]
+    fn channels_load_by_path_or_group_and_channel() {
+        let (group, channel) = ("Dave's group", "Dave's 'special' channel");
+        let path = object_path(Some(group), Some(channel));
+        let data = ramp(DataTypeRaw::I16, 0, 10);
+        let bytes = TdmsBuilder::new()
+            .segment(SegmentBuilder::new().channel(&path, data.clone()))
+            .build();
+        let file = TdmsFile::new(Cursor::new(bytes)).unwrap();
+
+        assert_eq!(file.load_data(path.as_str()).unwrap(), data);
+        assert_eq!(file.load_data(&path).unwrap(), data);
+        assert_eq!(file.load_data((group, channel)).unwrap(), data);
+    }
+}
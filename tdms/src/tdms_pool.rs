@@ -0,0 +1,202 @@
+use std::io;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Condvar, Mutex, MutexGuard, PoisonError};
+
+type OpenReader<R> = Box<dyn Fn() -> io::Result<R> + Send + Sync>;
+
+/// Readers shared between the threads loading data from a file. Where the source can be
+/// reopened, e.g. a file on disk, each concurrent read gets its own reader, otherwise reads
+/// take turns with the one reader there is.
+pub(crate) struct ReaderPool<R> {
+    idle: Mutex<Vec<R>>,
+    returned: Condvar,
+    open: Option<OpenReader<R>>,
+}
+
+impl<R> ReaderPool<R> {
+    /// A pool of the one reader, which can't be reopened
+    pub(crate) fn single(reader: R) -> ReaderPool<R> {
+        ReaderPool {
+            idle: Mutex::new(vec![reader]),
+            returned: Condvar::new(),
+            open: None,
+        }
+    }
+
+    /// A pool starting with the given reader, opening more as they're needed
+    pub(crate) fn reopening<F>(reader: R, open: F) -> ReaderPool<R>
+    where
+        F: Fn() -> io::Result<R> + Send + Sync + 'static,
+    {
+        ReaderPool {
+            idle: Mutex::new(vec![reader]),
+            returned: Condvar::new(),
+            open: Some(Box::new(open)),
+        }
+    }
+
+    /// Take a reader for the duration of a read, it's returned to the pool when dropped. Only
+    /// one should be held at a time by a thread, as with a single reader the second would
+    /// wait on the first forever.
+    pub(crate) fn take(&self) -> io::Result<PooledReader<'_, R>> {
+        let mut idle = self.lock();
+        let reader = loop {
+            if let Some(reader) = idle.pop() {
+                break reader;
+            }
+            if let Some(open) = &self.open {
+                drop(idle);
+                break open()?;
+            }
+            idle = self
+                .returned
+                .wait(idle)
+                .unwrap_or_else(PoisonError::into_inner);
+        };
+        Ok(PooledReader {
+            pool: self,
+            reader: Some(reader),
+        })
+    }
+
+    /// Direct access to a reader when the pool isn't shared, e.g. to remap the file
+    pub(crate) fn get_mut(&mut self) -> io::Result<&mut R> {
+        let idle = self.idle.get_mut().unwrap_or_else(PoisonError::into_inner);
+        if idle.is_empty() {
+            // Readers are always returned, so this is only reached if one was leaked
+            let open = self
+                .open
+                .as_ref()
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no reader available"))?;
+            idle.push(open()?);
+        }
+        Ok(idle.last_mut().unwrap())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<R>> {
+        self.idle.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// A reader on loan from a ReaderPool
+pub(crate) struct PooledReader<'a, R> {
+    pool: &'a ReaderPool<R>,
+    reader: Option<R>,
+}
+
+impl<R> Deref for PooledReader<'_, R> {
+    type Target = R;
+
+    fn deref(&self) -> &R {
+        self.reader.as_ref().unwrap()
+    }
+}
+
+impl<R> DerefMut for PooledReader<'_, R> {
+    fn deref_mut(&mut self) -> &mut R {
+        self.reader.as_mut().unwrap()
+    }
+}
+
+impl<R> Drop for PooledReader<'_, R> {
+    fn drop(&mut self) {
+        if let Some(reader) = self.reader.take() {
+            self.pool.lock().push(reader);
+            self.pool.returned.notify_one();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tdms_datatypes::DataTypeRaw;
+    use crate::test_util::{ramp, SegmentBuilder, TdmsBuilder};
+    use crate::tests::open_bytes;
+    use crate::{ReadOptions, TdmsFile};
+    use std::io::{Cursor, Read, Seek};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    const CHANNELS: [&str; 4] = [
+        "/'group'/'a'",
+        "/'group'/'b'",
+        "/'group'/'c'",
+        "/'group'/'d'",
+    ];
+
+    /// Four channels over several segments, channel i ramping from 1000 * i
+    fn four_channels() -> Vec<u8> {
+        let mut builder = TdmsBuilder::new();
+        for segment in 0..5 {
+            let mut segment_builder = SegmentBuilder::new();
+            for (i, channel) in CHANNELS.iter().enumerate() {
+                let start = 1000 * i as u64 + 100 * segment;
+                segment_builder =
+                    segment_builder.channel(channel, ramp(DataTypeRaw::U64, start, 100));
+            }
+            builder = builder.segment(segment_builder);
+        }
+        builder.build()
+    }
+
+    /// Load each channel on its own thread, all at once
+    fn load_concurrently<R: Read + Seek + Send>(file: &TdmsFile<R>) {
+        thread::scope(|scope| {
+            for (i, channel) in CHANNELS.iter().enumerate() {
+                scope.spawn(move || {
+                    let data = file.load_data(*channel).unwrap();
+                    assert_eq!(data, ramp(DataTypeRaw::U64, 1000 * i as u64, 500));
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn files_are_send_and_sync() {
+        fn send_sync<T: Send + Sync>() {}
+        send_sync::<TdmsFile>();
+        send_sync::<TdmsFile<Cursor<Vec<u8>>>>();
+    }
+
+    #[test]
+    fn threads_load_channels_concurrently() {
+        let bytes = four_channels();
+        let (_path, file) = open_bytes(&bytes, ReadOptions::new());
+        load_concurrently(&file.unwrap());
+
+        let file = TdmsFile::new(Cursor::new(bytes)).unwrap();
+        load_concurrently(&file);
+    }
+
+    #[test]
+    fn readers_are_opened_while_others_are_taken() {
+        let opened = Arc::new(AtomicUsize::new(0));
+        let counter = opened.clone();
+        let pool = ReaderPool::reopening(0, move || Ok(counter.fetch_add(1, Ordering::SeqCst) + 1));
+
+        let first = pool.take().unwrap();
+        let second = pool.take().unwrap();
+        assert_eq!((*first, *second), (0, 1));
+        drop(second);
+        // A returned reader is taken again rather than opening another
+        assert_eq!(*pool.take().unwrap(), 1);
+        assert_eq!(opened.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn a_single_reader_is_waited_for() {
+        let pool = ReaderPool::single(Vec::<u8>::new());
+        let taken = pool.take().unwrap();
+        thread::scope(|scope| {
+            let waiting = scope.spawn(|| pool.take().unwrap().push(2));
+            // Written to before the waiting thread can have it
+            let mut taken = taken;
+            taken.push(1);
+            drop(taken);
+            waiting.join().unwrap();
+        });
+        assert_eq!(*pool.take().unwrap(), [1, 2]);
+    }
+}
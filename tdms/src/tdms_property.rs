@@ -0,0 +1,310 @@
+use std::fmt;
+use std::io::{Read, Seek, SeekFrom};
+use std::sync::OnceLock;
+
+use byteorder::{ByteOrder, BE, LE};
+use indexmap::IndexMap;
+
+use crate::tdms_datatypes::{DataType, DataTypeRaw, TocProperties};
+use crate::tdms_error::Result;
+use crate::tdms_path::ObjectPathRef;
+use crate::{ObjectMap, ObjectProperty, TdmsFile, TdmsObject, HEADER_LEN};
+
+/// Properties with names and meanings set by NI, which LabVIEW, DIAdem and other writers use.
+/// Any property can still be looked up by name, these save spelling the common ones out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StandardProperty {
+    /// `name`, the name of the file, group or channel
+    Name,
+    /// `description`
+    Description,
+    /// `author`, of the file
+    Author,
+    /// `title`, of the file
+    Title,
+    /// `datetime`, when the file was written
+    DateTime,
+    /// `wf_start_time`, the time of a waveform channel's first value
+    WfStartTime,
+    /// `wf_start_offset`, seconds from the start time to the first value
+    WfStartOffset,
+    /// `wf_increment`, seconds between a waveform channel's values
+    WfIncrement,
+    /// `wf_samples`, the number of values a waveform channel had when written
+    WfSamples,
+    /// `wf_xname`, the name of the x axis
+    WfXName,
+    /// `wf_xunit_string`, the unit of the x axis
+    WfXUnitString,
+    /// `unit_string`, the unit of a channel's values
+    UnitString,
+    /// `NI_ChannelName`, the name of the channel in the writer
+    NiChannelName,
+    /// `NI_UnitDescription`, the unit of a channel's values as NI's drivers give it
+    NiUnitDescription,
+}
+
+impl StandardProperty {
+    /// The name the property is stored under
+    pub fn name(&self) -> &'static str {
+        match self {
+            StandardProperty::Name => "name",
+            StandardProperty::Description => "description",
+            StandardProperty::Author => "author",
+            StandardProperty::Title => "title",
+            StandardProperty::DateTime => "datetime",
+            StandardProperty::WfStartTime => "wf_start_time",
+            StandardProperty::WfStartOffset => "wf_start_offset",
+            StandardProperty::WfIncrement => "wf_increment",
+            StandardProperty::WfSamples => "wf_samples",
+            StandardProperty::WfXName => "wf_xname",
+            StandardProperty::WfXUnitString => "wf_xunit_string",
+            StandardProperty::UnitString => "unit_string",
+            StandardProperty::NiChannelName => "NI_ChannelName",
+            StandardProperty::NiUnitDescription => "NI_UnitDescription",
+        }
+    }
+
+    /// The data type writers store the property as. Files may use another, e.g. wf_samples
+    /// as I64, so values are best read with `TdmsObject::get_property_as`.
+    pub fn data_type(&self) -> DataTypeRaw {
+        match self {
+            StandardProperty::DateTime | StandardProperty::WfStartTime => DataTypeRaw::TimeStamp,
+            StandardProperty::WfStartOffset | StandardProperty::WfIncrement => {
+                DataTypeRaw::DoubleFloat
+            }
+            StandardProperty::WfSamples => DataTypeRaw::I32,
+            _ => DataTypeRaw::TdmsString,
+        }
+    }
+}
+
+impl fmt::Display for StandardProperty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Where a listing of an object's properties skipped by defer_properties is
+#[derive(Debug, Clone)]
+pub(crate) struct PropertyBlock {
+    segment_index: usize,
+    offset: u64, // from the start of the segment's meta data
+    no_properties: u32,
+}
+
+impl ObjectMap {
+    /// Take the listings of the object's properties it skipped in the segment at segment_index
+    pub(crate) fn defer_properties(&mut self, object: &mut TdmsObject, segment_index: usize) {
+        if object.deferred_properties.is_empty() {
+            return;
+        }
+        let blocks = object.deferred_properties.drain(..);
+        self.property_blocks
+            .extend(blocks.map(|(offset, no_properties)| PropertyBlock {
+                segment_index,
+                offset,
+                no_properties,
+            }));
+        self.lazy_properties = OnceLock::new();
+    }
+}
+
+impl TdmsObject {
+    /// The value of a standard property, None if the object doesn't have it
+    pub fn standard_property(&self, property: StandardProperty) -> Option<&DataType> {
+        self.properties
+            .get(property.name())
+            .map(ObjectProperty::value)
+    }
+}
+
+impl<R: Read + Seek> TdmsFile<R> {
+    /// The value of a standard property of an object, None if the object doesn't exist or
+    /// doesn't have it
+    pub fn standard_property<'a, P: Into<ObjectPathRef<'a>>>(
+        &self,
+        path: P,
+        property: StandardProperty,
+    ) -> Option<&DataType> {
+        let object_map = self.tdms_map.all_objects.get(path.into().as_str())?;
+        self.properties_of(object_map)
+            .ok()?
+            .get(property.name())
+            .map(ObjectProperty::value)
+    }
+
+    /// The properties of an object. If the file was opened with defer_properties they're
+    /// parsed from the file the first time they're asked for, as every segment listing the
+    /// object left them, and kept for later calls. Otherwise they're those already parsed.
+    /// Unlike the other property accessors this reports a failure to parse them.
+    pub fn object_properties_lazy<'a, P: Into<ObjectPathRef<'a>>>(
+        &self,
+        path: P,
+    ) -> Result<&IndexMap<String, ObjectProperty>> {
+        let object_map = self.tdms_map.object_map(path.into().as_str())?;
+        self.properties_of(object_map)
+    }
+
+    /// The properties of an object as `object_properties_lazy` gives them, every property
+    /// accessor goes through this so they all see deferred properties
+    pub(crate) fn properties_of<'f>(
+        &'f self,
+        object_map: &'f ObjectMap,
+    ) -> Result<&'f IndexMap<String, ObjectProperty>> {
+        if object_map.property_blocks.is_empty() {
+            return Ok(&object_map.last_object.properties);
+        }
+        if let Some(properties) = object_map.lazy_properties.get() {
+            return Ok(properties);
+        }
+
+        // Every listing was deferred, including those of the timing properties read anyway
+        let path = object_map.last_object.object_path.as_str();
+        let mut properties = IndexMap::new();
+        let mut reader = self.readers.take()?;
+        for block in &object_map.property_blocks {
+            let segment = &self.tdms_map.segments[block.segment_index];
+            let meta_data_start = segment.start_index + HEADER_LEN;
+            let meta_data_end = meta_data_start + segment.raw_data_offset;
+            reader.seek(SeekFrom::Start(meta_data_start + block.offset))?;
+            let result = if segment.toc_mask.has_flag(TocProperties::KTocBigEndian) {
+                self.read_property_block::<_, BE>(&mut *reader, block, meta_data_end)
+            } else {
+                self.read_property_block::<_, LE>(&mut *reader, block, meta_data_end)
+            };
+            let block_properties = result.map_err(|e| {
+                let offset = reader.stream_position().unwrap_or(meta_data_start);
+                e.with_context(Some(path), Some(block.segment_index), offset)
+            })?;
+            for property in block_properties {
+                properties.insert(property.prop_name.clone(), property);
+            }
+        }
+        Ok(object_map.lazy_properties.get_or_init(|| properties))
+    }
+
+    /// Read the properties of a listing skipped by defer_properties, the reader at its first
+    fn read_property_block<S: Read + Seek, O: ByteOrder>(
+        &self,
+        reader: &mut S,
+        block: &PropertyBlock,
+        meta_data_end: u64,
+    ) -> Result<Vec<ObjectProperty>> {
+        let lossy_strings = self.tdms_map.options.lossy_strings;
+        (0..block.no_properties)
+            .map(|i| {
+                if self.tdms_map.options.keep_unknown_properties {
+                    let remaining = block.no_properties - i - 1;
+                    ObjectProperty::read_property_or_unknown::<S, O>(
+                        reader,
+                        lossy_strings,
+                        remaining,
+                        meta_data_end,
+                    )
+                } else {
+                    ObjectProperty::read_property::<S, O>(reader, lossy_strings)
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tdms_datatypes::{TimeStamp, LABVIEW_EPOCH_OFFSET};
+    use crate::test_util::{ramp, SegmentBuilder, TdmsBuilder};
+    use crate::tests::open_bytes;
+    use crate::{diff, DiffOptions, ReadOptions};
+    use chrono::DateTime;
+
+    const VOLTS: &str = "/'group'/'volts'";
+
+    fn start_time(secs: i64) -> DataType {
+        DataType::TimeStamp(TimeStamp {
+            epoch: LABVIEW_EPOCH_OFFSET + secs,
+            radix: 0,
+        })
+    }
+
+    /// A waveform channel listed by two segments with a 5 s gap between them, with file,
+    /// group and channel properties, some changed by the second segment
+    fn property_file() -> Vec<u8> {
+        let segment = |start_secs, first_value| {
+            SegmentBuilder::new()
+                .channel(VOLTS, ramp(DataTypeRaw::DoubleFloat, first_value, 10))
+                .property("wf_start_time", start_time(start_secs))
+                .property("wf_increment", DataType::Double(1.0))
+                .property("unit_string", DataType::TdmsString("V".to_string()))
+                .property("gain", DataType::I32(first_value as i32))
+        };
+        TdmsBuilder::new()
+            .segment(
+                segment(0, 0)
+                    .object("/")
+                    .property("name", DataType::TdmsString("run".to_string()))
+                    .object("/'group'")
+                    .property("location", DataType::TdmsString("lab".to_string())),
+            )
+            .segment(segment(15, 10))
+            .build()
+    }
+
+    #[test]
+    fn deferred_properties_are_seen_by_every_accessor() {
+        let bytes = property_file();
+        let (_path, parsed) = open_bytes(&bytes, ReadOptions::new());
+        let parsed = parsed.unwrap();
+        let (_path, deferred) = open_bytes(&bytes, ReadOptions::new().defer_properties(true));
+        let deferred = deferred.unwrap();
+
+        // Only the timing properties were parsed while mapping
+        let mapped = &deferred.tdms_map.object_map(VOLTS).unwrap().last_object;
+        let names: Vec<&str> = mapped.properties().keys().map(String::as_str).collect();
+        assert_eq!(names, ["wf_start_time", "wf_increment"]);
+
+        for path in ["/", "/'group'", VOLTS] {
+            let properties = deferred.object_properties_lazy(path).unwrap();
+            assert_eq!(
+                format!("{:?}", properties),
+                format!("{:?}", parsed.object_properties_lazy(path).unwrap())
+            );
+        }
+        let name = deferred.file_properties().unwrap()["name"].value();
+        assert_eq!(name, &DataType::TdmsString("run".to_string()));
+        assert_eq!(
+            deferred.resolve_property(VOLTS, "location"),
+            Some(&DataType::TdmsString("lab".to_string()))
+        );
+        assert_eq!(
+            deferred.standard_property(VOLTS, StandardProperty::WfIncrement),
+            Some(&DataType::Double(1.0))
+        );
+        assert_eq!(deferred.get_property_as::<i64>(VOLTS, "gain").unwrap(), 10);
+        assert_eq!(deferred.channel_unit(VOLTS).as_deref(), Some("V"));
+        let handle = &deferred.channels()[0];
+        assert_eq!(handle.properties(&deferred).unwrap().len(), 4);
+        assert!(diff(&parsed, &deferred, &DiffOptions::new())
+            .unwrap()
+            .is_empty());
+        #[cfg(feature = "serde")]
+        assert_eq!(deferred.metadata_json(), parsed.metadata_json());
+    }
+
+    #[test]
+    fn deferred_properties_keep_waveform_timing() {
+        let bytes = property_file();
+        let (_path, deferred) = open_bytes(&bytes, ReadOptions::new().defer_properties(true));
+        let deferred = deferred.unwrap();
+
+        let gaps = deferred.discontinuities(VOLTS).unwrap();
+        assert_eq!(gaps.len(), 1);
+        assert_eq!((gaps[0].index, gaps[0].missing_values), (10, 5));
+
+        let from = DateTime::from_timestamp(8, 0).unwrap();
+        let to = DateTime::from_timestamp(17, 0).unwrap();
+        let data = deferred.load_time_range(VOLTS, from, to).unwrap();
+        assert_eq!(data, ramp(DataTypeRaw::DoubleFloat, 8, 4));
+    }
+}
@@ -0,0 +1,298 @@
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::path;
+
+use byteorder::{BE, LE};
+use indexmap::IndexMap;
+use log::debug;
+
+use crate::tdms_datatypes::TocProperties;
+use crate::tdms_digest::{DigestAlgo, Hasher};
+use crate::tdms_error::Result;
+use crate::{
+    buffered, companion_paths, file_readers, TdmsFile, TdmsMap, TdmsMetaData, TdmsSegment,
+    HEADER_LEN, UNFINISHED_SEGMENT,
+};
+
+/// The parsed meta data of a file's first segment, kept for opening others written with the
+/// same layout without parsing it again, e.g. the many files of a logger, see
+/// `TdmsFile::open_with_template`
+#[derive(Debug, Clone)]
+pub struct TdmsMapTemplate {
+    map: TdmsMap, // as the first segment's meta data leaves it, before its raw data is placed
+    meta_data: TdmsMetaData, // the objects the first segment lists
+    fingerprint: Option<[u8; 32]>,
+}
+
+impl TdmsMapTemplate {
+    /// A SHA-256 digest of the first segment's lead in and meta data, which give the objects,
+    /// properties and raw data layout of most files. The next segment offset is left out, it
+    /// only gives how much data the segment holds, so files with the same fingerprint were
+    /// written with the same layout whatever their length. None if the file has no segments.
+    pub fn fingerprint(&self) -> Option<[u8; 32]> {
+        self.fingerprint
+    }
+
+    /// The first segment of the file in reader, if it has the template's fingerprint and fits
+    /// in the file so its raw data can be placed from the template's meta data
+    fn matching_segment<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        file_length: u64,
+    ) -> Result<Option<TdmsSegment>> {
+        let (segment, fingerprint) = first_segment(reader)?;
+        let fits = segment.next_seg_offset != UNFINISHED_SEGMENT
+            && segment.next_seg_offset <= file_length.saturating_sub(HEADER_LEN);
+        Ok((fits && self.fingerprint == Some(fingerprint)).then_some(segment))
+    }
+}
+
+/// Read the lead in and meta data of the file's first segment, giving the segment and its
+/// fingerprint
+fn first_segment<R: Read + Seek>(reader: &mut R) -> Result<(TdmsSegment, [u8; 32])> {
+    reader.seek(SeekFrom::Start(0))?;
+    let mut lead_in = [0u8; HEADER_LEN as usize];
+    reader.read_exact(&mut lead_in)?;
+    let segment = TdmsSegment::parse_lead_in(&lead_in, 0)?;
+    let mut meta_data = Vec::new();
+    reader
+        .take(segment.raw_data_offset)
+        .read_to_end(&mut meta_data)?;
+
+    // Bytes 12 to 20 of the lead in are the next segment offset
+    let mut hasher = Hasher::new(DigestAlgo::Sha256);
+    hasher.update(&lead_in[..12]);
+    hasher.update(&lead_in[20..]);
+    hasher.update(&meta_data);
+    Ok((segment, hasher.finish()))
+}
+
+impl TdmsFile {
+    /// Open a file written with the same layout as the template's, reusing the template's
+    /// parsed meta data for the file's first segment once its fingerprint matches. The rest of
+    /// the file is mapped as usual from the segments' lead ins, which is all a segment that
+    /// leaves the meta data as it was takes, so files of any length open from the template.
+    /// A file with a different first segment, or one still being written or cut short, is
+    /// mapped as `open_with_options` would with the options the template's file was opened
+    /// with.
+    pub fn open_with_template(path: &path::Path, template: &TdmsMapTemplate) -> Result<TdmsFile> {
+        let (path, _) = companion_paths(path);
+        let path = path.as_path();
+        let options = &template.map.options;
+        let mut reader = buffered(fs::File::open(path)?, options);
+        let capacity = reader.capacity();
+        let file_length = reader.seek(SeekFrom::End(0))?;
+        let segment = match template.matching_segment(&mut reader, file_length) {
+            Ok(Some(segment)) => segment,
+            _ => {
+                debug!("{:?} doesn't match the template, mapping it in full", path);
+                return TdmsFile::open_with_options(path, options.clone());
+            }
+        };
+
+        let mut tdms_map = template.map.clone();
+        let raw_data_start = HEADER_LEN + segment.raw_data_offset;
+        let segment = tdms_map
+            .place_raw_data(segment, &template.meta_data)
+            .map_err(|e| e.with_context(None, Some(0), raw_data_start))?;
+        tdms_map.next_segment_address = segment.next_seg_offset.saturating_add(HEADER_LEN);
+        tdms_map.push_segment(segment);
+        tdms_map.map_segments(&mut reader, file_length)?;
+
+        Ok(TdmsFile {
+            readers: file_readers(reader, path, capacity),
+            tdms_map,
+            aliases: IndexMap::new(),
+        })
+    }
+}
+
+impl<R: Read + Seek> TdmsFile<R> {
+    /// Keep the parsed meta data of the file's first segment as a template for opening others
+    /// written with the same layout, see `TdmsFile::open_with_template`. The first segment's
+    /// meta data is read again to fingerprint it.
+    pub fn map_template(&self) -> Result<TdmsMapTemplate> {
+        let mut map = TdmsMap::new(self.tdms_map.options.clone());
+        map.channel_filter = self.tdms_map.channel_filter.clone();
+        if self.tdms_map.segments.is_empty() {
+            return Ok(TdmsMapTemplate {
+                map,
+                meta_data: TdmsMetaData::default(),
+                fingerprint: None,
+            });
+        }
+
+        let mut reader = self.readers.take()?;
+        let (segment, fingerprint) = first_segment(&mut *reader)?;
+        map.check_version(&segment, 0)?;
+        reader.seek(SeekFrom::Start(HEADER_LEN))?;
+        let len = segment.raw_data_offset;
+        let meta_data = if !segment.toc_mask.has_flag(TocProperties::KTocMetaData) {
+            TdmsMetaData::default()
+        } else if segment.toc_mask.has_flag(TocProperties::KTocBigEndian) {
+            TdmsMetaData::read_metadata::<_, BE>(&mut map, &mut *reader, len)?
+        } else {
+            TdmsMetaData::read_metadata::<_, LE>(&mut map, &mut *reader, len)?
+        };
+
+        Ok(TdmsMapTemplate {
+            map,
+            meta_data,
+            fingerprint: Some(fingerprint),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tdms_datatypes::{DataType, DataTypeRaw, DataTypeVec};
+    use crate::test_util::{ramp, SegmentBuilder, TdmsBuilder};
+    use std::io::{Cursor, Write};
+    use tempfile::{NamedTempFile, TempPath};
+
+    const VOLTS: &str = "/'group'/'volts'";
+    const COUNT: &str = "/'group'/'count'";
+
+    /// A logger's file, a first segment listing the objects then segments of data alone. The
+    /// first segment repeats its values chunks times, later ones run on from them.
+    fn logger_file(start: u64, chunks: u64, segments: usize) -> Vec<u8> {
+        let first = SegmentBuilder::new()
+            .object("/")
+            .property("title", DataType::TdmsString("rig 1".to_string()))
+            .object("/'group'")
+            .channel(VOLTS, ramp(DataTypeRaw::DoubleFloat, start, 4))
+            .property("unit_string", DataType::TdmsString("V".to_string()))
+            .channel(COUNT, ramp(DataTypeRaw::U32, start, 4))
+            .chunks(chunks);
+        let mut builder = TdmsBuilder::new().segment(first);
+        let mut next = start + 4;
+        for _ in 1..segments {
+            builder = builder.segment(
+                SegmentBuilder::new()
+                    .meta_data(false)
+                    .channel(VOLTS, ramp(DataTypeRaw::DoubleFloat, next, 4))
+                    .channel(COUNT, ramp(DataTypeRaw::U32, next, 4)),
+            );
+            next += 4;
+        }
+        builder.build()
+    }
+
+    /// The values of each channel in logger_file
+    fn logger_values(start: u32, chunks: usize, segments: u32) -> Vec<u32> {
+        let first = (start..start + 4).cycle().take(4 * chunks);
+        first.chain(start + 4..start + 4 * segments).collect()
+    }
+
+    fn write_temp(bytes: &[u8]) -> TempPath {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(bytes).unwrap();
+        file.into_temp_path()
+    }
+
+    fn template_of(bytes: Vec<u8>) -> TdmsMapTemplate {
+        TdmsFile::new(Cursor::new(bytes))
+            .unwrap()
+            .map_template()
+            .unwrap()
+    }
+
+    /// Whether the file's first segment matches the template, so opening it uses the template
+    fn matches(template: &TdmsMapTemplate, bytes: &[u8]) -> bool {
+        let mut reader = Cursor::new(bytes);
+        let file_length = bytes.len() as u64;
+        template
+            .matching_segment(&mut reader, file_length)
+            .unwrap()
+            .is_some()
+    }
+
+    /// Open the file from the template and as open_with_options would, checking both give
+    /// the same map
+    fn open_both(template: &TdmsMapTemplate, bytes: &[u8]) -> TdmsFile {
+        let path = write_temp(bytes);
+        let from_template = TdmsFile::open_with_template(&path, template).unwrap();
+        let in_full = TdmsFile::open_with_options(&path, template.map.options.clone()).unwrap();
+        assert_eq!(
+            format!("{:?}", from_template.tdms_map),
+            format!("{:?}", in_full.tdms_map)
+        );
+        from_template
+    }
+
+    #[test]
+    fn files_differing_only_in_length_open_from_the_template() {
+        let template = template_of(logger_file(0, 1, 2));
+        for (chunks, segments) in [(1, 2), (1, 5), (3, 1), (2, 4)] {
+            let bytes = logger_file(100, chunks, segments);
+            assert!(matches(&template, &bytes), "{} {}", chunks, segments);
+            let file = open_both(&template, &bytes);
+
+            let values = logger_values(100, chunks as usize, segments as u32);
+            assert_eq!(file.segments().len(), segments);
+            assert_eq!(
+                file.load_data(VOLTS).unwrap(),
+                DataTypeVec::Double(values.iter().map(|&v| v as f64).collect())
+            );
+            assert_eq!(file.load_data(COUNT).unwrap(), DataTypeVec::U32(values));
+            assert_eq!(
+                file.get_property_as::<String>(VOLTS, "unit_string")
+                    .unwrap(),
+                "V"
+            );
+        }
+        assert_eq!(
+            template.fingerprint(),
+            template_of(logger_file(7, 3, 6)).fingerprint()
+        );
+    }
+
+    #[test]
+    fn files_with_another_layout_are_mapped_in_full() {
+        let template = template_of(logger_file(0, 1, 2));
+        let other = TdmsBuilder::new()
+            .segment(
+                SegmentBuilder::new()
+                    .object("/")
+                    .property("title", DataType::TdmsString("rig 2".to_string()))
+                    .object("/'group'")
+                    .channel(VOLTS, ramp(DataTypeRaw::DoubleFloat, 0, 4))
+                    .property("unit_string", DataType::TdmsString("V".to_string()))
+                    .channel(COUNT, ramp(DataTypeRaw::U32, 0, 4)),
+            )
+            .build();
+        assert!(!matches(&template, &other));
+        assert_ne!(
+            template.fingerprint(),
+            template_of(other.clone()).fingerprint()
+        );
+        let file = open_both(&template, &other);
+        assert_eq!(
+            file.get_property_as::<String>("/", "title").unwrap(),
+            "rig 2"
+        );
+        assert_eq!(
+            file.load_data(VOLTS).unwrap(),
+            ramp(DataTypeRaw::DoubleFloat, 0, 4)
+        );
+    }
+
+    #[test]
+    fn files_still_being_written_are_mapped_in_full() {
+        let template = template_of(logger_file(0, 1, 2));
+        let mut bytes = logger_file(0, 2, 1);
+        bytes[12..20].copy_from_slice(&UNFINISHED_SEGMENT.to_le_bytes());
+        assert!(!matches(&template, &bytes));
+        let file = open_both(&template, &bytes);
+        assert_eq!(
+            file.load_data(COUNT).unwrap(),
+            DataTypeVec::U32(logger_values(0, 2, 1))
+        );
+    }
+
+    #[test]
+    fn files_without_segments_have_no_fingerprint() {
+        assert_eq!(template_of(Vec::new()).fingerprint(), None);
+    }
+}
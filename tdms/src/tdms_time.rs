@@ -0,0 +1,763 @@
+use std::io::{Read, Seek};
+use std::ops::Range;
+
+use chrono::{DateTime, Utc};
+
+use crate::tdms_datatypes::{
+    read_pairs_vector, DataTypeRaw, DataTypeVec, TimeStamp, LABVIEW_EPOCH_OFFSET,
+};
+use crate::tdms_error::{Result, TdmsError};
+use crate::tdms_path::{object_path, path_components, ObjectPathRef};
+use crate::tdms_property::StandardProperty;
+use crate::{ObjectMap, ReadPair, TdmsFile, TdmsObject, STREAM_BLOCK_VALUES};
+
+/// A break in a channel's waveform timing, where a segment's wf_start_time doesn't follow on
+/// from the values before it, e.g. from a logger that paused
+#[derive(Debug, Clone, PartialEq)]
+pub struct Discontinuity {
+    /// The index of the segment listing the new start time
+    pub segment: usize,
+    /// The index of the first value after the break
+    pub index: u64,
+    /// The time the value at index would have had continuing the timing before it
+    pub expected: TimeStamp,
+    /// The time the value at index has
+    pub actual: TimeStamp,
+    /// Seconds from expected to actual, negative if the timing went back
+    pub gap: f64,
+    /// The whole number of values the gap spans at the increment before it, 0 if it went back
+    pub missing_values: u64,
+}
+
+/// What `TdmsFile::load_data_gap_filled` fills the values missing from gaps with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FillPolicy {
+    /// NaN in float channels, other channels are filled as for Zero
+    Nan,
+    /// The type's default value, e.g. 0 or an empty string
+    Zero,
+    /// The value before the gap
+    Repeat,
+}
+
+/// The properties waveform timing is recorded from while mapping, defer_properties still
+/// parses them
+pub(crate) const TIMING_PROPERTIES: [StandardProperty; 2] =
+    [StandardProperty::WfStartTime, StandardProperty::WfIncrement];
+
+/// Waveform timing a channel takes from a segment on, until a later segment lists new timing.
+/// Its values from that segment are increment seconds apart starting at start.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TimingRun {
+    segment_index: usize,
+    start: TimeStamp,
+    increment: f64,
+}
+
+impl ObjectMap {
+    /// Record the waveform timing of the object as listed in the segment at segment_index, if
+    /// it's complete and differs from the timing it had
+    pub(crate) fn record_timing(&mut self, object: &TdmsObject, segment_index: usize) {
+        let start = object.get_property_as::<TimeStamp>(StandardProperty::WfStartTime.name());
+        let increment = object.get_property_as::<f64>(StandardProperty::WfIncrement.name());
+        if let (Ok(start), Ok(increment)) = (start, increment) {
+            let changed = match self.timing.last() {
+                Some(run) => run.start != start || run.increment != increment,
+                None => true,
+            };
+            if changed && increment > 0.0 {
+                self.timing.push(TimingRun {
+                    segment_index,
+                    start,
+                    increment,
+                });
+            }
+        }
+    }
+
+    /// The index of the first value of each timing run, then the total number of values
+    fn timing_bounds(&self) -> Vec<u64> {
+        let total = self.total_values();
+        self.timing
+            .iter()
+            .map(|run| self.values_before(run.segment_index))
+            .chain(Some(total))
+            .collect()
+    }
+
+    /// The number of values the object has in the segments before segment_index
+    fn values_before(&self, segment_index: usize) -> u64 {
+        self.read_map
+            .iter()
+            .flat_map(ReadPair::chunks)
+            .filter(|pair| pair.segment_index < segment_index)
+            .map(|pair| pair.no_values)
+            .sum()
+    }
+}
+
+impl<R: Read + Seek> TdmsFile<R> {
+    /// Load a channel's values from start up to but not including end. Times come from the
+    /// channel's wf_start_time and wf_increment properties, as listed by each segment, so a
+    /// segment giving a new start time restarts the timing from its values. Channels without
+    /// them use a timestamp channel in the same group, named by the channel's NI_TimeChannel
+    /// property or else the channel's name with `_time` appended, whose times must be in
+    /// order. Empty if the range is outside the data, fails with `MissingProperty` if the
+    /// channel has neither timing.
+    pub fn load_time_range<'a, P: Into<ObjectPathRef<'a>>>(
+        &self,
+        path: P,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<DataTypeVec> {
+        let path = path.into();
+        let object_map = self.tdms_map.object_map(path.as_str())?;
+        if self.tdms_map.options.metadata_only {
+            return Err(TdmsError::MetadataOnly);
+        }
+
+        let ranges = if !object_map.timing.is_empty() {
+            waveform_ranges(object_map, &start, &end)
+        } else {
+            let time_map = self.time_channel(object_map)?;
+            vec![self.time_channel_range(time_map, &timestamp(&start), &timestamp(&end))?]
+        };
+
+        // Starting from an empty vector of the channel's type
+        let mut data = self.read_value_range(object_map, 0..0)?;
+        for range in ranges.into_iter().filter(|range| !range.is_empty()) {
+            data.extend(self.read_value_range(object_map, range)?)?;
+        }
+        Ok(data)
+    }
+
+    /// Where a channel's waveform timing breaks, from the wf_start_time and wf_increment each
+    /// segment lists. A segment's start time counts as a break if it's more than half an
+    /// increment from where the values before it would put it. Fails with `MissingProperty`
+    /// if the channel has no waveform timing.
+    pub fn discontinuities<'a, P: Into<ObjectPathRef<'a>>>(
+        &self,
+        path: P,
+    ) -> Result<Vec<Discontinuity>> {
+        let object_map = self.tdms_map.object_map(path.into().as_str())?;
+        if object_map.timing.is_empty() {
+            return Err(TdmsError::MissingProperty {
+                path: object_map.last_object.object_path.clone(),
+                property: StandardProperty::WfStartTime.name().to_string(),
+            });
+        }
+
+        let bounds = object_map.timing_bounds();
+        let mut discontinuities = Vec::new();
+        for (i, pair) in object_map.timing.windows(2).enumerate() {
+            let (before, run) = (&pair[0], &pair[1]);
+            let values = bounds[i + 1].saturating_sub(bounds[i]);
+            let expected = add_seconds(&before.start, values as f64 * before.increment);
+            let gap = seconds_between(&expected, &run.start);
+            if gap.abs() > before.increment / 2.0 {
+                discontinuities.push(Discontinuity {
+                    segment: run.segment_index,
+                    index: bounds[i + 1],
+                    expected,
+                    actual: run.start.clone(),
+                    gap,
+                    missing_values: (gap / before.increment).round().max(0.0) as u64,
+                });
+            }
+        }
+        Ok(discontinuities)
+    }
+
+    /// Load a channel's data with values filled in for the gaps `discontinuities` finds, as
+    /// fill says, so the values keep a uniform time base. Values where the timing went back
+    /// are kept as they are.
+    pub fn load_data_gap_filled<'a, P: Into<ObjectPathRef<'a>>>(
+        &self,
+        path: P,
+        fill: FillPolicy,
+    ) -> Result<DataTypeVec> {
+        let path = path.into();
+        let discontinuities = self.discontinuities(path.as_str())?;
+        let object_map = self.tdms_map.object_map(path.as_str())?;
+        if self.tdms_map.options.metadata_only {
+            return Err(TdmsError::MetadataOnly);
+        }
+        self.check_load_size(object_map)?;
+
+        let mut data = self.read_value_range(object_map, 0..0)?;
+        let mut start = 0;
+        for discontinuity in discontinuities {
+            data.extend(self.read_value_range(object_map, start..discontinuity.index)?)?;
+            data.fill_gap(discontinuity.missing_values as usize, fill);
+            start = discontinuity.index;
+        }
+        let total = object_map.total_values();
+        data.extend(self.read_value_range(object_map, start..total)?)?;
+        Ok(data)
+    }
+
+    /// Iterate over the values of a timestamp channel and a numeric channel together as
+    /// (time, value) pairs, e.g. a group's `Time` channel and one of its data channels. Both
+    /// are streamed a block at a time, so neither is loaded whole. Fails before reading any
+    /// values with `ChannelLengthMismatch` if their lengths differ by more than slack values,
+    /// e.g. 1 where one was logged a value further than the other, pairs otherwise stop with
+    /// the shorter channel. The iterator ends after the first error.
+    pub fn zip_channels<'a, P: Into<ObjectPathRef<'a>>, Q: Into<ObjectPathRef<'a>>>(
+        &self,
+        time_path: P,
+        value_path: Q,
+        slack: u64,
+    ) -> Result<impl Iterator<Item = Result<(TimeStamp, f64)>> + '_> {
+        let time_map = self.timestamp_object_map(time_path.into().as_str())?;
+        let value_map = self.numeric_object_map(value_path.into().as_str())?;
+        let (time_len, value_len) = (time_map.total_values(), value_map.total_values());
+        if time_len.abs_diff(value_len) > slack {
+            return Err(TdmsError::ChannelLengthMismatch {
+                path: value_map.last_object.object_path.clone(),
+                expected: usize::try_from(time_len).unwrap_or(usize::MAX),
+                found: usize::try_from(value_len).unwrap_or(usize::MAX),
+            });
+        }
+
+        let times = self.value_blocks(time_map).flat_map(|block| {
+            let (times, error) = match block {
+                Ok(DataTypeVec::TimeStamp(times)) => (times, None),
+                Ok(other) => {
+                    let mismatch = TdmsError::ChannelDataTypeMismatch {
+                        expected: DataTypeRaw::TimeStamp,
+                        found: other.data_type(),
+                    };
+                    (Vec::new(), Some(Err(mismatch)))
+                }
+                Err(e) => (Vec::new(), Some(Err(e))),
+            };
+            times.into_iter().map(Ok).chain(error)
+        });
+        let values = self.value_blocks(value_map).flat_map(|block| {
+            let (values, error) = match block {
+                Ok(block) => (block.iter_f64().collect::<Vec<f64>>(), None),
+                Err(e) => (Vec::new(), Some(Err(e))),
+            };
+            values.into_iter().map(Ok).chain(error)
+        });
+        Ok(times.zip(values).scan(false, |failed, (time, value)| {
+            if *failed {
+                return None;
+            }
+            let pair = time.and_then(|time| Ok((time, value?)));
+            *failed = pair.is_err();
+            Some(pair)
+        }))
+    }
+
+    /// Load a timestamp channel as UTC date times. Fails with `ChannelDataTypeMismatch`
+    /// before reading anything if the channel isn't a timestamp channel, and with
+    /// `TimestampOutOfRange` if a value can't be represented as a date time.
+    pub fn load_data_datetime<'a, P: Into<ObjectPathRef<'a>>>(
+        &self,
+        path: P,
+    ) -> Result<Vec<DateTime<Utc>>> {
+        let object_map = self.timestamp_object_map(path.into().as_str())?;
+        let path = object_map.last_object.object_path.as_str();
+        let times = match self.load_data(path)? {
+            DataTypeVec::TimeStamp(times) => times,
+            other => {
+                return Err(TdmsError::ChannelDataTypeMismatch {
+                    expected: DataTypeRaw::TimeStamp,
+                    found: other.data_type(),
+                })
+            }
+        };
+        times
+            .iter()
+            .enumerate()
+            .map(|(index, time)| {
+                time.to_utc().ok_or_else(|| TdmsError::TimestampOutOfRange {
+                    path: path.to_string(),
+                    index: index as u64,
+                })
+            })
+            .collect()
+    }
+
+    /// The map of a channel holding timestamps, failing if it holds any other type
+    fn timestamp_object_map(&self, path: &str) -> Result<&ObjectMap> {
+        let object_map = self.tdms_map.object_map(path)?;
+        let data_type = object_map
+            .last_object
+            .raw_data_type
+            .ok_or(TdmsError::ObjectHasNoRawData)?;
+        if data_type != DataTypeRaw::TimeStamp {
+            return Err(TdmsError::ChannelDataTypeMismatch {
+                expected: DataTypeRaw::TimeStamp,
+                found: data_type,
+            });
+        }
+        Ok(object_map)
+    }
+
+    /// Read a channel's values a block of at most STREAM_BLOCK_VALUES at a time as they're
+    /// reached. No reader is held between blocks.
+    fn value_blocks<'f>(
+        &'f self,
+        object_map: &'f ObjectMap,
+    ) -> impl Iterator<Item = Result<DataTypeVec>> + 'f {
+        let value_size = object_map
+            .last_object
+            .raw_data_type
+            .and_then(|data_type| data_type.size().ok());
+        let pieces: Vec<ReadPair> = object_map
+            .read_map
+            .iter()
+            .flat_map(ReadPair::chunks)
+            .flat_map(|chunk| {
+                chunk
+                    .pieces(value_size, STREAM_BLOCK_VALUES)
+                    .collect::<Vec<ReadPair>>()
+            })
+            .collect();
+        let lossy_strings = self.tdms_map.options.lossy_strings;
+        pieces.into_iter().map(move |piece| {
+            let mut reader = self.readers.take()?;
+            read_pairs_vector(
+                object_map,
+                std::slice::from_ref(&piece),
+                &mut *reader,
+                lossy_strings,
+            )
+        })
+    }
+
+    /// The timestamp channel paired with a channel without waveform timing
+    fn time_channel(&self, object_map: &ObjectMap) -> Result<&ObjectMap> {
+        let object = &object_map.last_object;
+        let missing = || TdmsError::MissingProperty {
+            path: object.object_path.clone(),
+            property: StandardProperty::WfStartTime.name().to_string(),
+        };
+        let (group, channel) = match path_components(&object.object_path).as_slice() {
+            [group, channel] => (group.clone(), channel.clone()),
+            _ => return Err(missing()),
+        };
+        let name = self
+            .get_property_as::<String>(object.object_path.as_str(), "NI_TimeChannel")
+            .unwrap_or_else(|_| format!("{}_time", channel));
+        let time_path = object_path(Some(&group), Some(&name));
+        if !self.tdms_map.all_objects.contains_key(&time_path) {
+            return Err(missing());
+        }
+        self.timestamp_object_map(&time_path)
+    }
+
+    /// The indexes of a timestamp channel's values from start up to end, streaming it a block
+    /// at a time. Its times must be in order.
+    fn time_channel_range(
+        &self,
+        time_map: &ObjectMap,
+        start: &TimeStamp,
+        end: &TimeStamp,
+    ) -> Result<Range<u64>> {
+        let (mut first, mut last) = (0, 0);
+        for block in self.value_blocks(time_map) {
+            let times = match block? {
+                DataTypeVec::TimeStamp(times) => times,
+                other => {
+                    return Err(TdmsError::ChannelDataTypeMismatch {
+                        expected: DataTypeRaw::TimeStamp,
+                        found: other.data_type(),
+                    })
+                }
+            };
+            // Blocks from here on are all at or after end
+            if times.first().is_some_and(|time| !earlier(time, end)) {
+                break;
+            }
+            let before = |bound| times.partition_point(|time| earlier(time, bound)) as u64;
+            first += before(start);
+            last += before(end);
+        }
+        Ok(first..last.max(first))
+    }
+}
+
+/// The ranges of value indexes each timing run has from start up to end, in order
+fn waveform_ranges(
+    object_map: &ObjectMap,
+    start: &DateTime<Utc>,
+    end: &DateTime<Utc>,
+) -> Vec<Range<u64>> {
+    let bounds = object_map.timing_bounds();
+    object_map
+        .timing
+        .iter()
+        .zip(bounds.windows(2))
+        .map(|(run, bounds)| {
+            let (first, next) = (bounds[0], bounds[1].max(bounds[0]));
+            // The index of the first value at or after time, allowing for rounding
+            let index = |time: &DateTime<Utc>| {
+                let offset = seconds_since(&run.start, time) / run.increment;
+                let offset = (offset - 1e-9).ceil().clamp(0.0, (next - first) as f64);
+                first + offset as u64
+            };
+            index(start)..index(end)
+        })
+        .collect()
+}
+
+impl DataTypeVec {
+    /// Append count values for a gap, as fill says
+    fn fill_gap(&mut self, count: usize, fill: FillPolicy) {
+        let len = self.len() + count;
+        match (self, fill) {
+            (DataTypeVec::Float(datavec), FillPolicy::Nan) => datavec.resize(len, f32::NAN),
+            (DataTypeVec::Double(datavec), FillPolicy::Nan) => datavec.resize(len, f64::NAN),
+            (datavec, FillPolicy::Nan) | (datavec, FillPolicy::Zero) => datavec.pad(len),
+            (datavec, FillPolicy::Repeat) => match datavec {
+                DataTypeVec::Void(datavec) => datavec.resize(len, ()),
+                DataTypeVec::Boolean(datavec) => repeat_last(datavec, len),
+                DataTypeVec::I8(datavec) => repeat_last(datavec, len),
+                DataTypeVec::I16(datavec) => repeat_last(datavec, len),
+                DataTypeVec::I32(datavec) => repeat_last(datavec, len),
+                DataTypeVec::I64(datavec) => repeat_last(datavec, len),
+                DataTypeVec::U8(datavec) => repeat_last(datavec, len),
+                DataTypeVec::U16(datavec) => repeat_last(datavec, len),
+                DataTypeVec::U32(datavec) => repeat_last(datavec, len),
+                DataTypeVec::U64(datavec) => repeat_last(datavec, len),
+                DataTypeVec::Float(datavec) => repeat_last(datavec, len),
+                DataTypeVec::Double(datavec) => repeat_last(datavec, len),
+                DataTypeVec::TdmsString(datavec) => repeat_last(datavec, len),
+                DataTypeVec::TimeStamp(datavec) => repeat_last(datavec, len),
+            },
+        }
+    }
+}
+
+/// Extend values to len with copies of the last value, the default if there are none
+fn repeat_last<T: Clone + Default>(values: &mut Vec<T>, len: usize) {
+    let last = values.last().cloned().unwrap_or_default();
+    values.resize(len, last);
+}
+
+/// Seconds from a TDMS timestamp to a date time, negative if the date time is earlier
+fn seconds_since(from: &TimeStamp, to: &DateTime<Utc>) -> f64 {
+    seconds_between(from, &timestamp(to))
+}
+
+/// Seconds from one TDMS timestamp to another, negative if to is earlier
+fn seconds_between(from: &TimeStamp, to: &TimeStamp) -> f64 {
+    let fraction = |radix: u64| radix as f64 / 2f64.powi(64);
+    (to.epoch - from.epoch) as f64 + fraction(to.radix) - fraction(from.radix)
+}
+
+/// The timestamp seconds after time, which may be negative
+fn add_seconds(time: &TimeStamp, seconds: f64) -> TimeStamp {
+    let whole = seconds.floor();
+    let radix = ((seconds - whole) * 2f64.powi(64)) as u64;
+    let (radix, carry) = time.radix.overflowing_add(radix);
+    TimeStamp {
+        epoch: time.epoch + whole as i64 + carry as i64,
+        radix,
+    }
+}
+
+/// A date time as a TDMS timestamp, seconds since 1904 with fractions in units of 2^-64 s
+fn timestamp(time: &DateTime<Utc>) -> TimeStamp {
+    let nanos = time.timestamp_subsec_nanos() as u128;
+    TimeStamp {
+        epoch: time.timestamp() + LABVIEW_EPOCH_OFFSET,
+        radix: ((nanos << 64) / 1_000_000_000) as u64,
+    }
+}
+
+fn earlier(a: &TimeStamp, b: &TimeStamp) -> bool {
+    (a.epoch, a.radix) < (b.epoch, b.radix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tdms_datatypes::DataType;
+    use crate::test_util::{ramp, SegmentBuilder, TdmsBuilder};
+    use crate::tests::open_bytes;
+    use crate::ReadOptions;
+    use std::io::Cursor;
+
+    const TIME: &str = "/'group'/'Time'";
+    const VOLTS: &str = "/'group'/'volts'";
+
+    /// The timestamp secs whole seconds after the Unix epoch
+    fn unix_timestamp(secs: i64) -> TimeStamp {
+        TimeStamp {
+            epoch: LABVIEW_EPOCH_OFFSET + secs,
+            radix: 0,
+        }
+    }
+
+    fn unix(secs: f64) -> DateTime<Utc> {
+        DateTime::from_timestamp_millis((secs * 1000.0) as i64).unwrap()
+    }
+
+    fn ramp_f64(start: u64, len: usize) -> Vec<f64> {
+        (start..start + len as u64)
+            .map(|value| value as f64)
+            .collect()
+    }
+
+    /// Two segments of ten volts values a second apart, the second starting 5 s after the
+    /// first ends, at 15 s
+    fn waveform_file() -> TdmsFile<Cursor<Vec<u8>>> {
+        let segment = |start_secs, first_value| {
+            SegmentBuilder::new()
+                .channel(VOLTS, ramp(DataTypeRaw::DoubleFloat, first_value, 10))
+                .property(
+                    "wf_start_time",
+                    DataType::TimeStamp(unix_timestamp(start_secs)),
+                )
+                .property("wf_increment", DataType::Double(1.0))
+        };
+        let bytes = TdmsBuilder::new()
+            .segment(segment(0, 0))
+            .segment(segment(15, 10))
+            .build();
+        TdmsFile::new(Cursor::new(bytes)).unwrap()
+    }
+
+    /// A volts channel without waveform timing, with times in a timestamp channel at path,
+    /// one a second across three segments
+    fn time_channel_file(time_path: &str, time_channel_property: Option<&str>) -> Vec<u8> {
+        let segment = |start: u64| {
+            let times = (start..start + 10).map(|secs| unix_timestamp(secs as i64));
+            let mut segment = SegmentBuilder::new()
+                .channel(time_path, DataTypeVec::TimeStamp(times.collect()))
+                .channel(VOLTS, ramp(DataTypeRaw::DoubleFloat, start, 10));
+            if let Some(name) = time_channel_property {
+                segment = segment.property("NI_TimeChannel", DataType::TdmsString(name.into()));
+            }
+            segment
+        };
+        TdmsBuilder::new()
+            .segment(segment(0))
+            .segment(segment(10))
+            .segment(segment(20))
+            .build()
+    }
+
+    #[test]
+    fn load_time_range_follows_waveform_timing() {
+        let tdms = waveform_file();
+        let load = |start, end| tdms.load_time_range(VOLTS, unix(start), unix(end)).unwrap();
+
+        assert_eq!(load(2.5, 5.0), DataTypeVec::Double(vec![3.0, 4.0]));
+        // Across the gap into the second segment's restarted timing
+        assert_eq!(
+            load(8.0, 17.0),
+            DataTypeVec::Double(vec![8.0, 9.0, 10.0, 11.0])
+        );
+        assert_eq!(load(11.0, 14.0), DataTypeVec::Double(vec![]));
+        assert_eq!(load(-10.0, -5.0), DataTypeVec::Double(vec![]));
+        assert_eq!(load(100.0, 200.0), DataTypeVec::Double(vec![]));
+        assert_eq!(load(-10.0, 100.0), ramp(DataTypeRaw::DoubleFloat, 0, 20));
+    }
+
+    #[test]
+    fn load_time_range_searches_a_paired_time_channel() {
+        let expected = ramp(DataTypeRaw::DoubleFloat, 12, 13);
+        for bytes in [
+            time_channel_file("/'group'/'volts_time'", None),
+            time_channel_file(TIME, Some("Time")),
+        ] {
+            let tdms = TdmsFile::new(Cursor::new(bytes)).unwrap();
+            let data = tdms.load_time_range(VOLTS, unix(11.5), unix(25.0));
+            assert_eq!(data.unwrap(), expected);
+            let data = tdms.load_time_range(VOLTS, unix(40.0), unix(50.0));
+            assert_eq!(data.unwrap(), DataTypeVec::Double(vec![]));
+        }
+    }
+
+    #[test]
+    fn load_time_range_needs_timing() {
+        // Times in a channel neither named for volts nor named by its properties
+        let bytes = time_channel_file(TIME, None);
+        let tdms = TdmsFile::new(Cursor::new(bytes)).unwrap();
+        match tdms.load_time_range(VOLTS, unix(0.0), unix(1.0)) {
+            Err(TdmsError::MissingProperty { path, property }) => {
+                assert_eq!(path, VOLTS);
+                assert_eq!(property, "wf_start_time");
+            }
+            other => panic!("expected a missing property, got {:?}", other),
+        }
+    }
+
+    /// Three segments of time and volts values, the last missing the given number of volts
+    fn zip_file(missing_volts: usize) -> Vec<u8> {
+        let segment = |start, volts| {
+            SegmentBuilder::new()
+                .channel(TIME, ramp(DataTypeRaw::TimeStamp, start, 10))
+                .channel(VOLTS, ramp(DataTypeRaw::DoubleFloat, start, volts))
+        };
+        TdmsBuilder::new()
+            .segment(segment(0, 10))
+            .segment(segment(10, 10))
+            .segment(segment(20, 10 - missing_volts))
+            .build()
+    }
+
+    #[test]
+    fn discontinuities_find_the_gap_between_segments() {
+        let tdms = waveform_file();
+        let expected = Discontinuity {
+            segment: 1,
+            index: 10,
+            expected: unix_timestamp(10),
+            actual: unix_timestamp(15),
+            gap: 5.0,
+            missing_values: 5,
+        };
+        assert_eq!(tdms.discontinuities(VOLTS).unwrap(), vec![expected]);
+
+        let bytes = time_channel_file(TIME, None);
+        let tdms = TdmsFile::new(Cursor::new(bytes)).unwrap();
+        assert!(matches!(
+            tdms.discontinuities(VOLTS),
+            Err(TdmsError::MissingProperty { .. })
+        ));
+    }
+
+    #[test]
+    fn gap_filled_data_keeps_a_uniform_time_base() {
+        let tdms = waveform_file();
+        let filled = |fill| match tdms.load_data_gap_filled(VOLTS, fill).unwrap() {
+            DataTypeVec::Double(values) => values,
+            other => panic!("expected doubles, got {:?}", other),
+        };
+
+        let values = filled(FillPolicy::Nan);
+        assert_eq!(values.len(), 25);
+        assert_eq!(values[..10], ramp_f64(0, 10));
+        assert!(values[10..15].iter().all(|value| value.is_nan()));
+        assert_eq!(values[15..], ramp_f64(10, 10));
+
+        let values = filled(FillPolicy::Zero);
+        assert_eq!(values[9..16], [9.0, 0.0, 0.0, 0.0, 0.0, 0.0, 10.0]);
+
+        let values = filled(FillPolicy::Repeat);
+        assert_eq!(values[9..16], [9.0, 9.0, 9.0, 9.0, 9.0, 9.0, 10.0]);
+    }
+
+    #[test]
+    fn zip_channels_pairs_values_across_segments() {
+        let tdms = TdmsFile::new(Cursor::new(zip_file(0))).unwrap();
+        let pairs: Vec<(TimeStamp, f64)> = tdms
+            .zip_channels(TIME, VOLTS, 0)
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+        let expected: Vec<(TimeStamp, f64)> = (0..30)
+            .map(|v| (TimeStamp { epoch: v, radix: 0 }, v as f64))
+            .collect();
+        assert_eq!(pairs, expected);
+    }
+
+    #[test]
+    fn zip_channels_checks_lengths_against_slack() {
+        let tdms = TdmsFile::new(Cursor::new(zip_file(2))).unwrap();
+        match tdms.zip_channels(TIME, VOLTS, 1) {
+            Err(TdmsError::ChannelLengthMismatch {
+                path,
+                expected,
+                found,
+            }) => {
+                assert_eq!(path, VOLTS);
+                assert_eq!((expected, found), (30, 28));
+            }
+            Err(e) => panic!("expected a length mismatch, got {:?}", e),
+            Ok(_) => panic!("expected a length mismatch"),
+        }
+
+        // Within the slack pairs stop with the shorter channel
+        let pairs = tdms.zip_channels(TIME, VOLTS, 2).unwrap();
+        let last = pairs.map(|pair| pair.unwrap()).last().unwrap();
+        assert_eq!(
+            last,
+            (
+                TimeStamp {
+                    epoch: 27,
+                    radix: 0
+                },
+                27.0
+            )
+        );
+    }
+
+    #[test]
+    fn zip_channels_ends_after_an_error() {
+        let bytes = zip_file(0);
+        let (path, tdms) = open_bytes(&bytes, ReadOptions::new());
+        let tdms = tdms.unwrap();
+        // Cut the last segment's raw data after mapping, so reading it fails
+        let file = std::fs::OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_len(bytes.len() as u64 - 40).unwrap();
+
+        let pairs: Vec<Result<(TimeStamp, f64)>> =
+            tdms.zip_channels(TIME, VOLTS, 0).unwrap().collect();
+        assert_eq!(pairs.len(), 21);
+        assert!(pairs[..20].iter().all(Result::is_ok));
+        assert!(pairs[20].is_err());
+    }
+
+    #[test]
+    fn datetimes_are_the_utc_instants_written() {
+        let times = vec![
+            TimeStamp { epoch: 0, radix: 0 },
+            TimeStamp {
+                epoch: LABVIEW_EPOCH_OFFSET - 1,
+                radix: 1 << 63,
+            },
+            TimeStamp {
+                epoch: LABVIEW_EPOCH_OFFSET + 1_709_210_096,
+                radix: 1 << 62,
+            },
+        ];
+        let bytes = TdmsBuilder::new()
+            .segment(
+                SegmentBuilder::new()
+                    .channel(TIME, DataTypeVec::TimeStamp(times))
+                    .channel(VOLTS, ramp(DataTypeRaw::DoubleFloat, 0, 3)),
+            )
+            .segment(SegmentBuilder::new().channel(
+                TIME,
+                DataTypeVec::TimeStamp(vec![TimeStamp {
+                    epoch: i64::MIN,
+                    radix: 0,
+                }]),
+            ))
+            .build();
+        let tdms = TdmsFile::new(Cursor::new(bytes.clone())).unwrap();
+
+        let expected: Vec<DateTime<Utc>> = [
+            "1904-01-01T00:00:00Z",
+            "1969-12-31T23:59:59.5Z",
+            "2024-02-29T12:34:56.25Z",
+        ]
+        .iter()
+        .map(|time| time.parse().unwrap())
+        .collect();
+        let first_segment = TdmsFile::new(Cursor::new(
+            bytes[..tdms.segments()[1].start_index as usize].to_vec(),
+        ))
+        .unwrap();
+        assert_eq!(first_segment.load_data_datetime(TIME).unwrap(), expected);
+
+        assert!(matches!(
+            tdms.load_data_datetime(TIME),
+            Err(TdmsError::TimestampOutOfRange { index: 3, .. })
+        ));
+        assert!(matches!(
+            tdms.load_data_datetime(VOLTS),
+            Err(TdmsError::ChannelDataTypeMismatch {
+                expected: DataTypeRaw::TimeStamp,
+                found: DataTypeRaw::DoubleFloat
+            })
+        ));
+    }
+}
@@ -0,0 +1,206 @@
+use std::io::{Read, Seek};
+
+use indexmap::IndexMap;
+
+use crate::tdms_path::{path_components, quote_component};
+use crate::TdmsFile;
+
+/// An object in an `ObjectTree`, the root, a group or a channel, with those below it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectNode {
+    name: String,
+    path: String,
+    written: bool,
+    children: Vec<ObjectNode>,
+}
+
+impl ObjectNode {
+    /// The object's name, empty for the root
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The object's path
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Whether the file lists the object itself, rather than only objects below it e.g. a
+    /// group only named in its channels' paths
+    pub fn is_written(&self) -> bool {
+        self.written
+    }
+
+    /// The objects directly below, in the order they first appear in the file
+    pub fn children(&self) -> &[ObjectNode] {
+        &self.children
+    }
+
+    /// The object directly below with the given name
+    pub fn child(&self, name: &str) -> Option<&ObjectNode> {
+        self.children.iter().find(|child| child.name == name)
+    }
+}
+
+/// The objects of a file nested by path, root then groups then channels, each level in the
+/// order its objects first appear in the file
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectTree {
+    root: ObjectNode,
+}
+
+impl ObjectTree {
+    /// The root object, `/`
+    pub fn root(&self) -> &ObjectNode {
+        &self.root
+    }
+
+    /// The groups, the objects directly below the root
+    pub fn groups(&self) -> &[ObjectNode] {
+        &self.root.children
+    }
+
+    /// The object at path
+    pub fn find(&self, path: &str) -> Option<&ObjectNode> {
+        self.iter().find(|node| node.path == path)
+    }
+
+    /// Iterate over the objects depth first, each before those below it, starting with the root
+    pub fn iter(&self) -> impl Iterator<Item = &ObjectNode> {
+        let mut stack = vec![&self.root];
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            stack.extend(node.children.iter().rev());
+            Some(node)
+        })
+    }
+}
+
+/// A node while the tree is built, children keyed by name
+#[derive(Default)]
+struct Branch {
+    path: String,
+    written: bool,
+    children: IndexMap<String, Branch>,
+}
+
+impl Branch {
+    fn into_node(self, name: String) -> ObjectNode {
+        ObjectNode {
+            name,
+            path: self.path,
+            written: self.written,
+            children: self
+                .children
+                .into_iter()
+                .map(|(name, branch)| branch.into_node(name))
+                .collect(),
+        }
+    }
+}
+
+impl<R: Read + Seek> TdmsFile<R> {
+    /// The file's objects nested by path, e.g. to show as a tree. Groups only named in their
+    /// channels' paths are included, marked as not written, and objects whose paths can't be
+    /// parsed are placed below the root named by their whole path.
+    pub fn object_tree(&self) -> ObjectTree {
+        let mut root = Branch {
+            path: "/".to_string(),
+            ..Branch::default()
+        };
+        for path in self.tdms_map.all_objects.keys() {
+            let mut components = path_components(path);
+            if components.is_empty() && path != "/" {
+                components.push(path.clone());
+            }
+
+            let mut branch = &mut root;
+            for component in components {
+                let parent_path = match branch.path.as_str() {
+                    "/" => "",
+                    parent_path => parent_path,
+                };
+                let child_path = parent_path.to_string() + &quote_component(&component);
+                branch = branch.children.entry(component).or_insert_with(|| Branch {
+                    path: child_path,
+                    ..Branch::default()
+                });
+            }
+            branch.path = path.clone();
+            branch.written = true;
+        }
+        ObjectTree {
+            root: root.into_node(String::new()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tdms_datatypes::DataTypeRaw;
+    use crate::test_util::{ramp, SegmentBuilder, TdmsBuilder};
+    use std::io::Cursor;
+
+    #[test]
+    fn channels_nest_below_their_groups_in_file_order() {
+        let bytes = TdmsBuilder::new()
+            .segment(
+                SegmentBuilder::new()
+                    .object("/")
+                    .object("/'b'")
+                    .channel("/'b'/'x'", ramp(DataTypeRaw::I32, 0, 2))
+                    .channel("/'a'/'y'", ramp(DataTypeRaw::I32, 0, 2)),
+            )
+            .segment(
+                SegmentBuilder::new()
+                    .channel("/'b'/'it''s'", ramp(DataTypeRaw::I32, 0, 2))
+                    .channel("/'a'/'z'", ramp(DataTypeRaw::I32, 0, 2)),
+            )
+            .build();
+        let tdms = TdmsFile::new(Cursor::new(bytes)).unwrap();
+        let tree = tdms.object_tree();
+
+        let root = tree.root();
+        assert_eq!(
+            (root.name(), root.path(), root.is_written()),
+            ("", "/", true)
+        );
+        let groups: Vec<&str> = tree.groups().iter().map(ObjectNode::name).collect();
+        assert_eq!(groups, ["b", "a"]);
+
+        let b = root.child("b").unwrap();
+        assert!(b.is_written());
+        let channels: Vec<(&str, &str)> = b
+            .children()
+            .iter()
+            .map(|channel| (channel.name(), channel.path()))
+            .collect();
+        assert_eq!(channels, [("x", "/'b'/'x'"), ("it's", "/'b'/'it''s'")]);
+        assert!(b
+            .children()
+            .iter()
+            .all(|channel| channel.children().is_empty()));
+
+        // Group a is only named in its channels' paths
+        let a = tree.find("/'a'").unwrap();
+        assert_eq!(a.name(), "a");
+        assert!(!a.is_written());
+        assert!(a.child("z").unwrap().is_written());
+
+        let paths: Vec<&str> = tree.iter().map(ObjectNode::path).collect();
+        assert_eq!(
+            paths,
+            [
+                "/",
+                "/'b'",
+                "/'b'/'x'",
+                "/'b'/'it''s'",
+                "/'a'",
+                "/'a'/'y'",
+                "/'a'/'z'"
+            ]
+        );
+        assert!(tree.find("/'c'").is_none());
+    }
+}
@@ -0,0 +1,90 @@
+use std::fmt;
+use std::io::{Read, Seek};
+
+use crate::TdmsFile;
+
+/// A way the file breaks the spec that mapping it tolerated, as reported by
+/// `TdmsFile::validate`
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationIssue {
+    /// A property listed more than once in a single listing of an object, so the object has
+    /// fewer properties than the listing's count. The last value listed is kept.
+    DuplicateProperty { path: String, property: String },
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValidationIssue::DuplicateProperty { path, property } => write!(
+                f,
+                "{} lists the property {:?} more than once, the last value is kept",
+                path, property
+            ),
+        }
+    }
+}
+
+impl<R: Read + Seek> TdmsFile<R> {
+    /// Check the mapped file for anything that breaks the spec but didn't stop it being read,
+    /// in the order the objects appear. An empty report means nothing was found.
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        for (path, object_map) in &self.tdms_map.all_objects {
+            for property in &object_map.last_object.duplicate_properties {
+                issues.push(ValidationIssue::DuplicateProperty {
+                    path: path.clone(),
+                    property: property.clone(),
+                });
+            }
+        }
+        issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tdms_datatypes::{DataType, DataTypeRaw};
+    use crate::test_util::{ramp, SegmentBuilder, TdmsBuilder};
+    use std::io::Cursor;
+
+    const CHANNEL: &str = "/'group'/'channel'";
+
+    fn validate(segment: SegmentBuilder) -> Vec<ValidationIssue> {
+        let bytes = TdmsBuilder::new().segment(segment).build();
+        TdmsFile::new(Cursor::new(bytes)).unwrap().validate()
+    }
+
+    #[test]
+    fn clean_files_have_no_issues() {
+        let segment = SegmentBuilder::new()
+            .object("/")
+            .property("name", DataType::TdmsString("run".into()))
+            .channel(CHANNEL, ramp(DataTypeRaw::I32, 0, 4))
+            .property("unit_string", DataType::TdmsString("V".into()));
+        assert_eq!(validate(segment), []);
+    }
+
+    #[test]
+    fn properties_listed_twice_are_reported_once() {
+        let segment = SegmentBuilder::new()
+            .channel(CHANNEL, ramp(DataTypeRaw::I32, 0, 4))
+            .property("gain", DataType::Double(1.0))
+            .property("gain", DataType::Double(2.0))
+            .property("gain", DataType::Double(3.0))
+            .property("offset", DataType::Double(0.0));
+
+        let issues = validate(segment);
+        assert_eq!(
+            issues,
+            [ValidationIssue::DuplicateProperty {
+                path: CHANNEL.to_string(),
+                property: "gain".to_string(),
+            }]
+        );
+        assert_eq!(
+            issues[0].to_string(),
+            "/'group'/'channel' lists the property \"gain\" more than once, the last value is kept"
+        );
+    }
+}
@@ -0,0 +1,297 @@
+use std::fmt;
+
+use crate::tdms_error::TdmsError;
+
+/// Something in the file breaking the spec that mapping carried on past, see
+/// `TdmsFile::warnings`. With `ParseMode::Strict` each is an error instead.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TdmsWarning {
+    /// The index of the segment being mapped
+    pub segment: usize,
+    /// The byte position in the file being mapped, a .tdms_index file while mapping from one
+    pub offset: u64,
+    pub reason: WarningReason,
+}
+
+impl fmt::Display for TdmsWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Segment {} at byte {}: {}",
+            self.segment, self.offset, self.reason
+        )
+    }
+}
+
+/// Why a warning was raised
+#[derive(Debug, Clone, PartialEq)]
+pub enum WarningReason {
+    /// The segment was written by TDMS 1.0, it's parsed as 2.0. Other versions fail with
+    /// `UnsupportedVersion`.
+    UnexpectedVersion { version: u32 },
+    /// The segment starting at start_index runs past the end of the file. If repaired it was
+    /// cut back to its complete chunks, otherwise reading its missing data fails.
+    TruncatedSegment { start_index: u64, repaired: bool },
+    /// The final segment, starting at start_index, ends before its meta data does so isn't
+    /// mapped
+    UnreadableSegment { start_index: u64 },
+    /// The segment's objects end at actual rather than at expected where its raw data starts
+    MetadataLengthMismatch { expected: u64, actual: u64 },
+    /// The segment at first gives the next segment at second, inside it, so it was skipped
+    OverlappingSegments { first: u64, second: u64 },
+    /// A property listed more than once in a single listing of an object, the last value
+    /// listed is kept
+    DuplicateProperty { path: String, property: String },
+    /// An object's raw data index has a length the spec doesn't give for its data type, 20
+    /// bytes or 28 for strings
+    UnexpectedIndexLength { path: String, index_info_len: u32 },
+    /// An object's raw data index gives type_id, which isn't a known data type, so its data
+    /// can't be loaded. Unless the index gave the size of its data, or where the segment is
+    /// interleaved, none of the segment's raw data can be read. See
+    /// `ReadOptions::skip_unknown_types`.
+    UnknownDataType { path: String, type_id: u32 },
+}
+
+impl fmt::Display for WarningReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WarningReason::UnexpectedVersion { version } => {
+                write!(f, "TDMS version {}, parsed as version 2.0", version)
+            }
+            WarningReason::TruncatedSegment {
+                start_index,
+                repaired,
+            } => {
+                write!(f, "The segment at {} is truncated", start_index)?;
+                if *repaired {
+                    write!(f, ", keeping its complete chunks")?;
+                }
+                Ok(())
+            }
+            WarningReason::UnreadableSegment { start_index } => write!(
+                f,
+                "The final segment at {} is cut short before its raw data",
+                start_index
+            ),
+            WarningReason::MetadataLengthMismatch { expected, actual } => write!(
+                f,
+                "Meta data ends at {} rather than {}, skipping to the raw data",
+                actual, expected
+            ),
+            WarningReason::OverlappingSegments { first, second } => write!(
+                f,
+                "Skipping the segment at {}, the next segment it gives at {} overlaps it",
+                first, second
+            ),
+            WarningReason::DuplicateProperty { path, property } => write!(
+                f,
+                "{} lists the property {:?} more than once, the last value is kept",
+                path, property
+            ),
+            WarningReason::UnexpectedIndexLength {
+                path,
+                index_info_len,
+            } => write!(
+                f,
+                "{} has a raw data index of unexpected length {}",
+                path, index_info_len
+            ),
+            WarningReason::UnknownDataType { path, type_id } => write!(
+                f,
+                "{} has raw data of unknown type {:#x}, its data is skipped",
+                path, type_id
+            ),
+        }
+    }
+}
+
+impl From<WarningReason> for TdmsError {
+    fn from(reason: WarningReason) -> Self {
+        match reason {
+            WarningReason::UnexpectedVersion { version } => {
+                TdmsError::UnsupportedVersion { version }
+            }
+            WarningReason::TruncatedSegment { start_index, .. }
+            | WarningReason::UnreadableSegment { start_index } => {
+                TdmsError::TruncatedSegment { start_index }
+            }
+            WarningReason::MetadataLengthMismatch { expected, actual } => {
+                TdmsError::MetadataLengthMismatch { expected, actual }
+            }
+            WarningReason::OverlappingSegments { first, second } => {
+                TdmsError::OverlappingSegments { first, second }
+            }
+            WarningReason::DuplicateProperty { path, property } => {
+                TdmsError::DuplicateProperty { path, property }
+            }
+            WarningReason::UnexpectedIndexLength {
+                path,
+                index_info_len,
+            } => TdmsError::UnexpectedIndexLength {
+                path,
+                index_info_len,
+            },
+            WarningReason::UnknownDataType { .. } => TdmsError::RawDataTypeNotFound,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tdms_datatypes::{DataType, DataTypeRaw};
+    use crate::test_util::{ramp, SegmentBuilder, TdmsBuilder};
+    use crate::tests::open_bytes;
+    use crate::{ParseMode, ReadOptions, HEADER_LEN, TDMS_VERSION_1};
+
+    const VOLTS: &str = "/'group'/'volts'";
+
+    fn volts(start: u64) -> SegmentBuilder {
+        SegmentBuilder::new().channel(VOLTS, ramp(DataTypeRaw::DoubleFloat, start, 10))
+    }
+
+    /// A file whose second segment is the one given, and the address of that segment
+    fn second_segment(second: SegmentBuilder) -> (Vec<u8>, u64) {
+        let address = TdmsBuilder::new().segment(volts(0)).build().len() as u64;
+        let bytes = TdmsBuilder::new().segment(volts(0)).segment(second).build();
+        (bytes, address)
+    }
+
+    /// Files breaking the spec in the second segment, with the options needed to carry on
+    /// past it and the warnings that gives
+    fn anomalies() -> Vec<(Vec<u8>, ReadOptions, Vec<TdmsWarning>)> {
+        let warning = |offset, reason| TdmsWarning {
+            segment: 1,
+            offset,
+            reason,
+        };
+        let mut anomalies = Vec::new();
+
+        let (bytes, address) = second_segment(volts(10).version(TDMS_VERSION_1));
+        let reason = WarningReason::UnexpectedVersion {
+            version: TDMS_VERSION_1,
+        };
+        anomalies.push((
+            bytes,
+            ReadOptions::new(),
+            vec![warning(address + 8, reason)],
+        ));
+
+        let (mut bytes, address) = second_segment(volts(10).chunks(2));
+        bytes.truncate(bytes.len() - 12);
+        let reason = WarningReason::TruncatedSegment {
+            start_index: address,
+            repaired: true,
+        };
+        anomalies.push((bytes, ReadOptions::new(), vec![warning(address, reason)]));
+
+        let (mut bytes, address) = second_segment(volts(10));
+        bytes.truncate(address as usize + HEADER_LEN as usize + 8);
+        // Its meta data runs past the end of the file, as its raw data must too
+        let truncated = WarningReason::TruncatedSegment {
+            start_index: address,
+            repaired: true,
+        };
+        let reason = WarningReason::UnreadableSegment {
+            start_index: address,
+        };
+        let warnings = vec![warning(address, truncated), warning(address, reason)];
+        anomalies.push((bytes, ReadOptions::new(), warnings));
+
+        let (bytes, address) = second_segment(volts(10));
+        let at = address as usize + 20;
+        let meta_data_len = u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap());
+        let meta_data_end = address + HEADER_LEN + meta_data_len;
+        let (bytes, _) = second_segment(volts(10).raw_data_offset(Some(meta_data_len + 4)));
+        let reason = WarningReason::MetadataLengthMismatch {
+            expected: meta_data_end + 4,
+            actual: meta_data_end,
+        };
+        anomalies.push((
+            bytes,
+            ReadOptions::new(),
+            vec![warning(meta_data_end, reason)],
+        ));
+
+        let (bytes, address) = second_segment(
+            volts(10)
+                .property("gain", DataType::Double(1.0))
+                .property("gain", DataType::Double(2.0)),
+        );
+        let reason = WarningReason::DuplicateProperty {
+            path: VOLTS.to_string(),
+            property: "gain".to_string(),
+        };
+        anomalies.push((
+            bytes,
+            ReadOptions::new(),
+            vec![warning(address + HEADER_LEN, reason)],
+        ));
+
+        let (mut bytes, address) = second_segment(volts(10));
+        let at = address as usize + HEADER_LEN as usize + 8 + VOLTS.len();
+        bytes[at..at + 4].copy_from_slice(&28u32.to_le_bytes());
+        let reason = WarningReason::UnexpectedIndexLength {
+            path: VOLTS.to_string(),
+            index_info_len: 28,
+        };
+        anomalies.push((
+            bytes,
+            ReadOptions::new(),
+            vec![warning(address + HEADER_LEN, reason)],
+        ));
+
+        let (mut bytes, address) = second_segment(volts(10));
+        bytes[at + 4..at + 8].copy_from_slice(&0x99u32.to_le_bytes());
+        let reason = WarningReason::UnknownDataType {
+            path: VOLTS.to_string(),
+            type_id: 0x99,
+        };
+        let options = ReadOptions::new().skip_unknown_types(true);
+        anomalies.push((bytes, options, vec![warning(address + HEADER_LEN, reason)]));
+
+        anomalies
+    }
+
+    #[test]
+    fn lenient_parsing_collects_a_warning_for_each_anomaly() {
+        for (bytes, options, warnings) in anomalies() {
+            let options = options.parse_mode(Some(ParseMode::Lenient));
+            let (_path, file) = open_bytes(&bytes, options);
+            assert_eq!(file.unwrap().warnings(), warnings);
+        }
+    }
+
+    #[test]
+    fn strict_parsing_fails_on_each_anomaly() {
+        for (bytes, options, mut warnings) in anomalies() {
+            let options = options.parse_mode(Some(ParseMode::Strict));
+            let (_path, file) = open_bytes(&bytes, options);
+            let error = file.err().unwrap();
+            let expected = TdmsError::from(warnings.remove(0).reason);
+            assert_eq!(error.root_cause().to_string(), expected.to_string());
+        }
+    }
+
+    #[test]
+    fn unknown_versions_fail_in_every_parse_mode() {
+        let (bytes, _) = second_segment(volts(10).version(4714));
+        for parse_mode in [None, Some(ParseMode::Lenient), Some(ParseMode::Strict)] {
+            let options = ReadOptions::new().parse_mode(parse_mode);
+            let (_path, file) = open_bytes(&bytes, options);
+            let error = file.err().unwrap();
+            assert!(matches!(
+                error.root_cause(),
+                TdmsError::UnsupportedVersion { version: 4714 }
+            ));
+            // Naming the segment it's in
+            let message = error.to_string();
+            assert!(
+                message.starts_with("Reading segment 1 failed"),
+                "{}",
+                message
+            );
+            assert!(message.ends_with("TDMS version 4714 isn't supported"));
+        }
+    }
+}
@@ -0,0 +1,330 @@
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use byteorder::{WriteBytesExt, LE};
+
+use crate::tdms_datatypes::DataTypeRaw;
+use crate::tdms_error::{Result, TdmsError};
+use crate::tdms_property::StandardProperty;
+use crate::TdmsFile;
+
+/// Sample formats a WAV file can be written in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WavSampleFormat {
+    Pcm16,
+    Pcm32,
+    Float32,
+}
+
+impl WavSampleFormat {
+    fn bits(&self) -> u16 {
+        match self {
+            WavSampleFormat::Pcm16 => 16,
+            WavSampleFormat::Pcm32 | WavSampleFormat::Float32 => 32,
+        }
+    }
+
+    fn format_tag(&self) -> u16 {
+        match self {
+            WavSampleFormat::Pcm16 | WavSampleFormat::Pcm32 => 1,
+            WavSampleFormat::Float32 => 3,
+        }
+    }
+}
+
+/// Options for `TdmsFile::export_wav`. By default the sample rate is derived from the
+/// channel's `wf_increment`, the format follows the channel's data type and values are not
+/// normalized.
+#[derive(Debug, Clone, Default)]
+pub struct WavOptions {
+    sample_rate: Option<u32>,
+    format: Option<WavSampleFormat>,
+    normalize: bool,
+}
+
+impl WavOptions {
+    pub fn new() -> WavOptions {
+        WavOptions::default()
+    }
+
+    /// Use this sample rate rather than deriving it from `wf_increment`
+    pub fn sample_rate(mut self, sample_rate: Option<u32>) -> Self {
+        self.sample_rate = sample_rate;
+        self
+    }
+
+    /// Write this sample format rather than one matching the channel, i.e. Pcm16 for I16,
+    /// Pcm32 for I32 and Float32 for float channels
+    pub fn format(mut self, format: Option<WavSampleFormat>) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Scale the samples so the largest magnitude is full scale. Otherwise integer channels
+    /// are full scale at the limits of their type and float channels at +-1.0, with anything
+    /// beyond clipped when writing PCM.
+    pub fn normalize(mut self, normalize: bool) -> Self {
+        self.normalize = normalize;
+        self
+    }
+}
+
+impl<R: Read + Seek> TdmsFile<R> {
+    /// Export an I16, I32, F32 or F64 channel as a single channel WAV file
+    pub fn export_wav<W: Write + Seek>(
+        &self,
+        path: &str,
+        out: W,
+        options: WavOptions,
+    ) -> Result<()> {
+        self.export_wav_channels(&[path], out, options)
+    }
+
+    /// Export several channels of equal length as one WAV file with interleaved samples, in
+    /// the order given. The sample rate and default format are taken from the first channel,
+    /// nothing is written if no channels are given.
+    pub fn export_wav_channels<W: Write + Seek>(
+        &self,
+        paths: &[&str],
+        mut out: W,
+        options: WavOptions,
+    ) -> Result<()> {
+        let first = match paths.first() {
+            Some(first) => *first,
+            None => return Ok(()),
+        };
+        let sample_rate = match options.sample_rate {
+            Some(sample_rate) => sample_rate,
+            None => self.wf_sample_rate(first)?,
+        };
+
+        // Every channel is scaled to +-1.0 full scale before being written in the output format
+        let mut channels = Vec::with_capacity(paths.len());
+        let mut default_format = None;
+        for path in paths {
+            // Check the type before loading so a huge unsupported channel isn't read for nothing
+            let (format, full_scale) = self.wav_format(path)?;
+            default_format.get_or_insert(format);
+            let samples: Vec<f64> = self
+                .load_data(*path)?
+                .iter_f64()
+                .map(|sample| sample / full_scale)
+                .collect();
+            if let Some(expected) = channels.first().map(Vec::len) {
+                if samples.len() != expected {
+                    return Err(TdmsError::ChannelLengthMismatch {
+                        path: path.to_string(),
+                        expected,
+                        found: samples.len(),
+                    });
+                }
+            }
+            channels.push(samples);
+        }
+        let format = options.format.or(default_format).unwrap();
+
+        let scale = if options.normalize {
+            let peak = channels
+                .iter()
+                .flatten()
+                .fold(0.0f64, |peak, sample| peak.max(sample.abs()));
+            if peak > 0.0 {
+                1.0 / peak
+            } else {
+                1.0
+            }
+        } else {
+            1.0
+        };
+
+        let start = out.stream_position()?;
+        write_wav_header(&mut out, format, paths.len() as u16, sample_rate, 0)?;
+        for i in 0..channels[0].len() {
+            for channel in &channels {
+                write_sample(&mut out, format, channel[i] * scale)?;
+            }
+        }
+
+        // Now the data is written its size is known, go back and fill it in
+        let end = out.stream_position()?;
+        out.seek(SeekFrom::Start(start))?;
+        let data_size = (end - start - WAV_HEADER_LEN) as u32;
+        write_wav_header(&mut out, format, paths.len() as u16, sample_rate, data_size)?;
+        out.seek(SeekFrom::Start(end))?;
+        Ok(())
+    }
+
+    /// The sample format matching a channel's data type, and the value that is full scale
+    fn wav_format(&self, path: &str) -> Result<(WavSampleFormat, f64)> {
+        let object_map = self.tdms_map.object_map(path)?;
+        match object_map.last_object.raw_data_type {
+            Some(DataTypeRaw::I16) => Ok((WavSampleFormat::Pcm16, 32768.0)),
+            Some(DataTypeRaw::I32) => Ok((WavSampleFormat::Pcm32, 2147483648.0)),
+            Some(DataTypeRaw::SingleFloat) | Some(DataTypeRaw::DoubleFloat) => {
+                Ok((WavSampleFormat::Float32, 1.0))
+            }
+            Some(data_type) => Err(TdmsError::UnsupportedExportType {
+                path: path.to_string(),
+                data_type,
+            }),
+            None => Err(TdmsError::ObjectHasNoRawData),
+        }
+    }
+
+    /// Sample rate from the channel's wf_increment property, the time between samples
+    fn wf_sample_rate(&self, path: &str) -> Result<u32> {
+        let increment: Option<f64> = self
+            .get_property_as(path, StandardProperty::WfIncrement.name())
+            .ok();
+
+        increment
+            .filter(|increment| *increment > 0.0)
+            .map(|increment| (1.0 / increment).round() as u32)
+            .ok_or_else(|| TdmsError::MissingProperty {
+                path: path.to_string(),
+                property: StandardProperty::WfIncrement.name().to_string(),
+            })
+    }
+}
+
+const WAV_HEADER_LEN: u64 = 44;
+
+fn write_wav_header<W: Write>(
+    out: &mut W,
+    format: WavSampleFormat,
+    no_channels: u16,
+    sample_rate: u32,
+    data_size: u32,
+) -> Result<()> {
+    let block_align = no_channels * format.bits() / 8;
+
+    out.write_all(b"RIFF")?;
+    out.write_u32::<LE>(WAV_HEADER_LEN as u32 - 8 + data_size)?;
+    out.write_all(b"WAVE")?;
+    out.write_all(b"fmt ")?;
+    out.write_u32::<LE>(16)?;
+    out.write_u16::<LE>(format.format_tag())?;
+    out.write_u16::<LE>(no_channels)?;
+    out.write_u32::<LE>(sample_rate)?;
+    out.write_u32::<LE>(sample_rate * block_align as u32)?;
+    out.write_u16::<LE>(block_align)?;
+    out.write_u16::<LE>(format.bits())?;
+    out.write_all(b"data")?;
+    out.write_u32::<LE>(data_size)?;
+    Ok(())
+}
+
+fn write_sample<W: Write>(out: &mut W, format: WavSampleFormat, sample: f64) -> Result<()> {
+    match format {
+        WavSampleFormat::Pcm16 => {
+            let value = (sample * 32768.0)
+                .round()
+                .clamp(i16::MIN as f64, i16::MAX as f64);
+            out.write_i16::<LE>(value as i16)?
+        }
+        WavSampleFormat::Pcm32 => {
+            let value = (sample * 2147483648.0)
+                .round()
+                .clamp(i32::MIN as f64, i32::MAX as f64);
+            out.write_i32::<LE>(value as i32)?
+        }
+        WavSampleFormat::Float32 => out.write_f32::<LE>(sample as f32)?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tdms_datatypes::{DataType, DataTypeVec};
+    use crate::test_util::{ramp, SegmentBuilder, TdmsBuilder};
+    use byteorder::{ByteOrder, LE};
+    use std::io::Cursor;
+
+    const SOUND: &str = "/'audio'/'left'";
+    const RIGHT: &str = "/'audio'/'right'";
+    const NOTES: &str = "/'audio'/'notes'";
+
+    fn file() -> TdmsFile<Cursor<Vec<u8>>> {
+        let left = DataTypeVec::I16(vec![0, 100, -200, i16::MAX, i16::MIN]);
+        let right = DataTypeVec::I16(vec![1, 2, 3, 4, 5]);
+        let bytes = TdmsBuilder::new()
+            .segment(
+                SegmentBuilder::new()
+                    .channel(SOUND, left)
+                    .property("wf_increment", DataType::Double(0.001))
+                    .channel(RIGHT, right)
+                    .channel(NOTES, ramp(DataTypeRaw::TdmsString, 0, 5)),
+            )
+            .build();
+        TdmsFile::new(Cursor::new(bytes)).unwrap()
+    }
+
+    /// The header fields after the RIFF and fmt tags: format tag, channels, sample rate, byte
+    /// rate, block align and bits per sample, then the data size
+    fn header(wav: &[u8]) -> (u16, u16, u32, u32, u16, u16, u32) {
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(LE::read_u32(&wav[4..8]) as usize, wav.len() - 8);
+        assert_eq!(&wav[8..16], b"WAVEfmt ");
+        assert_eq!(LE::read_u32(&wav[16..20]), 16);
+        assert_eq!(&wav[36..40], b"data");
+        (
+            LE::read_u16(&wav[20..22]),
+            LE::read_u16(&wav[22..24]),
+            LE::read_u32(&wav[24..28]),
+            LE::read_u32(&wav[28..32]),
+            LE::read_u16(&wav[32..34]),
+            LE::read_u16(&wav[34..36]),
+            LE::read_u32(&wav[40..44]),
+        )
+    }
+
+    #[test]
+    fn channels_export_at_their_sample_rate() {
+        let mut wav = Cursor::new(Vec::new());
+        file()
+            .export_wav(SOUND, &mut wav, WavOptions::new())
+            .unwrap();
+        let wav = wav.into_inner();
+
+        assert_eq!(header(&wav), (1, 1, 1000, 2000, 2, 16, 10));
+        let mut samples = vec![0i16; 5];
+        LE::read_i16_into(&wav[44..], &mut samples);
+        assert_eq!(samples, [0, 100, -200, i16::MAX, i16::MIN]);
+    }
+
+    #[test]
+    fn several_channels_interleave_in_the_format_asked_for() {
+        let options = WavOptions::new()
+            .sample_rate(Some(48000))
+            .format(Some(WavSampleFormat::Float32));
+        let mut wav = Cursor::new(Vec::new());
+        file()
+            .export_wav_channels(&[SOUND, RIGHT], &mut wav, options)
+            .unwrap();
+        let wav = wav.into_inner();
+
+        assert_eq!(header(&wav), (3, 2, 48000, 48000 * 8, 8, 32, 5 * 8));
+        let mut samples = vec![0f32; 10];
+        LE::read_f32_into(&wav[44..], &mut samples);
+        assert_eq!(
+            &samples[..4],
+            [0.0, 1.0 / 32768.0, 100.0 / 32768.0, 2.0 / 32768.0]
+        );
+        assert_eq!(samples[8], -1.0);
+    }
+
+    #[test]
+    fn channels_without_audio_fail_to_export() {
+        let file = file();
+        let mut wav = Cursor::new(Vec::new());
+        assert!(matches!(
+            file.export_wav(NOTES, &mut wav, WavOptions::new().sample_rate(Some(100))),
+            Err(TdmsError::UnsupportedExportType { .. })
+        ));
+        // Without wf_increment there's no sample rate
+        assert!(matches!(
+            file.export_wav(RIGHT, &mut wav, WavOptions::new()),
+            Err(TdmsError::MissingProperty { .. })
+        ));
+    }
+}
@@ -0,0 +1,407 @@
+//! Synthetic TDMS files for tests and benchmarks, enabled by the `test_util` feature.
+//!
+//! `TdmsBuilder` writes files segment by segment from `SegmentBuilder`s, which can also produce
+//! deliberately malformed lead ins to exercise error handling, e.g.
+//! `TdmsBuilder::new().segment(SegmentBuilder::new().channel("/'group'/'channel'", data)).build()`.
+
+use byteorder::{ByteOrder, WriteBytesExt, BE, LE};
+
+use crate::tdms_datatypes::{
+    write_data_vector, write_datatype, write_string, DataType, DataTypeRaw, DataTypeVec, TimeStamp,
+    TocProperties,
+};
+use crate::tdms_path::object_path;
+use crate::{DATA_INDEX_MATCHES_PREVIOUS, NO_RAW_DATA, TDMS_VERSION_2};
+
+/// Builds a file from segments, in the order they're added
+#[derive(Debug, Clone, Default)]
+pub struct TdmsBuilder {
+    segments: Vec<SegmentBuilder>,
+}
+
+impl TdmsBuilder {
+    pub fn new() -> TdmsBuilder {
+        TdmsBuilder::default()
+    }
+
+    pub fn segment(mut self, segment: SegmentBuilder) -> Self {
+        self.segments.push(segment);
+        self
+    }
+
+    /// The bytes of the file, readable with `TdmsFile::new(Cursor::new(bytes))`
+    pub fn build(&self) -> Vec<u8> {
+        let mut file = Vec::new();
+        for segment in &self.segments {
+            segment.write(&mut file);
+        }
+        file
+    }
+}
+
+/// How an object's raw data index is written
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RawIndex {
+    NoRawData,
+    MatchesPrevious,
+    New,
+}
+
+#[derive(Debug, Clone)]
+struct SegmentObject {
+    path: String,
+    index: RawIndex,
+    data: Option<DataTypeVec>,
+    dimension: u32,
+    properties: Vec<(String, DataType)>,
+}
+
+/// One segment of a file. By default it's a little endian, contiguous, version 4713 segment
+/// with a new object list, holding one chunk of raw data. The lead in is computed from the
+/// contents unless overridden.
+#[derive(Debug, Clone)]
+pub struct SegmentBuilder {
+    objects: Vec<SegmentObject>,
+    meta_data: bool,
+    new_obj_list: bool,
+    interleaved: bool,
+    big_endian: bool,
+    no_chunks: u64,
+    tag: [u8; 4],
+    toc: Option<u32>,
+    version: u32,
+    next_segment_offset: Option<u64>,
+    raw_data_offset: Option<u64>,
+}
+
+impl Default for SegmentBuilder {
+    fn default() -> Self {
+        SegmentBuilder {
+            objects: Vec::new(),
+            meta_data: true,
+            new_obj_list: true,
+            interleaved: false,
+            big_endian: false,
+            no_chunks: 1,
+            tag: *b"TDSm",
+            toc: None,
+            version: TDMS_VERSION_2,
+            next_segment_offset: None,
+            raw_data_offset: None,
+        }
+    }
+}
+
+impl SegmentBuilder {
+    pub fn new() -> SegmentBuilder {
+        SegmentBuilder::default()
+    }
+
+    /// Add an object without raw data, e.g. the root "/" or a group
+    pub fn object(self, path: &str) -> Self {
+        self.push_object(path, RawIndex::NoRawData, None)
+    }
+
+    /// Add a channel with a new raw data index describing the data
+    pub fn channel(self, path: &str, data: DataTypeVec) -> Self {
+        self.push_object(path, RawIndex::New, Some(data))
+    }
+
+    /// Add a channel whose raw data index is "matches previous", the data must have the same
+    /// type and length as in the channel's last index
+    pub fn channel_matching_previous(self, path: &str, data: DataTypeVec) -> Self {
+        self.push_object(path, RawIndex::MatchesPrevious, Some(data))
+    }
+
+    /// Add a property to the most recently added object
+    pub fn property(mut self, name: &str, value: DataType) -> Self {
+        self.objects
+            .last_mut()
+            .expect("properties need an object to belong to")
+            .properties
+            .push((name.to_string(), value));
+        self
+    }
+
+    /// Give the most recently added channel's raw data this dimension, so its data is read as
+    /// dimension values per raw value. The data's length must be a multiple of it.
+    pub fn dimension(mut self, dimension: u32) -> Self {
+        self.objects
+            .last_mut()
+            .expect("a dimension needs a channel to belong to")
+            .dimension = dimension;
+        self
+    }
+
+    /// Whether meta data is written. Without it the segment is raw data only, laid out like
+    /// the previous segment, and its channels just supply the values.
+    pub fn meta_data(mut self, meta_data: bool) -> Self {
+        self.meta_data = meta_data;
+        self
+    }
+
+    /// Whether the new object list flag is set, without it objects are added to the previous
+    /// segment's list. Only set on segments with meta data. Raw data is only written for the
+    /// channels added here, so those carried over with data must be added again.
+    pub fn new_obj_list(mut self, new_obj_list: bool) -> Self {
+        self.new_obj_list = new_obj_list;
+        self
+    }
+
+    /// Interleave the channels' values, all channels must have fixed size types and the same
+    /// number of values
+    pub fn interleaved(mut self, interleaved: bool) -> Self {
+        self.interleaved = interleaved;
+        self
+    }
+
+    /// Write everything after the tag and ToC big endian
+    pub fn big_endian(mut self, big_endian: bool) -> Self {
+        self.big_endian = big_endian;
+        self
+    }
+
+    /// Repeat the raw data this many times, as a writer appending chunks would
+    pub fn chunks(mut self, no_chunks: u64) -> Self {
+        self.no_chunks = no_chunks;
+        self
+    }
+
+    /// Write this tag rather than "TDSm"
+    pub fn tag(mut self, tag: [u8; 4]) -> Self {
+        self.tag = tag;
+        self
+    }
+
+    /// Write these ToC flags rather than ones matching the contents
+    pub fn toc(mut self, toc: Option<u32>) -> Self {
+        self.toc = toc;
+        self
+    }
+
+    pub fn version(mut self, version: u32) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Write this next segment offset rather than the segment's real length
+    pub fn next_segment_offset(mut self, offset: Option<u64>) -> Self {
+        self.next_segment_offset = offset;
+        self
+    }
+
+    /// Write this raw data offset rather than the meta data's real length
+    pub fn raw_data_offset(mut self, offset: Option<u64>) -> Self {
+        self.raw_data_offset = offset;
+        self
+    }
+
+    fn push_object(mut self, path: &str, index: RawIndex, data: Option<DataTypeVec>) -> Self {
+        self.objects.push(SegmentObject {
+            path: path.to_string(),
+            index,
+            data,
+            dimension: 1,
+            properties: Vec::new(),
+        });
+        self
+    }
+
+    fn toc_flags(&self) -> u32 {
+        let flags = [
+            (self.meta_data, TocProperties::KTocMetaData),
+            (
+                self.meta_data && self.new_obj_list,
+                TocProperties::KTocNewObjList,
+            ),
+            (self.has_raw_data(), TocProperties::KTocRawData),
+            (self.interleaved, TocProperties::KTocInterleavedData),
+            (self.big_endian, TocProperties::KTocBigEndian),
+        ];
+        flags
+            .into_iter()
+            .filter(|(set, _)| *set)
+            .fold(0, |toc, (_, flag)| toc | u32::from(flag))
+    }
+
+    fn has_raw_data(&self) -> bool {
+        self.objects.iter().any(|object| object.data.is_some())
+    }
+
+    fn write(&self, file: &mut Vec<u8>) {
+        if self.big_endian {
+            self.write_ordered::<BE>(file)
+        } else {
+            self.write_ordered::<LE>(file)
+        }
+    }
+
+    fn write_ordered<O: ByteOrder>(&self, file: &mut Vec<u8>) {
+        let meta_data = if self.meta_data {
+            self.meta_data_bytes::<O>()
+        } else {
+            Vec::new()
+        };
+        let raw_data = self.raw_data_bytes::<O>().repeat(self.no_chunks as usize);
+
+        // The tag and ToC are always little endian, the rest of the lead in follows the ToC
+        file.extend_from_slice(&self.tag);
+        file.write_u32::<LE>(self.toc.unwrap_or_else(|| self.toc_flags()))
+            .unwrap();
+        file.write_u32::<O>(self.version).unwrap();
+        let segment_length = (meta_data.len() + raw_data.len()) as u64;
+        file.write_u64::<O>(self.next_segment_offset.unwrap_or(segment_length))
+            .unwrap();
+        file.write_u64::<O>(self.raw_data_offset.unwrap_or(meta_data.len() as u64))
+            .unwrap();
+        file.extend_from_slice(&meta_data);
+        file.extend_from_slice(&raw_data);
+    }
+
+    fn meta_data_bytes<O: ByteOrder>(&self) -> Vec<u8> {
+        let mut meta_data = Vec::new();
+        meta_data.write_u32::<O>(self.objects.len() as u32).unwrap();
+        for object in &self.objects {
+            write_string::<_, O>(&mut meta_data, &object.path).unwrap();
+            match (object.index, &object.data) {
+                (RawIndex::New, Some(data)) => {
+                    let data_type = data.data_type();
+                    if data_type == DataTypeRaw::TdmsString {
+                        meta_data.write_u32::<O>(28).unwrap();
+                    } else {
+                        meta_data.write_u32::<O>(20).unwrap();
+                    }
+                    meta_data.write_u32::<O>(data_type as u32).unwrap();
+                    meta_data.write_u32::<O>(object.dimension).unwrap();
+                    let no_values = data.len() as u64 / object.dimension as u64;
+                    meta_data.write_u64::<O>(no_values).unwrap();
+                    if data_type == DataTypeRaw::TdmsString {
+                        meta_data.write_u64::<O>(data.raw_size()).unwrap();
+                    }
+                }
+                (RawIndex::MatchesPrevious, _) => meta_data
+                    .write_u32::<O>(DATA_INDEX_MATCHES_PREVIOUS)
+                    .unwrap(),
+                _ => meta_data.write_u32::<O>(NO_RAW_DATA).unwrap(),
+            }
+
+            meta_data
+                .write_u32::<O>(object.properties.len() as u32)
+                .unwrap();
+            for (name, value) in &object.properties {
+                write_string::<_, O>(&mut meta_data, name).unwrap();
+                let type_id = match value {
+                    DataType::Unknown { type_id, .. } => *type_id,
+                    value => value.data_type() as u32,
+                };
+                meta_data.write_u32::<O>(type_id).unwrap();
+                write_datatype::<_, O>(&mut meta_data, value).unwrap();
+            }
+        }
+        meta_data
+    }
+
+    fn raw_data_bytes<O: ByteOrder>(&self) -> Vec<u8> {
+        let channels: Vec<&DataTypeVec> = self
+            .objects
+            .iter()
+            .filter_map(|object| object.data.as_ref())
+            .collect();
+        let raw_data: Vec<Vec<u8>> = channels
+            .iter()
+            .map(|data| {
+                let mut bytes = Vec::new();
+                write_data_vector::<_, O>(&mut bytes, data).unwrap();
+                bytes
+            })
+            .collect();
+
+        if self.interleaved {
+            interleave(&channels, &raw_data)
+        } else {
+            raw_data.concat()
+        }
+    }
+}
+
+/// Interleave the channels' raw data value by value
+fn interleave(channels: &[&DataTypeVec], raw_data: &[Vec<u8>]) -> Vec<u8> {
+    let sizes: Vec<usize> = channels
+        .iter()
+        .map(|data| data.data_type().size().expect("can't interleave strings") as usize)
+        .collect();
+    let len = channels.first().map_or(0, |data| data.len());
+
+    let mut interleaved = Vec::with_capacity(raw_data.iter().map(Vec::len).sum());
+    for i in 0..len {
+        for (bytes, size) in raw_data.iter().zip(&sizes) {
+            interleaved.extend_from_slice(&bytes[i * size..(i + 1) * size]);
+        }
+    }
+    interleaved
+}
+
+/// Build a file of no_segments segments, each listing every channel afresh and holding
+/// values_per_segment values for each. Channels are given as (group, channel, data type) and
+/// hold a ramp continuing across segments, strings are the ramp's values formatted.
+/// Interleaving only applies to fixed size types.
+pub fn synthetic_file(
+    channels: &[(&str, &str, DataTypeRaw)],
+    no_segments: usize,
+    values_per_segment: usize,
+    interleaved: bool,
+) -> Vec<u8> {
+    let mut groups: Vec<&str> = Vec::new();
+    for (group, _, _) in channels {
+        if !groups.contains(group) {
+            groups.push(group);
+        }
+    }
+
+    let mut builder = TdmsBuilder::new();
+    for segment in 0..no_segments {
+        let start = (segment * values_per_segment) as u64;
+        let mut segment = SegmentBuilder::new().interleaved(interleaved);
+        for group in &groups {
+            segment = segment.object(&object_path(Some(group), None));
+        }
+        for (group, channel, data_type) in channels {
+            segment = segment.channel(
+                &object_path(Some(group), Some(channel)),
+                ramp(*data_type, start, values_per_segment),
+            );
+        }
+        builder = builder.segment(segment);
+    }
+    builder.build()
+}
+
+/// Values start, start + 1, ... converted to the data type
+pub fn ramp(data_type: DataTypeRaw, start: u64, len: usize) -> DataTypeVec {
+    let values = start..start + len as u64;
+    match data_type {
+        DataTypeRaw::Boolean => DataTypeVec::Boolean(values.map(|v| v % 2 == 1).collect()),
+        DataTypeRaw::I8 => DataTypeVec::I8(values.map(|v| v as i8).collect()),
+        DataTypeRaw::I16 => DataTypeVec::I16(values.map(|v| v as i16).collect()),
+        DataTypeRaw::I32 => DataTypeVec::I32(values.map(|v| v as i32).collect()),
+        DataTypeRaw::I64 => DataTypeVec::I64(values.map(|v| v as i64).collect()),
+        DataTypeRaw::U8 => DataTypeVec::U8(values.map(|v| v as u8).collect()),
+        DataTypeRaw::U16 => DataTypeVec::U16(values.map(|v| v as u16).collect()),
+        DataTypeRaw::U32 => DataTypeVec::U32(values.map(|v| v as u32).collect()),
+        DataTypeRaw::U64 => DataTypeVec::U64(values.collect()),
+        DataTypeRaw::SingleFloat => DataTypeVec::Float(values.map(|v| v as f32).collect()),
+        DataTypeRaw::DoubleFloat => DataTypeVec::Double(values.map(|v| v as f64).collect()),
+        DataTypeRaw::TdmsString => {
+            DataTypeVec::TdmsString(values.map(|v| format!("value {}", v)).collect())
+        }
+        DataTypeRaw::TimeStamp => DataTypeVec::TimeStamp(
+            values
+                .map(|v| TimeStamp {
+                    epoch: v as i64,
+                    radix: 0,
+                })
+                .collect(),
+        ),
+        other => panic!("can't generate {:?} data", other),
+    }
+}